@@ -29,6 +29,9 @@ struct StaticImpl {
     cppc: Option<Member>,
     nacl: Option<Member>,
     sta: Option<Member>,
+    sse: Option<Member>,
+    fwft: Option<Member>,
+    dbtr: Option<Member>,
     env_info: Option<Member>,
 }
 
@@ -50,6 +53,9 @@ impl StaticImpl {
             "cppc" => (true, self.cppc.replace(member)),
             "nacl" => (true, self.nacl.replace(member)),
             "sta" => (true, self.sta.replace(member)),
+            "sse" => (true, self.sse.replace(member)),
+            "fwft" => (true, self.fwft.replace(member)),
+            "dbtr" => (true, self.dbtr.replace(member)),
             "info" | "env_info" => (true, self.env_info.replace(member)),
             _ => (false, None),
         }
@@ -69,6 +75,9 @@ struct DynamicImpl {
     cppc: Vec<Member>,
     nacl: Vec<Member>,
     sta: Vec<Member>,
+    sse: Vec<Member>,
+    fwft: Vec<Member>,
+    dbtr: Vec<Member>,
     env_info: Option<Member>,
 }
 
@@ -86,6 +95,9 @@ impl DynamicImpl {
             "cppc" => self.cppc.push(member),
             "nacl" => self.nacl.push(member),
             "sta" => self.sta.push(member),
+            "sse" => self.sse.push(member),
+            "fwft" => self.fwft.push(member),
+            "dbtr" => self.dbtr.push(member),
             "info" | "env_info" => return self.env_info.replace(member).is_none(),
             _ => return false,
         }
@@ -244,6 +256,9 @@ fn impl_derive_rustsbi_static(name: &Ident, imp: StaticImpl, generics: &Generics
     let cppc_probe: usize = if imp.cppc.is_some() { 1 } else { 0 };
     let nacl_probe: usize = if imp.nacl.is_some() { 1 } else { 0 };
     let sta_probe: usize = if imp.sta.is_some() { 1 } else { 0 };
+    let sse_probe: usize = if imp.sse.is_some() { 1 } else { 0 };
+    let fwft_probe: usize = if imp.fwft.is_some() { 1 } else { 0 };
+    let dbtr_probe: usize = if imp.dbtr.is_some() { 1 } else { 0 };
     let probe = quote! {
         ::rustsbi::_StandardExtensionProbe {
             base: #base_probe,
@@ -258,6 +273,9 @@ fn impl_derive_rustsbi_static(name: &Ident, imp: StaticImpl, generics: &Generics
             cppc: #cppc_probe,
             nacl: #nacl_probe,
             sta: #sta_probe,
+            sse: #sse_probe,
+            fwft: #fwft_probe,
+            dbtr: #dbtr_probe,
         }
     };
     let mut match_arms = quote! {};
@@ -337,6 +355,21 @@ fn impl_derive_rustsbi_static(name: &Ident, imp: StaticImpl, generics: &Generics
             ::rustsbi::spec::sta::EID_STA => ::rustsbi::_rustsbi_sta(&self.#sta, param, function),
         })
     }
+    if let Some(sse) = &imp.sse {
+        match_arms.extend(quote! {
+            ::rustsbi::spec::sse::EID_SSE => ::rustsbi::_rustsbi_sse(&self.#sse, param, function),
+        })
+    }
+    if let Some(fwft) = &imp.fwft {
+        match_arms.extend(quote! {
+            ::rustsbi::spec::fwft::EID_FWFT => ::rustsbi::_rustsbi_fwft(&self.#fwft, param, function),
+        })
+    }
+    if let Some(dbtr) = &imp.dbtr {
+        match_arms.extend(quote! {
+            ::rustsbi::spec::dbtr::EID_DBTR => ::rustsbi::_rustsbi_dbtr(&self.#dbtr, param, function),
+        })
+    }
     let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
     let generated = quote! {
     impl #impl_generics ::rustsbi::RustSBI for #name #ty_generics #where_clause {
@@ -518,6 +551,51 @@ fn impl_derive_rustsbi_dynamic(name: &Ident, imp: DynamicImpl, generics: &Generi
             }
         });
     }
+    let mut sse_contents = quote! {};
+    let mut prober_sse = quote! {};
+    for sse in &imp.sse {
+        sse_contents.extend(quote! {
+            if ::rustsbi::_rustsbi_sse_probe(&self.#sse) != ::rustsbi::spec::base::UNAVAILABLE_EXTENSION {
+                return ::rustsbi::_rustsbi_sse(&self.#sse, param, function)
+            }
+        });
+        prober_sse.extend(quote! {
+            let value = ::rustsbi::_rustsbi_sse_probe(&self.0.#sse);
+            if value != ::rustsbi::spec::base::UNAVAILABLE_EXTENSION {
+                return value
+            }
+        });
+    }
+    let mut fwft_contents = quote! {};
+    let mut prober_fwft = quote! {};
+    for fwft in &imp.fwft {
+        fwft_contents.extend(quote! {
+            if ::rustsbi::_rustsbi_fwft_probe(&self.#fwft) != ::rustsbi::spec::base::UNAVAILABLE_EXTENSION {
+                return ::rustsbi::_rustsbi_fwft(&self.#fwft, param, function)
+            }
+        });
+        prober_fwft.extend(quote! {
+            let value = ::rustsbi::_rustsbi_fwft_probe(&self.0.#fwft);
+            if value != ::rustsbi::spec::base::UNAVAILABLE_EXTENSION {
+                return value
+            }
+        });
+    }
+    let mut dbtr_contents = quote! {};
+    let mut prober_dbtr = quote! {};
+    for dbtr in &imp.dbtr {
+        dbtr_contents.extend(quote! {
+            if ::rustsbi::_rustsbi_dbtr_probe(&self.#dbtr) != ::rustsbi::spec::base::UNAVAILABLE_EXTENSION {
+                return ::rustsbi::_rustsbi_dbtr(&self.#dbtr, param, function)
+            }
+        });
+        prober_dbtr.extend(quote! {
+            let value = ::rustsbi::_rustsbi_dbtr_probe(&self.0.#dbtr);
+            if value != ::rustsbi::spec::base::UNAVAILABLE_EXTENSION {
+                return value
+            }
+        });
+    }
 
     let (_, origin_ty_generics, _) = generics.split_for_impl();
     let prober_generics = {
@@ -546,7 +624,10 @@ fn impl_derive_rustsbi_dynamic(name: &Ident, imp: DynamicImpl, generics: &Generi
                     ::rustsbi::spec::susp::EID_SUSP => { #prober_susp ::rustsbi::spec::base::UNAVAILABLE_EXTENSION },
                     ::rustsbi::spec::cppc::EID_CPPC => { #prober_cppc ::rustsbi::spec::base::UNAVAILABLE_EXTENSION },
                     ::rustsbi::spec::nacl::EID_NACL => { #prober_nacl ::rustsbi::spec::base::UNAVAILABLE_EXTENSION },
-                    ::rustsbi::spec::sta::EID_STA => { #prober_sta ::rustsbi::spec::base::UNAVAILABLE_EXTENSION}
+                    ::rustsbi::spec::sta::EID_STA => { #prober_sta ::rustsbi::spec::base::UNAVAILABLE_EXTENSION},
+                    ::rustsbi::spec::sse::EID_SSE => { #prober_sse ::rustsbi::spec::base::UNAVAILABLE_EXTENSION},
+                    ::rustsbi::spec::fwft::EID_FWFT => { #prober_fwft ::rustsbi::spec::base::UNAVAILABLE_EXTENSION},
+                    ::rustsbi::spec::dbtr::EID_DBTR => { #prober_dbtr ::rustsbi::spec::base::UNAVAILABLE_EXTENSION},
                     _ => ::rustsbi::spec::base::UNAVAILABLE_EXTENSION,
                 }
             }
@@ -589,6 +670,9 @@ fn impl_derive_rustsbi_dynamic(name: &Ident, imp: DynamicImpl, generics: &Generi
                     ::rustsbi::spec::cppc::EID_CPPC => { #cppc_contents ::rustsbi::SbiRet::not_supported() },
                     ::rustsbi::spec::nacl::EID_NACL => { #nacl_contents ::rustsbi::SbiRet::not_supported() },
                     ::rustsbi::spec::sta::EID_STA => { #sta_contents ::rustsbi::SbiRet::not_supported() },
+                    ::rustsbi::spec::sse::EID_SSE => { #sse_contents ::rustsbi::SbiRet::not_supported() },
+                    ::rustsbi::spec::fwft::EID_FWFT => { #fwft_contents ::rustsbi::SbiRet::not_supported() },
+                    ::rustsbi::spec::dbtr::EID_DBTR => { #dbtr_contents ::rustsbi::SbiRet::not_supported() },
                     ::rustsbi::spec::base::EID_BASE => {
                         #define_prober
                         let prober = _Prober(&self);
@@ -0,0 +1,224 @@
+use sbi_spec::binary::{SbiRet, SharedPtr, TriggerMask};
+
+/// Debug Triggers extension.
+///
+/// The RISC-V Sdtrig extension allows machine-mode software to directly configure debug
+/// triggers, but the triggers are only accessible to machine mode. The SBI Debug Triggers
+/// (DBTR) extension provides supervisor software with an abstraction over these triggers,
+/// so that native debuggers in operating systems and hypervisors can use them.
+///
+/// Each hart has a fixed number of debug triggers `trig_max`. Each debug trigger is assigned
+/// a logical index `trig_idx` by the SBI implementation where `-1 < trig_idx < trig_max`.
+/// Trigger configurations are exchanged through per-hart shared memory, in entries of
+/// four XLEN-bit words each.
+pub trait Dbtr {
+    /// Get the number of debug triggers on the calling hart which can support the trigger
+    /// configuration specified by `trig_tdata1` parameter.
+    ///
+    /// This function always returns `SbiRet::success()` in `SbiRet.error`. It will return
+    /// `trig_max` in `SbiRet.value` when `trig_tdata1 == 0`, otherwise it will return the
+    /// number of matching debug triggers in `SbiRet.value`.
+    fn num_triggers(&self, trig_tdata1: usize) -> SbiRet;
+    /// Set and enable the shared memory for debug trigger configuration on the calling hart.
+    ///
+    /// If physical address of `shmem` is not all-ones bitwise, then `shmem` specifies the
+    /// physical base address of the shared memory, which MUST be `XLEN / 8` bytes aligned
+    /// and is assumed to be `trig_max * (XLEN / 2)` bytes in size.
+    ///
+    /// If physical address of `shmem` is all-ones bitwise, then shared memory for debug
+    /// trigger configuration is disabled.
+    ///
+    /// The `flags` parameter is reserved for future use and MUST be zero.
+    ///
+    /// # Return value
+    ///
+    /// | Return code                 | Description
+    /// |:----------------------------|:----------------------------------------------
+    /// | `SbiRet::success()`         | Shared memory was set or cleared successfully.
+    /// | `SbiRet::invalid_param()`   | The `flags` parameter is not zero or the `shmem` parameter is not `XLEN / 8` bytes aligned.
+    /// | `SbiRet::invalid_address()` | The shared memory pointed to by the `shmem` parameter does not satisfy the requirements.
+    /// | `SbiRet::failed()`          | The request failed for unspecified or unknown other reasons.
+    fn set_shmem(&self, shmem: SharedPtr<u8>, flags: usize) -> SbiRet;
+    /// Read the debug trigger state and configuration into shared memory for a range of
+    /// debug triggers specified by the `trig_idx_base` and `trig_count` parameters on the
+    /// calling hart.
+    ///
+    /// For each debug trigger with index `trig_idx_base + i`, the words `trig_state`,
+    /// `trig_tdata1`, `trig_tdata2` and `trig_tdata3` are written at `offset = i * (XLEN / 2)`
+    /// of the shared memory.
+    ///
+    /// # Return value
+    ///
+    /// | Return code                 | Description
+    /// |:----------------------------|:----------------------------------------------
+    /// | `SbiRet::success()`         | State and configuration of triggers read successfully.
+    /// | `SbiRet::no_shmem()`        | Shared memory for debug triggers is disabled.
+    /// | `SbiRet::bad_range()`       | Either `trig_idx_base >= trig_max` or `trig_idx_base + trig_count >= trig_max`.
+    fn read_triggers(&self, trig_idx_base: usize, trig_count: usize) -> SbiRet;
+    /// Install debug triggers based on an array of `trig_count` trigger configurations in
+    /// the shared memory of the calling hart.
+    ///
+    /// The `trig_idx` assigned to each installed trigger configuration is written back in
+    /// word 0 of its entry; words 1 to 3 hold `trig_tdata1`, `trig_tdata2` and `trig_tdata3`.
+    ///
+    /// Upon success, `SbiRet.value` is set to zero. Upon failure, `SbiRet.value` is set to
+    /// the array index of the failing trigger configuration.
+    ///
+    /// # Return value
+    ///
+    /// | Return code                 | Description
+    /// |:----------------------------|:----------------------------------------------
+    /// | `SbiRet::success()`         | Triggers installed successfully.
+    /// | `SbiRet::no_shmem()`        | Shared memory for debug triggers is disabled.
+    /// | `SbiRet::bad_range()`       | `trig_count >= trig_max`.
+    /// | `SbiRet::invalid_param()`   | One of the trigger configuration words `trig_tdata1`, `trig_tdata2`, or `trig_tdata3` has an invalid value.
+    /// | `SbiRet::failed()`          | Failed to assign `trig_idx` or HW debug trigger for one of the trigger configurations.
+    /// | `SbiRet::not_supported()`   | One of the trigger configuration can't be programmed due to unimplemented optional bits in `tdata1`, `tdata2`, or `tdata3` CSRs.
+    fn install_triggers(&self, trig_count: usize) -> SbiRet;
+    /// Update already installed debug triggers based on an array of `trig_count` trigger
+    /// configurations in the shared memory of the calling hart.
+    ///
+    /// Word 0 of each entry holds the `trig_idx` of the trigger to update; words 1 to 3 hold
+    /// `trig_tdata1`, `trig_tdata2` and `trig_tdata3`.
+    ///
+    /// Upon success, `SbiRet.value` is set to zero. Upon failure, `SbiRet.value` is set to
+    /// the array index of the failing trigger configuration.
+    ///
+    /// # Return value
+    ///
+    /// | Return code                 | Description
+    /// |:----------------------------|:----------------------------------------------
+    /// | `SbiRet::success()`         | Triggers updated successfully.
+    /// | `SbiRet::no_shmem()`        | Shared memory for debug triggers is disabled.
+    /// | `SbiRet::bad_range()`       | `trig_count >= trig_max`.
+    /// | `SbiRet::invalid_param()`   | One of the trigger configuration in the shared memory has an invalid `trig_idx` (i.e. `trig_idx >= trig_max`), `trig_tdata1`, `trig_tdata2`, or `trig_tdata3`.
+    /// | `SbiRet::failed()`          | One of the trigger configurations has valid `trig_idx` but the corresponding debug trigger is not mapped to any HW debug trigger.
+    /// | `SbiRet::not_supported()`   | One of the trigger configuration can't be programmed due to unimplemented optional bits in `tdata1`, `tdata2`, or `tdata3` CSRs.
+    fn update_triggers(&self, trig_count: usize) -> SbiRet;
+    /// Uninstall a set of debug triggers specified by the `triggers` mask on the calling hart.
+    ///
+    /// # Return value
+    ///
+    /// | Return code                 | Description
+    /// |:----------------------------|:----------------------------------------------
+    /// | `SbiRet::success()`         | Triggers uninstalled successfully.
+    /// | `SbiRet::invalid_param()`   | One of the debug triggers in `triggers` is either not mapped to any HW debug trigger or has `trig_idx >= trig_max`.
+    fn uninstall_triggers(&self, triggers: TriggerMask) -> SbiRet;
+    /// Enable a set of debug triggers specified by the `triggers` mask on the calling hart.
+    ///
+    /// # Return value
+    ///
+    /// | Return code                 | Description
+    /// |:----------------------------|:----------------------------------------------
+    /// | `SbiRet::success()`         | Triggers enabled successfully.
+    /// | `SbiRet::invalid_param()`   | One of the debug triggers in `triggers` is either not mapped to any HW debug trigger or has `trig_idx >= trig_max`.
+    fn enable_triggers(&self, triggers: TriggerMask) -> SbiRet;
+    /// Disable a set of debug triggers specified by the `triggers` mask on the calling hart.
+    ///
+    /// # Return value
+    ///
+    /// | Return code                 | Description
+    /// |:----------------------------|:----------------------------------------------
+    /// | `SbiRet::success()`         | Triggers disabled successfully.
+    /// | `SbiRet::invalid_param()`   | One of the debug triggers in `triggers` is either not mapped to any HW debug trigger or has `trig_idx >= trig_max`.
+    fn disable_triggers(&self, triggers: TriggerMask) -> SbiRet;
+    /// Function internal to macros. Do not use.
+    #[doc(hidden)]
+    #[inline]
+    fn _rustsbi_probe(&self) -> usize {
+        sbi_spec::base::UNAVAILABLE_EXTENSION.wrapping_add(1)
+    }
+}
+
+impl<T: Dbtr> Dbtr for &T {
+    #[inline]
+    fn num_triggers(&self, trig_tdata1: usize) -> SbiRet {
+        T::num_triggers(self, trig_tdata1)
+    }
+    #[inline]
+    fn set_shmem(&self, shmem: SharedPtr<u8>, flags: usize) -> SbiRet {
+        T::set_shmem(self, shmem, flags)
+    }
+    #[inline]
+    fn read_triggers(&self, trig_idx_base: usize, trig_count: usize) -> SbiRet {
+        T::read_triggers(self, trig_idx_base, trig_count)
+    }
+    #[inline]
+    fn install_triggers(&self, trig_count: usize) -> SbiRet {
+        T::install_triggers(self, trig_count)
+    }
+    #[inline]
+    fn update_triggers(&self, trig_count: usize) -> SbiRet {
+        T::update_triggers(self, trig_count)
+    }
+    #[inline]
+    fn uninstall_triggers(&self, triggers: TriggerMask) -> SbiRet {
+        T::uninstall_triggers(self, triggers)
+    }
+    #[inline]
+    fn enable_triggers(&self, triggers: TriggerMask) -> SbiRet {
+        T::enable_triggers(self, triggers)
+    }
+    #[inline]
+    fn disable_triggers(&self, triggers: TriggerMask) -> SbiRet {
+        T::disable_triggers(self, triggers)
+    }
+}
+
+impl<T: Dbtr> Dbtr for Option<T> {
+    #[inline]
+    fn num_triggers(&self, trig_tdata1: usize) -> SbiRet {
+        self.as_ref().map_or(SbiRet::not_supported(), |inner| {
+            T::num_triggers(inner, trig_tdata1)
+        })
+    }
+    #[inline]
+    fn set_shmem(&self, shmem: SharedPtr<u8>, flags: usize) -> SbiRet {
+        self.as_ref().map_or(SbiRet::not_supported(), |inner| {
+            T::set_shmem(inner, shmem, flags)
+        })
+    }
+    #[inline]
+    fn read_triggers(&self, trig_idx_base: usize, trig_count: usize) -> SbiRet {
+        self.as_ref().map_or(SbiRet::not_supported(), |inner| {
+            T::read_triggers(inner, trig_idx_base, trig_count)
+        })
+    }
+    #[inline]
+    fn install_triggers(&self, trig_count: usize) -> SbiRet {
+        self.as_ref().map_or(SbiRet::not_supported(), |inner| {
+            T::install_triggers(inner, trig_count)
+        })
+    }
+    #[inline]
+    fn update_triggers(&self, trig_count: usize) -> SbiRet {
+        self.as_ref().map_or(SbiRet::not_supported(), |inner| {
+            T::update_triggers(inner, trig_count)
+        })
+    }
+    #[inline]
+    fn uninstall_triggers(&self, triggers: TriggerMask) -> SbiRet {
+        self.as_ref().map_or(SbiRet::not_supported(), |inner| {
+            T::uninstall_triggers(inner, triggers)
+        })
+    }
+    #[inline]
+    fn enable_triggers(&self, triggers: TriggerMask) -> SbiRet {
+        self.as_ref().map_or(SbiRet::not_supported(), |inner| {
+            T::enable_triggers(inner, triggers)
+        })
+    }
+    #[inline]
+    fn disable_triggers(&self, triggers: TriggerMask) -> SbiRet {
+        self.as_ref().map_or(SbiRet::not_supported(), |inner| {
+            T::disable_triggers(inner, triggers)
+        })
+    }
+    #[inline]
+    fn _rustsbi_probe(&self) -> usize {
+        match self {
+            Some(_) => sbi_spec::base::UNAVAILABLE_EXTENSION.wrapping_add(1),
+            None => sbi_spec::base::UNAVAILABLE_EXTENSION,
+        }
+    }
+}
@@ -1,6 +1,8 @@
-use crate::{Console, Cppc, EnvInfo, Fence, Hsm, Ipi, Nacl, Pmu, Reset, Sta, Susp, Timer};
+use crate::{
+    Console, Cppc, Dbtr, EnvInfo, Fence, Fwft, Hsm, Ipi, Nacl, Pmu, Reset, Sse, Sta, Susp, Timer,
+};
 use sbi_spec::{
-    binary::{CounterMask, HartMask, Physical, SbiRet, SharedPtr},
+    binary::{CounterMask, HartMask, Physical, SbiRet, SharedPtr, TriggerMask},
     nacl, pmu,
 };
 
@@ -137,6 +139,112 @@ impl Cppc for Forward {
     }
 }
 
+impl Dbtr for Forward {
+    #[inline]
+    fn num_triggers(&self, trig_tdata1: usize) -> SbiRet {
+        match () {
+            #[cfg(feature = "forward")]
+            () => SbiRet::success(sbi_rt::debug_num_triggers(trig_tdata1)),
+            #[cfg(not(feature = "forward"))]
+            () => {
+                let _ = trig_tdata1;
+                unimplemented!()
+            }
+        }
+    }
+
+    #[inline]
+    fn set_shmem(&self, shmem: SharedPtr<u8>, flags: usize) -> SbiRet {
+        match () {
+            #[cfg(feature = "forward")]
+            () => sbi_rt::debug_set_shmem(shmem, flags),
+            #[cfg(not(feature = "forward"))]
+            () => {
+                let _ = (shmem, flags);
+                unimplemented!()
+            }
+        }
+    }
+
+    #[inline]
+    fn read_triggers(&self, trig_idx_base: usize, trig_count: usize) -> SbiRet {
+        match () {
+            #[cfg(feature = "forward")]
+            () => sbi_rt::debug_read_triggers(trig_idx_base, trig_count),
+            #[cfg(not(feature = "forward"))]
+            () => {
+                let _ = (trig_idx_base, trig_count);
+                unimplemented!()
+            }
+        }
+    }
+
+    #[inline]
+    fn install_triggers(&self, trig_count: usize) -> SbiRet {
+        match () {
+            #[cfg(feature = "forward")]
+            () => sbi_rt::debug_install_triggers(trig_count),
+            #[cfg(not(feature = "forward"))]
+            () => {
+                let _ = trig_count;
+                unimplemented!()
+            }
+        }
+    }
+
+    #[inline]
+    fn update_triggers(&self, trig_count: usize) -> SbiRet {
+        match () {
+            #[cfg(feature = "forward")]
+            () => sbi_rt::debug_update_triggers(trig_count),
+            #[cfg(not(feature = "forward"))]
+            () => {
+                let _ = trig_count;
+                unimplemented!()
+            }
+        }
+    }
+
+    #[inline]
+    fn uninstall_triggers(&self, triggers: TriggerMask) -> SbiRet {
+        match () {
+            #[cfg(feature = "forward")]
+            () => sbi_rt::debug_uninstall_triggers(triggers),
+            #[cfg(not(feature = "forward"))]
+            () => {
+                let _ = triggers;
+                unimplemented!()
+            }
+        }
+    }
+
+    #[inline]
+    fn enable_triggers(&self, triggers: TriggerMask) -> SbiRet {
+        match () {
+            #[cfg(feature = "forward")]
+            () => sbi_rt::debug_enable_triggers(triggers),
+            #[cfg(not(feature = "forward"))]
+            () => {
+                let _ = triggers;
+                unimplemented!()
+            }
+        }
+    }
+
+    #[inline]
+    fn disable_triggers(&self, triggers: TriggerMask) -> SbiRet {
+        match () {
+            #[cfg(feature = "forward")]
+            () => sbi_rt::debug_disable_triggers(triggers),
+            #[cfg(not(feature = "forward"))]
+            () => {
+                let _ = triggers;
+                unimplemented!()
+            }
+        }
+    }
+}
+
 impl Fence for Forward {
     #[inline]
     fn remote_fence_i(&self, hart_mask: HartMask) -> SbiRet {
@@ -248,6 +356,34 @@ impl Fence for Forward {
     }
 }
 
+impl Fwft for Forward {
+    #[inline]
+    fn set(&self, feature: u32, value: usize, flags: usize) -> SbiRet {
+        match () {
+            #[cfg(feature = "forward")]
+            () => sbi_rt::fwft_set(feature, value, flags),
+            #[cfg(not(feature = "forward"))]
+            () => {
+                let _ = (feature, value, flags);
+                unimplemented!()
+            }
+        }
+    }
+
+    #[inline]
+    fn get(&self, feature: u32) -> SbiRet {
+        match () {
+            #[cfg(feature = "forward")]
+            () => sbi_rt::fwft_get(feature),
+            #[cfg(not(feature = "forward"))]
+            () => {
+                let _ = feature;
+                unimplemented!()
+            }
+        }
+    }
+}
+
 impl Hsm for Forward {
     #[inline]
     fn hart_start(&self, hartid: usize, start_addr: usize, opaque: usize) -> SbiRet {
@@ -528,6 +664,147 @@ impl Reset for Forward {
     }
 }
 
+impl Sse for Forward {
+    #[inline]
+    fn read_attrs(
+        &self,
+        event_id: u32,
+        base_attr_id: u32,
+        attr_count: u32,
+        output: SharedPtr<u8>,
+    ) -> SbiRet {
+        match () {
+            #[cfg(feature = "forward")]
+            () => sbi_rt::sse_read_attrs(event_id, base_attr_id, attr_count, output),
+            #[cfg(not(feature = "forward"))]
+            () => {
+                let _ = (event_id, base_attr_id, attr_count, output);
+                unimplemented!()
+            }
+        }
+    }
+
+    #[inline]
+    fn write_attrs(
+        &self,
+        event_id: u32,
+        base_attr_id: u32,
+        attr_count: u32,
+        input: SharedPtr<u8>,
+    ) -> SbiRet {
+        match () {
+            #[cfg(feature = "forward")]
+            () => sbi_rt::sse_write_attrs(event_id, base_attr_id, attr_count, input),
+            #[cfg(not(feature = "forward"))]
+            () => {
+                let _ = (event_id, base_attr_id, attr_count, input);
+                unimplemented!()
+            }
+        }
+    }
+
+    #[inline]
+    fn register(&self, event_id: u32, handler_entry_pc: usize, handler_entry_arg: usize) -> SbiRet {
+        match () {
+            #[cfg(feature = "forward")]
+            () => sbi_rt::sse_register(event_id, handler_entry_pc, handler_entry_arg),
+            #[cfg(not(feature = "forward"))]
+            () => {
+                let _ = (event_id, handler_entry_pc, handler_entry_arg);
+                unimplemented!()
+            }
+        }
+    }
+
+    #[inline]
+    fn unregister(&self, event_id: u32) -> SbiRet {
+        match () {
+            #[cfg(feature = "forward")]
+            () => sbi_rt::sse_unregister(event_id),
+            #[cfg(not(feature = "forward"))]
+            () => {
+                let _ = event_id;
+                unimplemented!()
+            }
+        }
+    }
+
+    #[inline]
+    fn enable(&self, event_id: u32) -> SbiRet {
+        match () {
+            #[cfg(feature = "forward")]
+            () => sbi_rt::sse_enable(event_id),
+            #[cfg(not(feature = "forward"))]
+            () => {
+                let _ = event_id;
+                unimplemented!()
+            }
+        }
+    }
+
+    #[inline]
+    fn disable(&self, event_id: u32) -> SbiRet {
+        match () {
+            #[cfg(feature = "forward")]
+            () => sbi_rt::sse_disable(event_id),
+            #[cfg(not(feature = "forward"))]
+            () => {
+                let _ = event_id;
+                unimplemented!()
+            }
+        }
+    }
+
+    #[inline]
+    fn complete(&self) -> SbiRet {
+        match () {
+            #[cfg(feature = "forward")]
+            () => sbi_rt::sse_complete(),
+            #[cfg(not(feature = "forward"))]
+            () => {
+                unimplemented!()
+            }
+        }
+    }
+
+    #[inline]
+    fn inject(&self, event_id: u32, hart_id: usize) -> SbiRet {
+        match () {
+            #[cfg(feature = "forward")]
+            () => sbi_rt::sse_inject(event_id, hart_id),
+            #[cfg(not(feature = "forward"))]
+            () => {
+                let _ = (event_id, hart_id);
+                unimplemented!()
+            }
+        }
+    }
+
+    #[inline]
+    fn hart_unmask(&self) -> SbiRet {
+        match () {
+            #[cfg(feature = "forward")]
+            () => sbi_rt::sse_hart_unmask(),
+            #[cfg(not(feature = "forward"))]
+            () => {
+                unimplemented!()
+            }
+        }
+    }
+
+    #[inline]
+    fn hart_mask(&self) -> SbiRet {
+        match () {
+            #[cfg(feature = "forward")]
+            () => sbi_rt::sse_hart_mask(),
+            #[cfg(not(feature = "forward"))]
+            () => {
+                unimplemented!()
+            }
+        }
+    }
+}
+
 impl Sta for Forward {
     #[inline]
     fn set_shmem(&self, shmem: SharedPtr<[u8; 64]>, flags: usize) -> SbiRet {
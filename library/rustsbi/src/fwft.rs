@@ -0,0 +1,89 @@
+use sbi_spec::binary::SbiRet;
+
+/// Firmware Features extension.
+///
+/// Some features of the RISC-V privileged architecture are only controllable from
+/// machine mode. The SBI Firmware Features (FWFT) extension allows supervisor software
+/// to request such features be enabled or disabled and to read their current
+/// configuration.
+///
+/// Feature values are per-hart; a feature set on one hart does not change the
+/// configuration of other harts. A feature may be locked on set, after which its
+/// value can no longer be modified until the hart is reset.
+pub trait Fwft {
+    /// Set the configuration value of a specific firmware feature.
+    ///
+    /// The `feature` parameter is the identifier of the feature to set, `value` is the
+    /// requested configuration value, and `flags` modifies the behavior of the set
+    /// operation. When the `LOCK` flag is provided, the feature value can no longer be
+    /// modified after a successful set.
+    ///
+    /// # Return value
+    ///
+    /// A successful return results in the requested firmware feature to be set according
+    /// to the `value` and `flags` parameters. In case of failure, `feature` value is not
+    /// modified and the possible error codes returned in `SbiRet.error` are shown in the table below:
+    ///
+    /// | Return code                 | Description
+    /// |:----------------------------|:----------------------------------------------
+    /// | `SbiRet::success()`         | `feature` was set successfully.
+    /// | `SbiRet::not_supported()`   | `feature` is not reserved and valid, but the platform does not support it due to one or more missing dependencies (Hardware or SBI implementation).
+    /// | `SbiRet::invalid_param()`   | Provided `value` or `flags` parameter is invalid.
+    /// | `SbiRet::denied()`          | `feature` set operation failed because either it was denied by the SBI implementation, or `feature` is reserved or is platform-specific and unimplemented.
+    /// | `SbiRet::denied_locked()`   | `feature` set operation failed because the `feature` is locked.
+    /// | `SbiRet::failed()`          | The set operation failed for unspecified or unknown other reasons.
+    fn set(&self, feature: u32, value: usize, flags: usize) -> SbiRet;
+    /// Get the configuration value of a specific firmware feature.
+    ///
+    /// # Return value
+    ///
+    /// A successful return results in the firmware feature configuration value to be
+    /// returned in `SbiRet.value`. In case of failure, the content of `SbiRet.value` is
+    /// zero and the possible error codes returned in `SbiRet.error` are shown in the table below:
+    ///
+    /// | Return code                 | Description
+    /// |:----------------------------|:----------------------------------------------
+    /// | `SbiRet::success()`         | Feature status was retrieved successfully.
+    /// | `SbiRet::not_supported()`   | `feature` is not reserved and valid, but the platform does not support it due to one or more missing dependencies (Hardware or SBI implementation).
+    /// | `SbiRet::denied()`          | `feature` is reserved or is platform-specific and unimplemented.
+    /// | `SbiRet::failed()`          | The get operation failed for unspecified or unknown other reasons.
+    fn get(&self, feature: u32) -> SbiRet;
+    /// Function internal to macros. Do not use.
+    #[doc(hidden)]
+    #[inline]
+    fn _rustsbi_probe(&self) -> usize {
+        sbi_spec::base::UNAVAILABLE_EXTENSION.wrapping_add(1)
+    }
+}
+
+impl<T: Fwft> Fwft for &T {
+    #[inline]
+    fn set(&self, feature: u32, value: usize, flags: usize) -> SbiRet {
+        T::set(self, feature, value, flags)
+    }
+    #[inline]
+    fn get(&self, feature: u32) -> SbiRet {
+        T::get(self, feature)
+    }
+}
+
+impl<T: Fwft> Fwft for Option<T> {
+    #[inline]
+    fn set(&self, feature: u32, value: usize, flags: usize) -> SbiRet {
+        self.as_ref().map_or(SbiRet::not_supported(), |inner| {
+            T::set(inner, feature, value, flags)
+        })
+    }
+    #[inline]
+    fn get(&self, feature: u32) -> SbiRet {
+        self.as_ref()
+            .map_or(SbiRet::not_supported(), |inner| T::get(inner, feature))
+    }
+    #[inline]
+    fn _rustsbi_probe(&self) -> usize {
+        match self {
+            Some(_) => sbi_spec::base::UNAVAILABLE_EXTENSION.wrapping_add(1),
+            None => sbi_spec::base::UNAVAILABLE_EXTENSION,
+        }
+    }
+}
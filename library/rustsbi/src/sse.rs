@@ -0,0 +1,333 @@
+use sbi_spec::binary::{SbiRet, SharedPtr};
+
+/// Supervisor Software Events extension.
+///
+/// The SBI Supervisor Software Events (SSE) extension provides a mechanism to inject
+/// software events from an SBI implementation to supervisor software such that it
+/// preempts all other traps and interrupts. The supervisor software will receive
+/// software events only on harts which are ready to receive them. A software event
+/// is delivered only after supervisor software has registered an event handler and
+/// enabled the software event.
+///
+/// The software events can be local (per-hart) or global. A local event is triggered
+/// on a specific hart; a global event is triggered on any hart in the system, chosen
+/// by the `PREFERRED_HART` event attribute.
+///
+/// When a software event is delivered, the SBI implementation saves the interrupted
+/// `sepc`, `a6`, `a7` and part of `sstatus` into the `INTERRUPTED_*` event attributes,
+/// and resumes supervisor software at the event handler entry. The event handler
+/// resumes the interrupted context with [`complete`](Sse::complete).
+pub trait Sse {
+    /// Read a range of event attribute values from a software event.
+    ///
+    /// The `event_id` parameter specifies the software event ID whereas `base_attr_id`
+    /// and `attr_count` parameters specify the range of event attribute IDs.
+    ///
+    /// The event attribute values are written to an output shared memory which is specified
+    /// by the `output` parameter where:
+    ///
+    /// - The `output` parameter MUST be `XLEN / 8` bytes aligned;
+    /// - The size of output shared memory is assumed to be `(XLEN / 8) * attr_count`;
+    /// - The value of event attribute with ID `base_attr_id + i` should be written to offset `(XLEN / 8) * i`.
+    ///
+    /// # Return value
+    ///
+    /// | Return code                 | Description
+    /// |:----------------------------|:----------------------------------------------
+    /// | `SbiRet::success()`         | Event attribute values read successfully.
+    /// | `SbiRet::not_supported()`   | `event_id` is not reserved and valid, but the platform does not support it due to one or more missing dependencies (Hardware or SBI implementation).
+    /// | `SbiRet::invalid_param()`   | `event_id` is invalid or `attr_count` is zero.
+    /// | `SbiRet::bad_range()`       | One of the event attribute IDs in the range specified by `base_attr_id` and `attr_count` is reserved.
+    /// | `SbiRet::invalid_address()` | The shared memory pointed to by the `output` parameter does not satisfy the requirements.
+    /// | `SbiRet::failed()`          | The read failed for unspecified or unknown other reasons.
+    fn read_attrs(
+        &self,
+        event_id: u32,
+        base_attr_id: u32,
+        attr_count: u32,
+        output: SharedPtr<u8>,
+    ) -> SbiRet;
+    /// Write a range of event attribute values to a software event.
+    ///
+    /// The `event_id` parameter specifies the software event ID whereas `base_attr_id`
+    /// and `attr_count` parameters specify the range of event attribute IDs.
+    ///
+    /// The event attribute values are read from an input shared memory which is specified
+    /// by the `input` parameter, with the same layout as in [`read_attrs`](Sse::read_attrs).
+    ///
+    /// For local events, the event attributes are updated only for the calling hart.
+    /// For global events, the event attributes are updated for all the harts.
+    ///
+    /// # Return value
+    ///
+    /// | Return code                 | Description
+    /// |:----------------------------|:----------------------------------------------
+    /// | `SbiRet::success()`         | Event attribute values written successfully.
+    /// | `SbiRet::not_supported()`   | `event_id` is not reserved and valid, but the platform does not support it due to one or more missing dependencies (Hardware or SBI implementation).
+    /// | `SbiRet::invalid_param()`   | `event_id` is invalid or `attr_count` is zero, or a value to be written is invalid.
+    /// | `SbiRet::bad_range()`       | One of the event attribute IDs in the range specified by `base_attr_id` and `attr_count` is reserved or is read-only.
+    /// | `SbiRet::invalid_state()`   | `event_id` is valid but the event is in a state where the attributes can't be written.
+    /// | `SbiRet::invalid_address()` | The shared memory pointed to by the `input` parameter does not satisfy the requirements.
+    /// | `SbiRet::failed()`          | The write failed for unspecified or unknown other reasons.
+    fn write_attrs(
+        &self,
+        event_id: u32,
+        base_attr_id: u32,
+        attr_count: u32,
+        input: SharedPtr<u8>,
+    ) -> SbiRet;
+    /// Register an event handler for the software event.
+    ///
+    /// The `handler_entry_pc` parameter MUST be 2-bytes aligned and specifies the `ENTRY_PC`
+    /// event attribute of the software event, whereas `handler_entry_arg` specifies the
+    /// `ENTRY_ARG` event attribute.
+    ///
+    /// For local events, the event is registered only for the calling hart.
+    /// For global events, the event is registered for all the harts.
+    ///
+    /// The event MUST be in `UNUSED` state; upon success, the event state moves to `REGISTERED`.
+    ///
+    /// # Return value
+    ///
+    /// | Return code                 | Description
+    /// |:----------------------------|:----------------------------------------------
+    /// | `SbiRet::success()`         | Event handler is registered successfully.
+    /// | `SbiRet::not_supported()`   | `event_id` is not reserved and valid, but the platform does not support it due to one or more missing dependencies (Hardware or SBI implementation).
+    /// | `SbiRet::invalid_state()`   | `event_id` is valid but the event is not in `UNUSED` state.
+    /// | `SbiRet::invalid_param()`   | `event_id` is invalid or `handler_entry_pc` is not 2-bytes aligned.
+    fn register(&self, event_id: u32, handler_entry_pc: usize, handler_entry_arg: usize) -> SbiRet;
+    /// Unregister the event handler for the software event.
+    ///
+    /// For local events, the event is unregistered only for the calling hart.
+    /// For global events, the event is unregistered for all the harts.
+    ///
+    /// The event MUST be in `REGISTERED` state; upon success, the event state moves to `UNUSED`.
+    ///
+    /// # Return value
+    ///
+    /// | Return code                 | Description
+    /// |:----------------------------|:----------------------------------------------
+    /// | `SbiRet::success()`         | Event handler is unregistered successfully.
+    /// | `SbiRet::not_supported()`   | `event_id` is not reserved and valid, but the platform does not support it due to one or more missing dependencies (Hardware or SBI implementation).
+    /// | `SbiRet::invalid_state()`   | `event_id` is valid but the event is not in `REGISTERED` state.
+    /// | `SbiRet::invalid_param()`   | `event_id` is invalid.
+    fn unregister(&self, event_id: u32) -> SbiRet;
+    /// Enable the software event.
+    ///
+    /// For local events, the event is enabled only for the calling hart.
+    /// For global events, the event is enabled for all the harts.
+    ///
+    /// The event MUST be in `REGISTERED` state; upon success, the event state moves to `ENABLED`.
+    ///
+    /// # Return value
+    ///
+    /// | Return code                 | Description
+    /// |:----------------------------|:----------------------------------------------
+    /// | `SbiRet::success()`         | Event is successfully enabled.
+    /// | `SbiRet::not_supported()`   | `event_id` is not reserved and valid, but the platform does not support it due to one or more missing dependencies (Hardware or SBI implementation).
+    /// | `SbiRet::invalid_param()`   | `event_id` is invalid.
+    /// | `SbiRet::invalid_state()`   | `event_id` is valid but the event is not in `REGISTERED` state.
+    fn enable(&self, event_id: u32) -> SbiRet;
+    /// Disable the software event.
+    ///
+    /// For local events, the event is disabled only for the calling hart.
+    /// For global events, the event is disabled for all the harts.
+    ///
+    /// The event MUST be in `ENABLED` state; upon success, the event state moves to `REGISTERED`.
+    ///
+    /// # Return value
+    ///
+    /// | Return code                 | Description
+    /// |:----------------------------|:----------------------------------------------
+    /// | `SbiRet::success()`         | Event is successfully disabled.
+    /// | `SbiRet::not_supported()`   | `event_id` is not reserved and valid, but the platform does not support it due to one or more missing dependencies (Hardware or SBI implementation).
+    /// | `SbiRet::invalid_param()`   | `event_id` is invalid.
+    /// | `SbiRet::invalid_state()`   | `event_id` is valid but the event is not in `ENABLED` state.
+    fn disable(&self, event_id: u32) -> SbiRet;
+    /// Complete the supervisor event handling for the highest priority event in `RUNNING` state on the calling hart.
+    ///
+    /// If there were no events in `RUNNING` state on the calling hart then this function does nothing
+    /// and returns `SbiRet::success()`. Otherwise, it moves the highest priority event in `RUNNING`
+    /// state to `REGISTERED` if the event is configured as one-shot, or to `ENABLED` otherwise.
+    ///
+    /// The SBI implementation then resumes the interrupted supervisor state instead of returning
+    /// to the caller; the environment call handler is responsible for it after this function returns.
+    fn complete(&self) -> SbiRet;
+    /// Inject a software event.
+    ///
+    /// For local events, the `hart_id` parameter refers to the hart on which the event is to be injected.
+    /// For global events, the `hart_id` parameter is ignored.
+    ///
+    /// An event can only be injected if it is allowed by the event attribute.
+    ///
+    /// # Return value
+    ///
+    /// | Return code                 | Description
+    /// |:----------------------------|:----------------------------------------------
+    /// | `SbiRet::success()`         | Event is successfully injected.
+    /// | `SbiRet::not_supported()`   | `event_id` is not reserved and valid, but the platform does not support it due to one or more missing dependencies (Hardware or SBI implementation).
+    /// | `SbiRet::invalid_param()`   | `event_id` is invalid or `hart_id` is invalid.
+    /// | `SbiRet::failed()`          | The injection failed for unspecified or unknown other reasons.
+    fn inject(&self, event_id: u32, hart_id: usize) -> SbiRet;
+    /// Start receiving (or unmask) software events on the calling hart.
+    ///
+    /// The software events are masked initially on all harts so the supervisor software must
+    /// explicitly unmask software events on relevant harts at boot-time.
+    ///
+    /// # Return value
+    ///
+    /// | Return code                 | Description
+    /// |:----------------------------|:----------------------------------------------
+    /// | `SbiRet::success()`         | Software events unmasked successfully on the calling hart.
+    /// | `SbiRet::already_started()` | Software events were already unmasked on the calling hart.
+    /// | `SbiRet::failed()`          | The request failed for unspecified or unknown other reasons.
+    fn hart_unmask(&self) -> SbiRet;
+    /// Stop receiving (or mask) software events on the calling hart.
+    ///
+    /// # Return value
+    ///
+    /// | Return code                 | Description
+    /// |:----------------------------|:----------------------------------------------
+    /// | `SbiRet::success()`         | Software events masked successfully on the calling hart.
+    /// | `SbiRet::already_stopped()` | Software events were already masked on the calling hart.
+    /// | `SbiRet::failed()`          | The request failed for unspecified or unknown other reasons.
+    fn hart_mask(&self) -> SbiRet;
+    /// Function internal to macros. Do not use.
+    #[doc(hidden)]
+    #[inline]
+    fn _rustsbi_probe(&self) -> usize {
+        sbi_spec::base::UNAVAILABLE_EXTENSION.wrapping_add(1)
+    }
+}
+
+impl<T: Sse> Sse for &T {
+    #[inline]
+    fn read_attrs(
+        &self,
+        event_id: u32,
+        base_attr_id: u32,
+        attr_count: u32,
+        output: SharedPtr<u8>,
+    ) -> SbiRet {
+        T::read_attrs(self, event_id, base_attr_id, attr_count, output)
+    }
+    #[inline]
+    fn write_attrs(
+        &self,
+        event_id: u32,
+        base_attr_id: u32,
+        attr_count: u32,
+        input: SharedPtr<u8>,
+    ) -> SbiRet {
+        T::write_attrs(self, event_id, base_attr_id, attr_count, input)
+    }
+    #[inline]
+    fn register(&self, event_id: u32, handler_entry_pc: usize, handler_entry_arg: usize) -> SbiRet {
+        T::register(self, event_id, handler_entry_pc, handler_entry_arg)
+    }
+    #[inline]
+    fn unregister(&self, event_id: u32) -> SbiRet {
+        T::unregister(self, event_id)
+    }
+    #[inline]
+    fn enable(&self, event_id: u32) -> SbiRet {
+        T::enable(self, event_id)
+    }
+    #[inline]
+    fn disable(&self, event_id: u32) -> SbiRet {
+        T::disable(self, event_id)
+    }
+    #[inline]
+    fn complete(&self) -> SbiRet {
+        T::complete(self)
+    }
+    #[inline]
+    fn inject(&self, event_id: u32, hart_id: usize) -> SbiRet {
+        T::inject(self, event_id, hart_id)
+    }
+    #[inline]
+    fn hart_unmask(&self) -> SbiRet {
+        T::hart_unmask(self)
+    }
+    #[inline]
+    fn hart_mask(&self) -> SbiRet {
+        T::hart_mask(self)
+    }
+}
+
+impl<T: Sse> Sse for Option<T> {
+    #[inline]
+    fn read_attrs(
+        &self,
+        event_id: u32,
+        base_attr_id: u32,
+        attr_count: u32,
+        output: SharedPtr<u8>,
+    ) -> SbiRet {
+        self.as_ref().map_or(SbiRet::not_supported(), |inner| {
+            T::read_attrs(inner, event_id, base_attr_id, attr_count, output)
+        })
+    }
+    #[inline]
+    fn write_attrs(
+        &self,
+        event_id: u32,
+        base_attr_id: u32,
+        attr_count: u32,
+        input: SharedPtr<u8>,
+    ) -> SbiRet {
+        self.as_ref().map_or(SbiRet::not_supported(), |inner| {
+            T::write_attrs(inner, event_id, base_attr_id, attr_count, input)
+        })
+    }
+    #[inline]
+    fn register(&self, event_id: u32, handler_entry_pc: usize, handler_entry_arg: usize) -> SbiRet {
+        self.as_ref().map_or(SbiRet::not_supported(), |inner| {
+            T::register(inner, event_id, handler_entry_pc, handler_entry_arg)
+        })
+    }
+    #[inline]
+    fn unregister(&self, event_id: u32) -> SbiRet {
+        self.as_ref().map_or(SbiRet::not_supported(), |inner| {
+            T::unregister(inner, event_id)
+        })
+    }
+    #[inline]
+    fn enable(&self, event_id: u32) -> SbiRet {
+        self.as_ref()
+            .map_or(SbiRet::not_supported(), |inner| T::enable(inner, event_id))
+    }
+    #[inline]
+    fn disable(&self, event_id: u32) -> SbiRet {
+        self.as_ref()
+            .map_or(SbiRet::not_supported(), |inner| T::disable(inner, event_id))
+    }
+    #[inline]
+    fn complete(&self) -> SbiRet {
+        self.as_ref()
+            .map_or(SbiRet::not_supported(), |inner| T::complete(inner))
+    }
+    #[inline]
+    fn inject(&self, event_id: u32, hart_id: usize) -> SbiRet {
+        self.as_ref().map_or(SbiRet::not_supported(), |inner| {
+            T::inject(inner, event_id, hart_id)
+        })
+    }
+    #[inline]
+    fn hart_unmask(&self) -> SbiRet {
+        self.as_ref()
+            .map_or(SbiRet::not_supported(), |inner| T::hart_unmask(inner))
+    }
+    #[inline]
+    fn hart_mask(&self) -> SbiRet {
+        self.as_ref()
+            .map_or(SbiRet::not_supported(), |inner| T::hart_mask(inner))
+    }
+    #[inline]
+    fn _rustsbi_probe(&self) -> usize {
+        match self {
+            Some(_) => sbi_spec::base::UNAVAILABLE_EXTENSION.wrapping_add(1),
+            None => sbi_spec::base::UNAVAILABLE_EXTENSION,
+        }
+    }
+}
@@ -1,6 +1,6 @@
 #[cfg(feature = "machine")]
 use riscv::register::{marchid, mimpid, mvendorid};
-use spec::binary::{HartMask, Physical, SbiRet, SharedPtr};
+use spec::binary::{HartMask, Physical, SbiRet, SharedPtr, TriggerMask};
 
 /// RustSBI environment call handler.
 pub trait RustSBI {
@@ -118,6 +118,9 @@ pub struct _StandardExtensionProbe {
     pub cppc: usize,
     pub nacl: usize,
     pub sta: usize,
+    pub sse: usize,
+    pub fwft: usize,
+    pub dbtr: usize,
     // NOTE: remember to add to `fn probe_extension` in `impl _ExtensionProbe` as well
 }
 
@@ -137,6 +140,9 @@ impl _ExtensionProbe for _StandardExtensionProbe {
             spec::cppc::EID_CPPC => self.cppc,
             spec::nacl::EID_NACL => self.nacl,
             spec::sta::EID_STA => self.sta,
+            spec::sse::EID_SSE => self.sse,
+            spec::fwft::EID_FWFT => self.fwft,
+            spec::dbtr::EID_DBTR => self.dbtr,
             _ => spec::base::UNAVAILABLE_EXTENSION,
         }
     }
@@ -393,6 +399,105 @@ pub fn _rustsbi_sta<T: crate::Sta>(sta: &T, param: [usize; 6], function: usize)
     }
 }
 
+#[doc(hidden)]
+#[inline(always)]
+pub fn _rustsbi_sse<T: crate::Sse>(sse: &T, param: [usize; 6], function: usize) -> SbiRet {
+    let [param0, param1, param2, param3, param4] =
+        [param[0], param[1], param[2], param[3], param[4]];
+    match function {
+        spec::sse::READ_ATTRS => match (
+            u32::try_from(param0),
+            u32::try_from(param1),
+            u32::try_from(param2),
+        ) {
+            (Ok(event_id), Ok(base_attr_id), Ok(attr_count)) => sse.read_attrs(
+                event_id,
+                base_attr_id,
+                attr_count,
+                SharedPtr::new(param3, param4),
+            ),
+            (_, _, _) => SbiRet::invalid_param(),
+        },
+        spec::sse::WRITE_ATTRS => match (
+            u32::try_from(param0),
+            u32::try_from(param1),
+            u32::try_from(param2),
+        ) {
+            (Ok(event_id), Ok(base_attr_id), Ok(attr_count)) => sse.write_attrs(
+                event_id,
+                base_attr_id,
+                attr_count,
+                SharedPtr::new(param3, param4),
+            ),
+            (_, _, _) => SbiRet::invalid_param(),
+        },
+        spec::sse::REGISTER => match u32::try_from(param0) {
+            Ok(event_id) => sse.register(event_id, param1, param2),
+            _ => SbiRet::invalid_param(),
+        },
+        spec::sse::UNREGISTER => match u32::try_from(param0) {
+            Ok(event_id) => sse.unregister(event_id),
+            _ => SbiRet::invalid_param(),
+        },
+        spec::sse::ENABLE => match u32::try_from(param0) {
+            Ok(event_id) => sse.enable(event_id),
+            _ => SbiRet::invalid_param(),
+        },
+        spec::sse::DISABLE => match u32::try_from(param0) {
+            Ok(event_id) => sse.disable(event_id),
+            _ => SbiRet::invalid_param(),
+        },
+        spec::sse::COMPLETE => sse.complete(),
+        spec::sse::INJECT => match u32::try_from(param0) {
+            Ok(event_id) => sse.inject(event_id, param1),
+            _ => SbiRet::invalid_param(),
+        },
+        spec::sse::HART_UNMASK => sse.hart_unmask(),
+        spec::sse::HART_MASK => sse.hart_mask(),
+        _ => SbiRet::not_supported(),
+    }
+}
+
+#[doc(hidden)]
+#[inline(always)]
+pub fn _rustsbi_fwft<T: crate::Fwft>(fwft: &T, param: [usize; 6], function: usize) -> SbiRet {
+    let [param0, param1, param2] = [param[0], param[1], param[2]];
+    match function {
+        spec::fwft::SET => match u32::try_from(param0) {
+            Ok(feature) => fwft.set(feature, param1, param2),
+            _ => SbiRet::invalid_param(),
+        },
+        spec::fwft::GET => match u32::try_from(param0) {
+            Ok(feature) => fwft.get(feature),
+            _ => SbiRet::invalid_param(),
+        },
+        _ => SbiRet::not_supported(),
+    }
+}
+
+#[doc(hidden)]
+#[inline(always)]
+pub fn _rustsbi_dbtr<T: crate::Dbtr>(dbtr: &T, param: [usize; 6], function: usize) -> SbiRet {
+    let [param0, param1, param2] = [param[0], param[1], param[2]];
+    match function {
+        spec::dbtr::NUM_TRIGGERS => dbtr.num_triggers(param0),
+        spec::dbtr::SET_SHMEM => dbtr.set_shmem(SharedPtr::new(param0, param1), param2),
+        spec::dbtr::READ_TRIGGERS => dbtr.read_triggers(param0, param1),
+        spec::dbtr::INSTALL_TRIGGERS => dbtr.install_triggers(param0),
+        spec::dbtr::UPDATE_TRIGGERS => dbtr.update_triggers(param0),
+        spec::dbtr::UNINSTALL_TRIGGERS => {
+            dbtr.uninstall_triggers(TriggerMask::from_mask_base(param1, param0))
+        }
+        spec::dbtr::ENABLE_TRIGGERS => {
+            dbtr.enable_triggers(TriggerMask::from_mask_base(param1, param0))
+        }
+        spec::dbtr::DISABLE_TRIGGERS => {
+            dbtr.disable_triggers(TriggerMask::from_mask_base(param1, param0))
+        }
+        _ => SbiRet::not_supported(),
+    }
+}
+
 #[cfg(target_pointer_width = "32")]
 #[inline]
 const fn concat_u32(h: usize, l: usize) -> u64 {
@@ -464,3 +569,21 @@ pub fn _rustsbi_nacl_probe<T: crate::Nacl>(nacl: &T) -> usize {
 pub fn _rustsbi_sta_probe<T: crate::Sta>(sta: &T) -> usize {
     sta._rustsbi_probe()
 }
+
+#[doc(hidden)]
+#[inline(always)]
+pub fn _rustsbi_sse_probe<T: crate::Sse>(sse: &T) -> usize {
+    sse._rustsbi_probe()
+}
+
+#[doc(hidden)]
+#[inline(always)]
+pub fn _rustsbi_fwft_probe<T: crate::Fwft>(fwft: &T) -> usize {
+    fwft._rustsbi_probe()
+}
+
+#[doc(hidden)]
+#[inline(always)]
+pub fn _rustsbi_dbtr_probe<T: crate::Dbtr>(dbtr: &T) -> usize {
+    dbtr._rustsbi_probe()
+}
@@ -1,7 +1,7 @@
 use core::cell::RefCell;
 use rustsbi::RustSBI;
 use sbi_spec::{
-    binary::{HartMask, Physical, SbiRet, SharedPtr},
+    binary::{HartMask, Physical, SbiRet, SharedPtr, TriggerMask},
     nacl::shmem_size::NATIVE,
     pmu::shmem_size::SIZE,
 };
@@ -10,12 +10,15 @@ use sbi_spec::{
 struct FullyImplemented {
     console: DummyConsole,
     cppc: DummyCppc,
+    dbtr: DummyDbtr,
     hsm: DummyHsm,
     ipi: DummyIpi,
     nacl: DummyNacl,
     pmu: DummyPmu,
     reset: DummyReset,
     fence: DummyFence,
+    fwft: DummyFwft,
+    sse: DummySse,
     sta: DummySta,
     susp: DummySusp,
     timer: DummyTimer,
@@ -26,12 +29,15 @@ struct FullyImplemented {
 struct AlternateName {
     dbcn: DummyConsole,
     cppc: DummyCppc,
+    dbtr: DummyDbtr,
     hsm: DummyHsm,
     ipi: DummyIpi,
     nacl: DummyNacl,
     pmu: DummyPmu,
     srst: DummyReset,
     rfnc: DummyFence,
+    fwft: DummyFwft,
+    sse: DummySse,
     sta: DummySta,
     susp: DummySusp,
     time: DummyTimer,
@@ -42,12 +48,15 @@ struct AlternateName {
 struct TupleStruct(
     #[rustsbi(dbcn)] DummyConsole,
     #[rustsbi(cppc)] DummyCppc,
+    #[rustsbi(dbtr)] DummyDbtr,
     #[rustsbi(hsm)] DummyHsm,
     #[rustsbi(ipi)] DummyIpi,
     #[rustsbi(nacl)] DummyNacl,
     #[rustsbi(pmu)] DummyPmu,
     #[rustsbi(srst)] DummyReset,
     #[rustsbi(rfnc)] DummyFence,
+    #[rustsbi(fwft)] DummyFwft,
+    #[rustsbi(sse)] DummySse,
     #[rustsbi(sta)] DummySta,
     #[rustsbi(susp)] DummySusp,
     #[rustsbi(time)] DummyTimer,
@@ -63,12 +72,15 @@ fn rustsbi_impl_id() {
     let sbi = FullyImplemented {
         console: DummyConsole,
         cppc: DummyCppc,
+        dbtr: DummyDbtr,
         hsm: DummyHsm,
         ipi: DummyIpi,
         nacl: DummyNacl,
         pmu: DummyPmu,
         reset: DummyReset,
         fence: DummyFence,
+        fwft: DummyFwft,
+        sse: DummySse,
         sta: DummySta,
         susp: DummySusp,
         timer: DummyTimer(RefCell::new(0)),
@@ -78,12 +90,15 @@ fn rustsbi_impl_id() {
     let sbi = AlternateName {
         dbcn: DummyConsole,
         cppc: DummyCppc,
+        dbtr: DummyDbtr,
         hsm: DummyHsm,
         ipi: DummyIpi,
         nacl: DummyNacl,
         pmu: DummyPmu,
         srst: DummyReset,
         rfnc: DummyFence,
+        fwft: DummyFwft,
+        sse: DummySse,
         sta: DummySta,
         susp: DummySusp,
         time: DummyTimer(RefCell::new(0)),
@@ -93,12 +108,15 @@ fn rustsbi_impl_id() {
     let sbi = TupleStruct(
         DummyConsole,
         DummyCppc,
+        DummyDbtr,
         DummyHsm,
         DummyIpi,
         DummyNacl,
         DummyPmu,
         DummyReset,
         DummyFence,
+        DummyFwft,
+        DummySse,
         DummySta,
         DummySusp,
         DummyTimer(RefCell::new(0)),
@@ -119,12 +137,15 @@ fn generated_extensions() {
     let sbi = FullyImplemented {
         console: DummyConsole,
         cppc: DummyCppc,
+        dbtr: DummyDbtr,
         hsm: DummyHsm,
         ipi: DummyIpi,
         nacl: DummyNacl,
         pmu: DummyPmu,
         reset: DummyReset,
         fence: DummyFence,
+        fwft: DummyFwft,
+        sse: DummySse,
         sta: DummySta,
         susp: DummySusp,
         timer: DummyTimer(RefCell::new(0)),
@@ -139,7 +160,7 @@ fn generated_extensions() {
     // All SBI 2.0 extensions, including Base, are supported
     for eid in [
         0x10, 0x54494d45, 0x735049, 0x52464e43, 0x48534d, 0x53525354, 0x504d55, 0x4442434e,
-        0x53555350, 0x4e41434c, 0x535441, 0x43505043,
+        0x53555350, 0x4e41434c, 0x535441, 0x43505043, 0x535345, 0x46574654, 0x44425452,
     ] {
         assert_eq!(
             sbi.handle_ecall(0x10, 3, [eid, 0, 0, 0, 0, 0]),
@@ -194,6 +215,20 @@ fn generated_extensions() {
     assert_eq!(sbi.handle_ecall(0x10, 4, [0; 6]), SbiRet::success(37));
     assert_eq!(sbi.handle_ecall(0x10, 5, [0; 6]), SbiRet::success(38));
     assert_eq!(sbi.handle_ecall(0x10, 6, [0; 6]), SbiRet::success(39));
+    for fid in 0..=9 {
+        assert_eq!(
+            sbi.handle_ecall(0x535345, fid, [0; 6]),
+            SbiRet::success(40 + fid)
+        );
+    }
+    assert_eq!(sbi.handle_ecall(0x46574654, 0, [0; 6]), SbiRet::success(50));
+    assert_eq!(sbi.handle_ecall(0x46574654, 1, [0; 6]), SbiRet::success(51));
+    for fid in 0..=7 {
+        assert_eq!(
+            sbi.handle_ecall(0x44425452, fid, [0; 6]),
+            SbiRet::success(52 + fid)
+        );
+    }
 }
 
 struct DummyConsole;
@@ -360,6 +395,98 @@ impl rustsbi::Fence for DummyFence {
     }
 }
 
+struct DummyDbtr;
+
+impl rustsbi::Dbtr for DummyDbtr {
+    fn num_triggers(&self, _: usize) -> SbiRet {
+        SbiRet::success(52)
+    }
+
+    fn set_shmem(&self, _: SharedPtr<u8>, _: usize) -> SbiRet {
+        SbiRet::success(53)
+    }
+
+    fn read_triggers(&self, _: usize, _: usize) -> SbiRet {
+        SbiRet::success(54)
+    }
+
+    fn install_triggers(&self, _: usize) -> SbiRet {
+        SbiRet::success(55)
+    }
+
+    fn update_triggers(&self, _: usize) -> SbiRet {
+        SbiRet::success(56)
+    }
+
+    fn uninstall_triggers(&self, _: TriggerMask) -> SbiRet {
+        SbiRet::success(57)
+    }
+
+    fn enable_triggers(&self, _: TriggerMask) -> SbiRet {
+        SbiRet::success(58)
+    }
+
+    fn disable_triggers(&self, _: TriggerMask) -> SbiRet {
+        SbiRet::success(59)
+    }
+}
+
+struct DummyFwft;
+
+impl rustsbi::Fwft for DummyFwft {
+    fn set(&self, _: u32, _: usize, _: usize) -> SbiRet {
+        SbiRet::success(50)
+    }
+
+    fn get(&self, _: u32) -> SbiRet {
+        SbiRet::success(51)
+    }
+}
+
+struct DummySse;
+
+impl rustsbi::Sse for DummySse {
+    fn read_attrs(&self, _: u32, _: u32, _: u32, _: SharedPtr<u8>) -> SbiRet {
+        SbiRet::success(40)
+    }
+
+    fn write_attrs(&self, _: u32, _: u32, _: u32, _: SharedPtr<u8>) -> SbiRet {
+        SbiRet::success(41)
+    }
+
+    fn register(&self, _: u32, _: usize, _: usize) -> SbiRet {
+        SbiRet::success(42)
+    }
+
+    fn unregister(&self, _: u32) -> SbiRet {
+        SbiRet::success(43)
+    }
+
+    fn enable(&self, _: u32) -> SbiRet {
+        SbiRet::success(44)
+    }
+
+    fn disable(&self, _: u32) -> SbiRet {
+        SbiRet::success(45)
+    }
+
+    fn complete(&self) -> SbiRet {
+        SbiRet::success(46)
+    }
+
+    fn inject(&self, _: u32, _: usize) -> SbiRet {
+        SbiRet::success(47)
+    }
+
+    fn hart_unmask(&self) -> SbiRet {
+        SbiRet::success(48)
+    }
+
+    fn hart_mask(&self) -> SbiRet {
+        SbiRet::success(49)
+    }
+}
+
 struct DummySta;
 
 impl rustsbi::Sta for DummySta {
@@ -28,3 +28,53 @@ mod fid {
     #[doc(alias = "SBI_EXT_CPPC_WRITE")]
     pub const WRITE: usize = 3;
 }
+
+/// CPPC register IDs.
+///
+/// Declared in Table 79 at §14.
+pub mod reg_id {
+    /// Highest performance, read-only.
+    pub const HIGHEST_PERFORMANCE: u32 = 0x0000_0000;
+    /// Nominal performance, read-only.
+    pub const NOMINAL_PERFORMANCE: u32 = 0x0000_0001;
+    /// Lowest nonlinear performance, read-only.
+    pub const LOWEST_NONLINEAR_PERFORMANCE: u32 = 0x0000_0002;
+    /// Lowest performance, read-only.
+    pub const LOWEST_PERFORMANCE: u32 = 0x0000_0003;
+    /// Guaranteed performance register, read-only.
+    pub const GUARANTEED_PERFORMANCE: u32 = 0x0000_0004;
+    /// Desired performance register.
+    pub const DESIRED_PERFORMANCE: u32 = 0x0000_0005;
+    /// Minimum performance register.
+    pub const MINIMUM_PERFORMANCE: u32 = 0x0000_0006;
+    /// Maximum performance register.
+    pub const MAXIMUM_PERFORMANCE: u32 = 0x0000_0007;
+    /// Performance reduction tolerance register.
+    pub const PERFORMANCE_REDUCTION_TOLERANCE: u32 = 0x0000_0008;
+    /// Time window register.
+    pub const TIME_WINDOW: u32 = 0x0000_0009;
+    /// Counter wraparound time, read-only.
+    pub const COUNTER_WRAPAROUND_TIME: u32 = 0x0000_000A;
+    /// Reference performance counter register, read-only.
+    pub const REFERENCE_PERFORMANCE_COUNTER: u32 = 0x0000_000B;
+    /// Delivered performance counter register, read-only.
+    pub const DELIVERED_PERFORMANCE_COUNTER: u32 = 0x0000_000C;
+    /// Performance limited register.
+    pub const PERFORMANCE_LIMITED: u32 = 0x0000_000D;
+    /// CPPC enable register.
+    pub const CPPC_ENABLE: u32 = 0x0000_000E;
+    /// Autonomous selection enable.
+    pub const AUTONOMOUS_SELECTION_ENABLE: u32 = 0x0000_000F;
+    /// Autonomous activity window register.
+    pub const AUTONOMOUS_ACTIVITY_WINDOW: u32 = 0x0000_0010;
+    /// Energy performance preference register.
+    pub const ENERGY_PERFORMANCE_PREFERENCE: u32 = 0x0000_0011;
+    /// Reference performance, read-only.
+    pub const REFERENCE_PERFORMANCE: u32 = 0x0000_0012;
+    /// Lowest frequency, read-only.
+    pub const LOWEST_FREQUENCY: u32 = 0x0000_0013;
+    /// Nominal frequency, read-only.
+    pub const NOMINAL_FREQUENCY: u32 = 0x0000_0014;
+    /// Maximum performance state transition latency in nanoseconds, read-only.
+    pub const TRANSITION_LATENCY: u32 = 0x8000_0000;
+}
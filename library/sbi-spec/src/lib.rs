@@ -304,6 +304,12 @@ mod tests {
         const_assert_eq!(1, READ);
         const_assert_eq!(2, READ_HI);
         const_assert_eq!(3, WRITE);
+
+        const_assert_eq!(0x00, reg_id::HIGHEST_PERFORMANCE);
+        const_assert_eq!(0x05, reg_id::DESIRED_PERFORMANCE);
+        const_assert_eq!(0x0E, reg_id::CPPC_ENABLE);
+        const_assert_eq!(0x14, reg_id::NOMINAL_FREQUENCY);
+        const_assert_eq!(0x80000000, reg_id::TRANSITION_LATENCY);
     }
     // §15
     #[test]
@@ -363,6 +369,31 @@ mod tests {
         const_assert_eq!(7, INJECT);
         const_assert_eq!(8, HART_UNMASK);
         const_assert_eq!(9, HART_MASK);
+
+        const_assert_eq!(0x00000000, event_id::LOCAL_HIGH_PRIO_RAS);
+        const_assert_eq!(0x00000001, event_id::LOCAL_DOUBLE_TRAP);
+        const_assert_eq!(0x00008000, event_id::GLOBAL_HIGH_PRIO_RAS);
+        const_assert_eq!(0x00010000, event_id::LOCAL_PMU_OVERFLOW);
+        const_assert_eq!(0x00100000, event_id::LOCAL_LOW_PRIO_RAS);
+        const_assert_eq!(0x00108000, event_id::GLOBAL_LOW_PRIO_RAS);
+        const_assert_eq!(0xffff0000, event_id::LOCAL_SOFTWARE_INJECTED);
+        const_assert_eq!(0xffff8000, event_id::GLOBAL_SOFTWARE_INJECTED);
+
+        const_assert_eq!(0, attr_id::STATUS);
+        const_assert_eq!(1, attr_id::PRIORITY);
+        const_assert_eq!(2, attr_id::CONFIG);
+        const_assert_eq!(3, attr_id::PREFERRED_HART);
+        const_assert_eq!(4, attr_id::ENTRY_PC);
+        const_assert_eq!(5, attr_id::ENTRY_ARG);
+        const_assert_eq!(6, attr_id::INTERRUPTED_SEPC);
+        const_assert_eq!(7, attr_id::INTERRUPTED_FLAGS);
+        const_assert_eq!(8, attr_id::INTERRUPTED_A6);
+        const_assert_eq!(9, attr_id::INTERRUPTED_A7);
+
+        const_assert_eq!(0, event_state::UNUSED);
+        const_assert_eq!(1, event_state::REGISTERED);
+        const_assert_eq!(2, event_state::ENABLED);
+        const_assert_eq!(3, event_state::RUNNING);
     }
     // §18
     #[test]
@@ -58,3 +58,131 @@ mod fid {
     #[doc(alias = "SBI_EXT_SSE_HART_MASK")]
     pub const HART_MASK: usize = 9;
 }
+
+/// Software event IDs.
+///
+/// Declared in Table 88 at §17.
+pub mod event_id {
+    /// RAS event for high priority errors on the calling hart.
+    #[doc(alias = "SBI_SSE_EVENT_LOCAL_HIGH_PRIO_RAS")]
+    pub const LOCAL_HIGH_PRIO_RAS: u32 = 0x0000_0000;
+    /// Double trap event on the calling hart.
+    #[doc(alias = "SBI_SSE_EVENT_LOCAL_DOUBLE_TRAP")]
+    pub const LOCAL_DOUBLE_TRAP: u32 = 0x0000_0001;
+    /// RAS event for high priority errors of the platform.
+    #[doc(alias = "SBI_SSE_EVENT_GLOBAL_HIGH_PRIO_RAS")]
+    pub const GLOBAL_HIGH_PRIO_RAS: u32 = 0x0000_8000;
+    /// Performance counter overflow event on the calling hart.
+    #[doc(alias = "SBI_SSE_EVENT_LOCAL_PMU_OVERFLOW")]
+    pub const LOCAL_PMU_OVERFLOW: u32 = 0x0001_0000;
+    /// RAS event for low priority errors on the calling hart.
+    #[doc(alias = "SBI_SSE_EVENT_LOCAL_LOW_PRIO_RAS")]
+    pub const LOCAL_LOW_PRIO_RAS: u32 = 0x0010_0000;
+    /// RAS event for low priority errors of the platform.
+    #[doc(alias = "SBI_SSE_EVENT_GLOBAL_LOW_PRIO_RAS")]
+    pub const GLOBAL_LOW_PRIO_RAS: u32 = 0x0010_8000;
+    /// Software injected event on the calling hart.
+    #[doc(alias = "SBI_SSE_EVENT_LOCAL_SOFTWARE_INJECTED")]
+    pub const LOCAL_SOFTWARE_INJECTED: u32 = 0xFFFF_0000;
+    /// Software injected event of the platform.
+    #[doc(alias = "SBI_SSE_EVENT_GLOBAL_SOFTWARE_INJECTED")]
+    pub const GLOBAL_SOFTWARE_INJECTED: u32 = 0xFFFF_8000;
+    /// Bit in event ID marking a global event.
+    #[doc(alias = "SBI_SSE_EVENT_GLOBAL_BIT")]
+    pub const GLOBAL_BIT: u32 = 1 << 15;
+    /// Bit in event ID marking a platform-specific event.
+    #[doc(alias = "SBI_SSE_EVENT_PLATFORM_BIT")]
+    pub const PLATFORM_BIT: u32 = 1 << 14;
+}
+
+/// Software event attribute IDs.
+///
+/// Declared in Table 86 at §17.
+pub mod attr_id {
+    /// Status of the software event, read-only.
+    #[doc(alias = "SBI_SSE_ATTR_STATUS")]
+    pub const STATUS: u32 = 0x0000_0000;
+    /// Software event priority.
+    #[doc(alias = "SBI_SSE_ATTR_PRIORITY")]
+    pub const PRIORITY: u32 = 0x0000_0001;
+    /// Software event configuration.
+    #[doc(alias = "SBI_SSE_ATTR_CONFIG")]
+    pub const CONFIG: u32 = 0x0000_0002;
+    /// Hart ID of the preferred hart which should handle a global event.
+    #[doc(alias = "SBI_SSE_ATTR_PREFERRED_HART")]
+    pub const PREFERRED_HART: u32 = 0x0000_0003;
+    /// Entry program counter of the event handler, read-only.
+    #[doc(alias = "SBI_SSE_ATTR_ENTRY_PC")]
+    pub const ENTRY_PC: u32 = 0x0000_0004;
+    /// Entry argument of the event handler, read-only.
+    #[doc(alias = "SBI_SSE_ATTR_ENTRY_ARG")]
+    pub const ENTRY_ARG: u32 = 0x0000_0005;
+    /// Interrupted `sepc` CSR value.
+    #[doc(alias = "SBI_SSE_ATTR_INTERRUPTED_SEPC")]
+    pub const INTERRUPTED_SEPC: u32 = 0x0000_0006;
+    /// Interrupted privilege flags.
+    #[doc(alias = "SBI_SSE_ATTR_INTERRUPTED_FLAGS")]
+    pub const INTERRUPTED_FLAGS: u32 = 0x0000_0007;
+    /// Interrupted `a6` register value.
+    #[doc(alias = "SBI_SSE_ATTR_INTERRUPTED_A6")]
+    pub const INTERRUPTED_A6: u32 = 0x0000_0008;
+    /// Interrupted `a7` register value.
+    #[doc(alias = "SBI_SSE_ATTR_INTERRUPTED_A7")]
+    pub const INTERRUPTED_A7: u32 = 0x0000_0009;
+}
+
+/// Software event states.
+///
+/// Declared in §17.1.
+pub mod event_state {
+    /// The event is not registered.
+    #[doc(alias = "SBI_SSE_STATE_UNUSED")]
+    pub const UNUSED: usize = 0;
+    /// The event is registered but not enabled.
+    #[doc(alias = "SBI_SSE_STATE_REGISTERED")]
+    pub const REGISTERED: usize = 1;
+    /// The event is enabled and ready to be delivered.
+    #[doc(alias = "SBI_SSE_STATE_ENABLED")]
+    pub const ENABLED: usize = 2;
+    /// The event handler is running.
+    #[doc(alias = "SBI_SSE_STATE_RUNNING")]
+    pub const RUNNING: usize = 3;
+}
+
+/// Bit fields of the `STATUS` event attribute.
+///
+/// Declared in Table 87 at §17.
+pub mod status {
+    /// Mask of event state in `STATUS` attribute.
+    pub const STATE_MASK: usize = 0b11;
+    /// The event is pending.
+    pub const PENDING: usize = 1 << 2;
+    /// The event can be injected through `sse_inject`.
+    pub const INJECTABLE: usize = 1 << 3;
+}
+
+/// Bit fields of the `CONFIG` event attribute.
+///
+/// Declared in Table 86 at §17.
+pub mod config {
+    /// The event is disabled automatically after its handler completes.
+    pub const ONESHOT: usize = 1 << 0;
+}
+
+/// Bit fields of the `INTERRUPTED_FLAGS` event attribute.
+///
+/// Declared in Table 86 at §17.
+pub mod interrupted_flags {
+    /// Interrupted `sstatus.SPP` bit value.
+    pub const SSTATUS_SPP: usize = 1 << 0;
+    /// Interrupted `sstatus.SPIE` bit value.
+    pub const SSTATUS_SPIE: usize = 1 << 1;
+    /// Interrupted `hstatus.SPV` bit value.
+    pub const HSTATUS_SPV: usize = 1 << 2;
+    /// Interrupted `hstatus.SPVP` bit value.
+    pub const HSTATUS_SPVP: usize = 1 << 3;
+    /// Interrupted `sstatus.SPELP` bit value.
+    pub const SSTATUS_SPELP: usize = 1 << 4;
+    /// Interrupted `sstatus.SDT` bit value.
+    pub const SSTATUS_SDT: usize = 1 << 5;
+}
@@ -0,0 +1,155 @@
+//! CPPC extension test suite.
+
+use crate::expect;
+use sbi::SbiRet;
+use sbi_spec::cppc::reg_id;
+
+/// CPPC extension test cases.
+#[derive(Clone, Debug)]
+pub enum Case {
+    /// Can't proceed test for CPPC extension does not exist.
+    NotExist,
+    /// Test begin.
+    Begin,
+    /// Test process for a reserved register ID has been rejected.
+    ReservedRejected(u32),
+    /// Test process for a register has been probed.
+    Probe {
+        /// The CPPC register ID.
+        reg_id: u32,
+        /// Width of the register in bits, 0 if it is not implemented.
+        width: usize,
+    },
+    /// Test process for an implemented register has been read.
+    Read {
+        /// The CPPC register ID.
+        reg_id: u32,
+        /// Value of the register.
+        value: u64,
+    },
+    /// Test process for an invalid access has been rejected with the expected error.
+    Rejected {
+        /// The access being tested.
+        call: &'static str,
+        /// The CPPC register ID.
+        reg_id: u32,
+    },
+    /// Test process for desired performance register has been written back.
+    WriteBack(u64),
+    /// Test failed for an SBI call has returned an unexpected error.
+    UnexpectedRet {
+        /// The case in which the SBI call is made.
+        call: &'static str,
+        /// The CPPC register ID.
+        reg_id: u32,
+        /// The expected `SbiRet` value; only `error` field is compared.
+        expected: SbiRet,
+        /// The `SbiRet` value returned by the SBI call.
+        ret: SbiRet,
+    },
+    /// All test cases on CPPC extension finished.
+    Pass,
+}
+
+/// Registers defined by the specification, with whether they are read-only.
+const REGISTERS: [(u32, bool); 22] = [
+    (reg_id::HIGHEST_PERFORMANCE, true),
+    (reg_id::NOMINAL_PERFORMANCE, true),
+    (reg_id::LOWEST_NONLINEAR_PERFORMANCE, true),
+    (reg_id::LOWEST_PERFORMANCE, true),
+    (reg_id::GUARANTEED_PERFORMANCE, true),
+    (reg_id::DESIRED_PERFORMANCE, false),
+    (reg_id::MINIMUM_PERFORMANCE, false),
+    (reg_id::MAXIMUM_PERFORMANCE, false),
+    (reg_id::PERFORMANCE_REDUCTION_TOLERANCE, false),
+    (reg_id::TIME_WINDOW, false),
+    (reg_id::COUNTER_WRAPAROUND_TIME, true),
+    (reg_id::REFERENCE_PERFORMANCE_COUNTER, true),
+    (reg_id::DELIVERED_PERFORMANCE_COUNTER, true),
+    (reg_id::PERFORMANCE_LIMITED, false),
+    (reg_id::CPPC_ENABLE, false),
+    (reg_id::AUTONOMOUS_SELECTION_ENABLE, false),
+    (reg_id::AUTONOMOUS_ACTIVITY_WINDOW, false),
+    (reg_id::ENERGY_PERFORMANCE_PREFERENCE, false),
+    (reg_id::REFERENCE_PERFORMANCE, true),
+    (reg_id::LOWEST_FREQUENCY, true),
+    (reg_id::NOMINAL_FREQUENCY, true),
+    (reg_id::TRANSITION_LATENCY, true),
+];
+
+/// Reserved register IDs.
+const RESERVED: [u32; 3] = [0x0000_0015, 0x7FFF_FFFF, 0x8000_0001];
+
+/// Test CPPC extension.
+pub fn test(mut f: impl FnMut(Case)) {
+    if sbi::probe_extension(sbi::Cppc).is_unavailable() {
+        f(Case::NotExist);
+        return;
+    }
+
+    f(Case::Begin);
+
+    for reg_id in RESERVED {
+        let ret = sbi::cppc_probe(reg_id);
+        if expect(&mut f, ("probe", reg_id), SbiRet::invalid_param(), ret) {
+            f(Case::ReservedRejected(reg_id));
+        }
+    }
+    for (reg_id, read_only) in REGISTERS {
+        let ret = sbi::cppc_probe(reg_id);
+        if !expect(&mut f, ("probe", reg_id), SbiRet::success(0), ret) {
+            continue;
+        }
+        let width = ret.value;
+        f(Case::Probe { reg_id, width });
+        if width == 0 {
+            // 未实现的寄存器，读写都应返回不支持
+            let ret = sbi::cppc_read(reg_id);
+            if expect(&mut f, ("read", reg_id), SbiRet::not_supported(), ret) {
+                f(Case::Rejected {
+                    call: "read unimplemented register",
+                    reg_id,
+                });
+            }
+            continue;
+        }
+        let Some(value) = read(&mut f, reg_id, width) else {
+            continue;
+        };
+        f(Case::Read { reg_id, value });
+        if read_only {
+            let ret = sbi::cppc_write(reg_id, value);
+            if expect(&mut f, ("write", reg_id), SbiRet::denied(), ret) {
+                f(Case::Rejected {
+                    call: "write read-only register",
+                    reg_id,
+                });
+            }
+        } else if reg_id == reg_id::DESIRED_PERFORMANCE {
+            let ret = sbi::cppc_write(reg_id, value);
+            if expect(&mut f, ("write", reg_id), SbiRet::success(0), ret) {
+                f(Case::WriteBack(value));
+            }
+        }
+    }
+
+    f(Case::Pass);
+}
+
+/// 读取完整的寄存器值，32 位平台上需要分别读取高低位
+fn read(f: &mut impl FnMut(Case), reg_id: u32, width: usize) -> Option<u64> {
+    let lo = sbi::cppc_read(reg_id);
+    if !expect(f, ("read", reg_id), SbiRet::success(0), lo) {
+        return None;
+    }
+    if usize::BITS >= 64 || width <= 32 {
+        return Some(lo.value as u64);
+    }
+    let hi = sbi::cppc_read_hi(reg_id);
+    if !expect(f, ("read_hi", reg_id), SbiRet::success(0), hi) {
+        return None;
+    }
+    Some(((hi.value as u64) << 32) | lo.value as u64)
+}
+
+impl_unexpected_ret!(Case, reg_id: u32);
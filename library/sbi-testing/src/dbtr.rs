@@ -0,0 +1,214 @@
+//! Debug triggers extension test suite.
+
+use crate::expect;
+use sbi::{SbiRet, SharedPtr};
+use sbi_spec::binary::TriggerMask;
+
+/// Debug triggers extension test cases.
+#[derive(Clone, Debug)]
+pub enum Case {
+    /// Can't proceed test for debug triggers extension does not exist.
+    NotExist,
+    /// Test begin.
+    Begin,
+    /// Test process for number of debug triggers on the calling hart.
+    NumTriggers {
+        /// Total number of debug triggers.
+        total: usize,
+        /// Number of debug triggers supporting the execute `mcontrol6` configuration.
+        mcontrol6: usize,
+    },
+    /// Test process for no debug trigger supports the configuration under test.
+    NoTriggers,
+    /// Test process for an invalid call has been rejected with the expected error.
+    Rejected(&'static str),
+    /// Test process for a trigger has been installed.
+    ///
+    /// The returned value includes the `trig_idx` assigned.
+    Installed(usize),
+    /// Test failed for an installed trigger is not mapped to a hardware trigger.
+    NotMapped(usize),
+    /// Test process for a trigger has been disabled, enabled and updated.
+    Updated(usize),
+    /// Test process for a trigger has been uninstalled.
+    Uninstalled(usize),
+    /// Test process for the machine mode bit of a trigger has been rejected or cleared.
+    MachineModeSanitized,
+    /// Test failed for a trigger with machine mode bit set has been installed as is.
+    MachineModeInstalled(usize),
+    /// Test failed for an SBI call has returned an unexpected error.
+    UnexpectedRet {
+        /// The case in which the SBI call is made.
+        call: &'static str,
+        /// The expected `SbiRet` value; only `error` field is compared.
+        expected: SbiRet,
+        /// The `SbiRet` value returned by the SBI call.
+        ret: SbiRet,
+    },
+    /// All test cases on debug triggers extension finished.
+    Pass,
+}
+
+/// `tdata1` of an `mcontrol6` trigger matching instruction execution in S and U mode.
+const MCONTROL6_EXEC: usize = (6 << (usize::BITS - 4)) | TDATA1_S | TDATA1_U | TDATA1_EXECUTE;
+
+const TDATA1_M: usize = 1 << 6;
+const TDATA1_S: usize = 1 << 4;
+const TDATA1_U: usize = 1 << 3;
+const TDATA1_EXECUTE: usize = 1 << 2;
+
+const TRIG_STATE_MAPPED: usize = 1 << 0;
+
+/// Test debug triggers extension.
+pub fn test(mut f: impl FnMut(Case)) {
+    if sbi::probe_extension(sbi::Dbtr).is_unavailable() {
+        f(Case::NotExist);
+        return;
+    }
+
+    f(Case::Begin);
+
+    let total = sbi::debug_num_triggers(0);
+    let mcontrol6 = sbi::debug_num_triggers(MCONTROL6_EXEC);
+    f(Case::NumTriggers { total, mcontrol6 });
+
+    let addr = (&raw const SHMEM) as usize;
+
+    // 关闭共享内存后，读取触发器应失败
+    let ret = sbi::debug_set_shmem(SharedPtr::new(usize::MAX, usize::MAX), 0);
+    expect(&mut f, "disable shmem", SbiRet::success(0), ret);
+    let ret = sbi::debug_read_triggers(0, 1);
+    if expect(&mut f, "read without shmem", SbiRet::no_shmem(), ret) {
+        f(Case::Rejected("read without shmem"));
+    }
+    let ret = sbi::debug_set_shmem(SharedPtr::new(addr, 0), 1);
+    if expect(
+        &mut f,
+        "set shmem with reserved flags",
+        SbiRet::invalid_param(),
+        ret,
+    ) {
+        f(Case::Rejected("set shmem with reserved flags"));
+    }
+    let ret = sbi::debug_set_shmem(SharedPtr::new(addr + 1, 0), 0);
+    if expect(&mut f, "set misaligned shmem", SbiRet::invalid_param(), ret) {
+        f(Case::Rejected("set misaligned shmem"));
+    }
+    let ret = sbi::debug_set_shmem(SharedPtr::new(addr, 0), 0);
+    if !expect(&mut f, "set shmem", SbiRet::success(0), ret) {
+        return;
+    }
+    if total > 0 {
+        let ret = sbi::debug_read_triggers(total, 1);
+        if expect(&mut f, "read out of range", SbiRet::bad_range(), ret) {
+            f(Case::Rejected("read out of range"));
+        }
+    }
+
+    if mcontrol6 == 0 {
+        f(Case::NoTriggers);
+    } else {
+        test_trigger(&mut f);
+    }
+
+    let ret = sbi::debug_set_shmem(SharedPtr::new(usize::MAX, usize::MAX), 0);
+    expect(&mut f, "disable shmem", SbiRet::success(0), ret);
+
+    f(Case::Pass);
+}
+
+/// 在一个从不执行的地址上安装执行触发器，并走完触发器的生命周期
+fn test_trigger(f: &mut impl FnMut(Case)) {
+    let tdata2 = (&raw const WATCHED) as usize;
+    let Some(idx) = install(f, MCONTROL6_EXEC, tdata2) else {
+        return;
+    };
+    f(Case::Installed(idx));
+    let ret = sbi::debug_read_triggers(idx, 1);
+    if expect(f, "read triggers", SbiRet::success(0), ret) {
+        let [state, ..] = read_shmem();
+        if state & TRIG_STATE_MAPPED == 0 {
+            f(Case::NotMapped(idx));
+        }
+    }
+
+    let mask = TriggerMask::from_mask_base(1, idx);
+    let mut updated = true;
+    let ret = sbi::debug_disable_triggers(mask);
+    updated &= expect(f, "disable triggers", SbiRet::success(0), ret);
+    let ret = sbi::debug_enable_triggers(mask);
+    updated &= expect(f, "enable triggers", SbiRet::success(0), ret);
+    write_shmem([idx, MCONTROL6_EXEC, tdata2 + 4, 0]);
+    let ret = sbi::debug_update_triggers(1);
+    updated &= expect(f, "update triggers", SbiRet::success(0), ret);
+    if updated {
+        f(Case::Updated(idx));
+    }
+
+    let ret = sbi::debug_uninstall_triggers(mask);
+    if expect(f, "uninstall triggers", SbiRet::success(0), ret) {
+        f(Case::Uninstalled(idx));
+        let ret = sbi::debug_uninstall_triggers(mask);
+        if expect(f, "uninstall twice", SbiRet::invalid_param(), ret) {
+            f(Case::Rejected("uninstall twice"));
+        }
+    }
+
+    // 监管态不应安装机器态触发器
+    write_shmem([0, MCONTROL6_EXEC | TDATA1_M, tdata2, 0]);
+    let ret = sbi::debug_install_triggers(1);
+    if ret.error == SbiRet::invalid_param().error {
+        f(Case::MachineModeSanitized);
+        return;
+    }
+    if !expect(f, "install with m bit", SbiRet::success(0), ret) {
+        return;
+    }
+    let [idx, ..] = read_shmem();
+    let ret = sbi::debug_read_triggers(idx, 1);
+    if expect(f, "read triggers", SbiRet::success(0), ret) {
+        let [_, tdata1, ..] = read_shmem();
+        if tdata1 & TDATA1_M == 0 {
+            f(Case::MachineModeSanitized);
+        } else {
+            f(Case::MachineModeInstalled(idx));
+        }
+    }
+    let ret = sbi::debug_uninstall_triggers(TriggerMask::from_mask_base(1, idx));
+    expect(f, "uninstall triggers", SbiRet::success(0), ret);
+}
+
+/// 安装一个触发器，返回分配的 `trig_idx`
+fn install(f: &mut impl FnMut(Case), tdata1: usize, tdata2: usize) -> Option<usize> {
+    write_shmem([0, tdata1, tdata2, 0]);
+    let ret = sbi::debug_install_triggers(1);
+    if expect(f, "install triggers", SbiRet::success(0), ret) {
+        let [idx, ..] = read_shmem();
+        Some(idx)
+    } else {
+        None
+    }
+}
+
+impl_unexpected_ret!(Case);
+
+/// 触发器监视的地址，从不执行
+static WATCHED: u32 = 0;
+
+static mut SHMEM: Shmem = Shmem([0; 4]);
+
+/// 一个触发器配置项，共四个字
+#[repr(C, align(16))]
+struct Shmem([usize; 4]);
+
+/// 读取共享内存中的第一个配置项
+#[inline]
+fn read_shmem() -> [usize; 4] {
+    unsafe { (&raw const SHMEM).read_volatile().0 }
+}
+
+/// 写入共享内存中的第一个配置项
+#[inline]
+fn write_shmem(words: [usize; 4]) {
+    unsafe { (&raw mut SHMEM).write_volatile(Shmem(words)) }
+}
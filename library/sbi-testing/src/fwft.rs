@@ -0,0 +1,164 @@
+//! Firmware features extension test suite.
+
+use crate::expect;
+use sbi::SbiRet;
+use sbi_spec::fwft::{feature_type, flags::SetFlags};
+
+/// Firmware features extension test cases.
+#[derive(Clone, Debug)]
+pub enum Case {
+    /// Can't proceed test for firmware features extension does not exist.
+    NotExist,
+    /// Test begin.
+    Begin,
+    /// Test process for current value of a firmware feature.
+    Get {
+        /// The feature ID.
+        feature: u32,
+        /// The value of this feature, `None` if it is not supported.
+        value: Option<usize>,
+    },
+    /// Test process for an invalid call has been rejected with the expected error.
+    Rejected(&'static str),
+    /// Test process for misaligned exception delegation has been toggled and restored.
+    MisalignedDelegToggled,
+    /// Test process for misaligned exception delegation is not supported on this platform.
+    MisalignedDelegNotSupported,
+    /// Test process for a locked feature can no longer be changed.
+    Locked(u32),
+    /// Test failed for an SBI call has returned an unexpected error.
+    UnexpectedRet {
+        /// The case in which the SBI call is made.
+        call: &'static str,
+        /// The expected `SbiRet` value; only `error` field is compared.
+        expected: SbiRet,
+        /// The `SbiRet` value returned by the SBI call.
+        ret: SbiRet,
+    },
+    /// Test failed for a feature holds another value than what has been set.
+    ValueMismatch {
+        /// The feature ID.
+        feature: u32,
+        /// The value which has been set.
+        expected: usize,
+        /// The value read back.
+        actual: usize,
+    },
+    /// All test cases on firmware features extension finished.
+    Pass,
+}
+
+/// Features defined by the specification.
+const FEATURES: [usize; 6] = [
+    feature_type::MISALIGNED_EXC_DELEG,
+    feature_type::LANDING_PAD,
+    feature_type::SHADOW_STACK,
+    feature_type::DOUBLE_TRAP,
+    feature_type::PTE_AD_HW_UPDATING,
+    feature_type::POINTER_MASKING_PMLEN,
+];
+
+/// Reserved features, local and global.
+const RESERVED: [u32; 4] = [0x0000_0006, 0x3FFF_FFFF, 0x8000_0000, 0xBFFF_FFFF];
+
+const MISALIGNED_EXC_DELEG: u32 = feature_type::MISALIGNED_EXC_DELEG as _;
+
+/// Test firmware features extension.
+///
+/// If misaligned exception delegation is supported, it is locked to its original
+/// value at the end of this test, until the next system reset. Run this test after
+/// all others which may change firmware features.
+pub fn test(mut f: impl FnMut(Case)) {
+    if sbi::probe_extension(sbi::Fwft).is_unavailable() {
+        f(Case::NotExist);
+        return;
+    }
+
+    f(Case::Begin);
+
+    for feature in FEATURES.map(|id| id as u32) {
+        let ret = sbi::fwft_get(feature);
+        if ret.is_ok() {
+            f(Case::Get {
+                feature,
+                value: Some(ret.value),
+            });
+        } else if expect(&mut f, "get defined feature", SbiRet::not_supported(), ret) {
+            f(Case::Get {
+                feature,
+                value: None,
+            });
+        }
+    }
+    for feature in RESERVED {
+        let ret = sbi::fwft_get(feature);
+        if expect(&mut f, "get reserved feature", SbiRet::denied(), ret) {
+            f(Case::Rejected("get reserved feature"));
+        }
+        let ret = sbi::fwft_set(feature, 0, 0);
+        if expect(&mut f, "set reserved feature", SbiRet::denied(), ret) {
+            f(Case::Rejected("set reserved feature"));
+        }
+    }
+
+    let original = sbi::fwft_get(MISALIGNED_EXC_DELEG);
+    if original.error == SbiRet::not_supported().error {
+        let ret = sbi::fwft_set(MISALIGNED_EXC_DELEG, 1, 0);
+        if expect(
+            &mut f,
+            "set unsupported feature",
+            SbiRet::not_supported(),
+            ret,
+        ) {
+            f(Case::MisalignedDelegNotSupported);
+        }
+        f(Case::Pass);
+        return;
+    }
+    if original.is_err() {
+        // 错误已在上面的循环中报告
+        return;
+    }
+    let ret = sbi::fwft_set(MISALIGNED_EXC_DELEG, 2, 0);
+    if expect(&mut f, "set invalid value", SbiRet::invalid_param(), ret) {
+        f(Case::Rejected("set invalid value"));
+    }
+    let ret = sbi::fwft_set(MISALIGNED_EXC_DELEG, original.value, !SetFlags::LOCK.bits());
+    if expect(&mut f, "set reserved flags", SbiRet::invalid_param(), ret) {
+        f(Case::Rejected("set reserved flags"));
+    }
+    // 切换委托状态，读回并恢复
+    let mut toggled = true;
+    for value in [original.value ^ 1, original.value] {
+        let ret = sbi::fwft_set(MISALIGNED_EXC_DELEG, value, 0);
+        if !expect(&mut f, "set feature", SbiRet::success(0), ret) {
+            toggled = false;
+            continue;
+        }
+        let ret = sbi::fwft_get(MISALIGNED_EXC_DELEG);
+        if !expect(&mut f, "get feature", SbiRet::success(0), ret) {
+            toggled = false;
+        } else if ret.value != value {
+            toggled = false;
+            f(Case::ValueMismatch {
+                feature: MISALIGNED_EXC_DELEG,
+                expected: value,
+                actual: ret.value,
+            });
+        }
+    }
+    if toggled {
+        f(Case::MisalignedDelegToggled);
+    }
+    // 锁定为原值，之后的修改应被拒绝
+    let ret = sbi::fwft_set(MISALIGNED_EXC_DELEG, original.value, SetFlags::LOCK.bits());
+    if expect(&mut f, "lock feature", SbiRet::success(0), ret) {
+        let ret = sbi::fwft_set(MISALIGNED_EXC_DELEG, original.value ^ 1, 0);
+        if expect(&mut f, "set locked feature", SbiRet::denied_locked(), ret) {
+            f(Case::Locked(MISALIGNED_EXC_DELEG));
+        }
+    }
+    f(Case::Pass);
+}
+
+impl_unexpected_ret!(Case);
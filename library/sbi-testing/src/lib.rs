@@ -7,11 +7,55 @@ mod thread;
 
 pub extern crate sbi_rt as sbi;
 
+use sbi::SbiRet;
+
+/// 为带有 `UnexpectedRet { call, expected, ret }` 情形的测试用例实现 [`UnexpectedRet`]
+///
+/// 可以附加一个标识出错调用的字段，如 `impl_unexpected_ret!(Case, reg_id: u32)`。
+macro_rules! impl_unexpected_ret {
+    ($case:ty) => {
+        impl $crate::UnexpectedRet for $case {
+            type Call = &'static str;
+
+            #[inline]
+            fn unexpected_ret(call: &'static str, expected: sbi::SbiRet, ret: sbi::SbiRet) -> Self {
+                Self::UnexpectedRet {
+                    call,
+                    expected,
+                    ret,
+                }
+            }
+        }
+    };
+    ($case:ty, $field:ident: $ty:ty) => {
+        impl $crate::UnexpectedRet for $case {
+            type Call = (&'static str, $ty);
+
+            #[inline]
+            fn unexpected_ret(
+                (call, $field): (&'static str, $ty),
+                expected: sbi::SbiRet,
+                ret: sbi::SbiRet,
+            ) -> Self {
+                Self::UnexpectedRet {
+                    call,
+                    $field,
+                    expected,
+                    ret,
+                }
+            }
+        }
+    };
+}
+
 #[cfg(feature = "log")]
 mod log_test;
 
 #[cfg(feature = "log")]
-pub use log_test::Testing;
+pub use log_test::{Format, Testing};
+
+mod marker;
+pub use marker::{Entry, Marker};
 
 // §4
 mod base;
@@ -28,9 +72,52 @@ pub use spi::{Case as IpiCase, test as test_ipi};
 mod hsm;
 pub use hsm::{Case as HsmCase, test as test_hsm};
 // §10
-// pub mod srst;
+mod srst;
+pub use srst::{Case as SrstCase, test as test_srst};
 // §11
 // pub mod pmu;
 // §12
 mod dbcn;
 pub use dbcn::{Case as DbcnCase, test as test_dbcn};
+// §13
+mod susp;
+pub use susp::{Case as SuspCase, test as test_susp};
+// §14
+mod cppc;
+pub use cppc::{Case as CppcCase, test as test_cppc};
+// §16
+mod sta;
+pub use sta::{Case as StaCase, test as test_sta};
+// §17
+mod sse;
+pub use sse::{Case as SseCase, test as test_sse};
+// §18
+mod fwft;
+pub use fwft::{Case as FwftCase, test as test_fwft};
+// §19
+mod dbtr;
+pub use dbtr::{Case as DbtrCase, test as test_dbtr};
+
+/// 带有意外返回值情形的测试用例
+pub(crate) trait UnexpectedRet {
+    /// 标识出错调用的信息
+    type Call;
+
+    /// 构造意外返回值情形
+    fn unexpected_ret(call: Self::Call, expected: SbiRet, ret: SbiRet) -> Self;
+}
+
+/// 检查错误码，不符合预期时报告
+pub(crate) fn expect<C: UnexpectedRet>(
+    f: &mut impl FnMut(C),
+    call: C::Call,
+    expected: SbiRet,
+    ret: SbiRet,
+) -> bool {
+    if ret.error == expected.error {
+        true
+    } else {
+        f(C::unexpected_ret(call, expected, ret));
+        false
+    }
+}
@@ -1,5 +1,6 @@
-﻿use crate::{base, dbcn, hsm, spi, time};
-use log::*;
+use crate::{Entry, Marker, base, cppc, dbcn, dbtr, fwft, hsm, spi, srst, sse, sta, susp, time};
+use core::fmt::{self, Write};
+use log::{Level, log};
 
 /// Automatic SBI testing with logging enabled.
 pub struct Testing {
@@ -11,6 +12,21 @@ pub struct Testing {
     pub hart_mask_base: usize,
     /// Delay value to test Timer programmer extension.
     pub delay: u64,
+    /// Format of test results.
+    pub format: Format,
+}
+
+/// Output format of test results.
+#[derive(Clone, Copy, Debug)]
+pub enum Format {
+    /// Human readable records through the `log` crate.
+    Log,
+    /// One JSON object per line, written by the given function without line break.
+    ///
+    /// Each record has a `suite`, an `event` and a `message` field, where `event` is
+    /// one of `begin`, `pass`, `skip`, `fail`, `warn`, `info` or `debug`.
+    /// The last record is `{"event":"end","passed":<bool>}`.
+    Json(fn(fmt::Arguments)),
 }
 
 const TARGET: &str = "sbi-testing";
@@ -18,150 +34,568 @@ const TARGET: &str = "sbi-testing";
 impl Testing {
     /// Start testing process of RISC-V SBI implementation.
     pub fn test(self) -> bool {
+        let result = self.test_returning();
+        self.finish(result);
+        result
+    }
+
+    /// Start testing process of RISC-V SBI implementation, including extensions
+    /// which do not return on success.
+    ///
+    /// The test kernel should call this function on every entry with the same `marker`;
+    /// tests which have finished before a system suspend or reset are not run again.
+    /// Warm reboot is requested only if `reboot` is true.
+    pub fn test_reentrant(self, marker: &mut Marker, entry: Entry, reboot: bool) -> bool {
+        if marker.is_fresh() {
+            marker.begin();
+            if !self.test_returning() {
+                marker.fail();
+            }
+        }
+        let rep = self.report("SUSP");
+        susp::test(
+            marker,
+            entry,
+            self.delay,
+            self.hart_mask,
+            self.hart_mask_base,
+            |case| {
+                use susp::Case::*;
+                match case {
+                    NotExist => rep.not_exist(false),
+                    Begin => rep.begin(),
+                    Pass => rep.pass(),
+                    ReservedTypeRejected(ty) => {
+                        rep.info(format_args!("reserved sleep type {ty:#x} rejected"))
+                    }
+                    ReservedTypeNotRejected { sleep_type, ret } => {
+                        rep.error(format_args!(
+                            "reserved sleep type {sleep_type:#x} not rejected: {ret:?}"
+                        ));
+                    }
+                    UnexpectedResume(ty) => {
+                        rep.error(format_args!("system suspended with sleep type {ty:#x}"));
+                    }
+                    CorruptedMarker(stage) => {
+                        rep.error(format_args!("marker corrupted with stage {stage}"));
+                    }
+                    Denied(id) => rep.info(format_args!("suspend denied while hart {id} started")),
+                    NotDenied { hartid, ret } => {
+                        rep.error(format_args!(
+                            "suspend not denied while hart {hartid} started: {ret:?}"
+                        ));
+                    }
+                    InvalidAddressRejected(addr) => {
+                        rep.info(format_args!("resume address {addr:#x} rejected"))
+                    }
+                    InvalidAddressNotRejected { resume_addr, ret } => {
+                        rep.error(format_args!(
+                            "resume address {resume_addr:#x} not rejected: {ret:?}"
+                        ));
+                    }
+                    HartStartFailed { hartid, ret } => {
+                        rep.error(format_args!("hart {hartid} start failed: {ret:?}"));
+                    }
+                    NoStoppedHart => rep.warn(format_args!("no stopped hart")),
+                    NoWakeUpSource => {
+                        rep.warn(format_args!("no timer to wake system up, skip suspend"))
+                    }
+                    Suspend => rep.info(format_args!("suspending system to RAM")),
+                    SuspendNotSupported => rep.warn(format_args!("suspend to RAM not supported")),
+                    SuspendFailed(ret) => rep.error(format_args!("suspend failed: {ret:?}")),
+                    Resumed => rep.info(format_args!("system resumed")),
+                    ResumedOnOtherHart { expected, actual } => {
+                        rep.error(format_args!(
+                            "suspended on hart {expected} but resumed on hart {actual}"
+                        ));
+                    }
+                    OpaqueMismatch { expected, actual } => {
+                        rep.error(format_args!(
+                            "resumed with a1 = {actual:#x}, expected {expected:#x}"
+                        ));
+                    }
+                }
+            },
+        );
+        let rep = self.report("SRST");
+        srst::test(marker, reboot, |case| {
+            use srst::Case::*;
+            match case {
+                NotExist => rep.not_exist(false),
+                Begin => rep.begin(),
+                Pass => rep.pass(),
+                ReservedTypeRejected(ty) => {
+                    rep.info(format_args!("reserved reset type {ty:#x} rejected"))
+                }
+                ReservedReasonRejected(reason) => {
+                    rep.info(format_args!("reserved reset reason {reason:#x} rejected"))
+                }
+                ReservedNotRejected {
+                    reset_type,
+                    reset_reason,
+                    ret,
+                } => {
+                    rep.error(format_args!(
+                        "reset type {reset_type:#x} reason {reset_reason:#x} not rejected: {ret:?}"
+                    ));
+                }
+                UnexpectedReset {
+                    reset_type,
+                    reset_reason,
+                } => {
+                    rep.error(format_args!(
+                        "system reset with type {reset_type:#x} reason {reset_reason:#x}"
+                    ));
+                }
+                CorruptedMarker(stage) => {
+                    rep.error(format_args!("marker corrupted with stage {stage}"));
+                }
+                WarmReboot => rep.info(format_args!("requesting warm reboot")),
+                WarmRebootNotSupported => rep.warn(format_args!("warm reboot not supported")),
+                WarmRebootFailed(ret) => rep.error(format_args!("warm reboot failed: {ret:?}")),
+                Rebooted => rep.info(format_args!("system rebooted")),
+            }
+        });
+        let result = marker.passed();
+        marker.finish();
+        self.finish(result);
+        result
+    }
+
+    fn test_returning(&self) -> bool {
         let mut result = true;
+        let rep = self.report("Base");
         base::test(|case| {
             use base::Case::*;
             match case {
                 NotExist => panic!("Sbi `Base` not exist"),
-                Begin => info!(target: TARGET, "Testing `Base`"),
-                Pass => info!(target: TARGET, "Sbi `Base` test pass"),
+                Begin => rep.begin(),
+                Pass => rep.pass(),
                 GetSbiSpecVersion(version) => {
-                    info!(target: TARGET, "sbi spec version = {version}");
+                    rep.info(format_args!("sbi spec version = {version}"));
                 }
                 GetSbiImplId(Ok(name)) => {
-                    info!(target: TARGET, "sbi impl = {name}");
+                    rep.info(format_args!("sbi impl = {name}"));
                 }
                 GetSbiImplId(Err(unknown)) => {
-                    warn!(target: TARGET, "unknown sbi impl = {unknown:#x}");
+                    rep.warn(format_args!("unknown sbi impl = {unknown:#x}"));
                 }
                 GetSbiImplVersion(version) => {
-                    info!(target: TARGET, "sbi impl version = {version:#x}");
+                    rep.info(format_args!("sbi impl version = {version:#x}"));
                 }
                 ProbeExtensions(exts) => {
-                    info!(target: TARGET, "sbi extensions = {exts}");
+                    rep.info(format_args!("sbi extensions = {exts}"));
                 }
                 GetMvendorId(id) => {
-                    info!(target: TARGET, "mvendor id = {id:#x}");
+                    rep.info(format_args!("mvendor id = {id:#x}"));
                 }
                 GetMarchId(id) => {
-                    info!(target: TARGET, "march id = {id:#x}");
+                    rep.info(format_args!("march id = {id:#x}"));
                 }
                 GetMimpId(id) => {
-                    info!(target: TARGET, "mimp id = {id:#x}");
+                    rep.info(format_args!("mimp id = {id:#x}"));
                 }
             }
         });
+        let rep = self.report("TIME");
         time::test(self.delay, |case| {
             use time::Case::*;
             match case {
                 NotExist => {
-                    error!(target: TARGET, "Sbi `TIME` not exist");
+                    rep.not_exist(true);
                     result = false;
                 }
-                Begin => info!(target: TARGET, "Testing `TIME`"),
-                Pass => info!(target: TARGET, "Sbi `TIME` test pass"),
+                Begin => rep.begin(),
+                Pass => rep.pass(),
                 Interval { begin: _, end: _ } => {
-                    info!(
-                        target: TARGET,
+                    rep.info(format_args!(
                         "read time register successfully, set timer +1s"
-                    );
+                    ));
                 }
                 ReadFailed => {
-                    error!(target: TARGET, "csrr time failed");
+                    rep.error(format_args!("csrr time failed"));
                     result = false;
                 }
                 TimeDecreased { a, b } => {
-                    error!(target: TARGET, "time decreased: {a} -> {b}");
+                    rep.error(format_args!("time decreased: {a} -> {b}"));
                     result = false;
                 }
                 SetTimer => {
-                    info!(target: TARGET, "timer interrupt delegate successfully");
+                    rep.info(format_args!("timer interrupt delegate successfully"));
                 }
                 UnexpectedTrap(trap) => {
-                    error!(
-                        target: TARGET,
+                    rep.error(format_args!(
                         "expect trap at supervisor timer, but {trap:?} was caught"
-                    );
+                    ));
                     result = false;
                 }
             }
         });
+        let rep = self.report("sPI");
         spi::test(self.hartid, |case| {
             use spi::Case::*;
             match case {
                 NotExist => {
-                    error!(target: TARGET, "Sbi `sPI` not exist");
+                    rep.not_exist(true);
                     result = false;
                 }
-                Begin => info!(target: TARGET, "Testing `sPI`"),
-                Pass => info!(target: TARGET, "Sbi `sPI` test pass"),
-                SendIpi => info!(target: TARGET, "send ipi successfully"),
+                Begin => rep.begin(),
+                Pass => rep.pass(),
+                SendIpi => rep.info(format_args!("send ipi successfully")),
                 UnexpectedTrap(trap) => {
-                    error!(
-                        target: TARGET,
+                    rep.error(format_args!(
                         "expect trap at supervisor soft, but {trap:?} was caught"
-                    );
+                    ));
                     result = false;
                 }
             }
         });
+        let rep = self.report("HSM");
         hsm::test(self.hartid, self.hart_mask, self.hart_mask_base, |case| {
             use hsm::Case::*;
             match case {
                 NotExist => {
-                    error!(target: TARGET, "Sbi `HSM` not exist");
+                    rep.not_exist(true);
                     result = false;
                 }
-                Begin => info!(target: TARGET, "Testing `HSM`"),
-                Pass => info!(target: TARGET, "Sbi `HSM` test pass"),
-                HartStartedBeforeTest(id) => warn!(target: TARGET, "hart {id} already started"),
-                NoStoppedHart => warn!(target: TARGET, "no stopped hart"),
-                BatchBegin(batch) => info!(target: TARGET, "Testing harts: {batch:?}"),
-                HartStarted(id) => debug!(target: TARGET, "hart {id} started"),
+                Begin => rep.begin(),
+                Pass => rep.pass(),
+                HartStartedBeforeTest(id) => rep.warn(format_args!("hart {id} already started")),
+                NoStoppedHart => rep.warn(format_args!("no stopped hart")),
+                BatchBegin(batch) => rep.info(format_args!("Testing harts: {batch:?}")),
+                HartStarted(id) => rep.debug(format_args!("hart {id} started")),
                 HartStartFailed { hartid, ret } => {
-                    error!(target: TARGET, "hart {hartid} start failed: {ret:?}");
+                    rep.error(format_args!("hart {hartid} start failed: {ret:?}"));
                     result = false;
                 }
                 HartSuspendedNonretentive(id) => {
-                    debug!(target: TARGET, "hart {id} suspended nonretentive")
+                    rep.debug(format_args!("hart {id} suspended nonretentive"))
                 }
-                HartResumed(id) => debug!(target: TARGET, "hart {id} resumed"),
+                HartResumed(id) => rep.debug(format_args!("hart {id} resumed")),
                 HartSuspendedRetentive(id) => {
-                    debug!(target: TARGET, "hart {id} suspended retentive")
+                    rep.debug(format_args!("hart {id} suspended retentive"))
                 }
-                HartStopped(id) => debug!(target: TARGET, "hart {id} stopped"),
-                BatchPass(batch) => info!(target: TARGET, "Testing Pass: {batch:?}"),
+                HartStopped(id) => rep.debug(format_args!("hart {id} stopped")),
+                BatchPass(batch) => rep.info(format_args!("Testing Pass: {batch:?}")),
             }
         });
+        let rep = self.report("DBCN");
         dbcn::test(|case| {
             use dbcn::Case::*;
             match case {
                 NotExist => {
-                    error!(target: TARGET, "Sbi `DBCN` not exist");
+                    rep.not_exist(true);
                     result = false;
                 }
-                Begin => info!(target: TARGET, "Testing `DBCN`"),
-                Pass => info!(target: TARGET, "Sbi `DBCN` test pass"),
+                Begin => rep.begin(),
+                Pass => rep.pass(),
                 WriteByte => {}
                 WritingByteFailed(ret) => {
-                    error!(target: TARGET, "writing byte failed: {ret:?}");
+                    rep.error(format_args!("writing byte failed: {ret:?}"));
                     result = false;
                 }
-                WriteSlice => info!(target: TARGET, "writing slice successfully"),
+                WriteSlice => rep.info(format_args!("writing slice successfully")),
                 WritingPartialSlice(len) => {
-                    warn!(target: TARGET, "writing partial slice: {len} bytes written");
+                    rep.warn(format_args!("writing partial slice: {len} bytes written"));
                 }
                 WritingSliceFailed(ret) => {
-                    error!(target: TARGET, "writing slice failed: {ret:?}");
+                    rep.error(format_args!("writing slice failed: {ret:?}"));
                     result = false;
                 }
-                Read(len) => info!(target: TARGET, "reading {len} bytes from console"),
+                Read(len) => rep.info(format_args!("reading {len} bytes from console")),
                 ReadingFailed(ret) => {
-                    error!(target: TARGET, "reading failed: {ret:?}");
+                    rep.error(format_args!("reading failed: {ret:?}"));
+                    result = false;
+                }
+            }
+        });
+        let rep = self.report("CPPC");
+        cppc::test(|case| {
+            use cppc::Case::*;
+            match case {
+                NotExist => rep.not_exist(false),
+                Begin => rep.begin(),
+                Pass => rep.pass(),
+                ReservedRejected(id) => {
+                    rep.debug(format_args!("reserved register {id:#x} rejected"))
+                }
+                Probe { reg_id, width } => {
+                    rep.debug(format_args!("register {reg_id:#x} width = {width}"))
+                }
+                Read { reg_id, value } => {
+                    rep.info(format_args!("register {reg_id:#x} = {value:#x}"))
+                }
+                Rejected { call, reg_id } => rep.debug(format_args!("{call} {reg_id:#x} rejected")),
+                WriteBack(value) => {
+                    rep.info(format_args!("desired performance written back: {value:#x}"))
+                }
+                UnexpectedRet {
+                    call,
+                    reg_id,
+                    expected,
+                    ret,
+                } => {
+                    rep.error(format_args!(
+                        "{call} register {reg_id:#x}: expected {expected:?}, got {ret:?}"
+                    ));
+                    result = false;
+                }
+            }
+        });
+        let rep = self.report("STA");
+        sta::test(|case| {
+            use sta::Case::*;
+            match case {
+                NotExist => rep.not_exist(false),
+                Begin => rep.begin(),
+                Pass => rep.pass(),
+                Rejected(call) => rep.debug(format_args!("{call} rejected")),
+                ShmemSet => rep.info(format_args!("steal-time shmem set and zeroed")),
+                ShmemNotZeroed(offset) => {
+                    rep.error(format_args!(
+                        "steal-time shmem not zeroed at offset {offset}"
+                    ));
+                    result = false;
+                }
+                ShmemDisabled => rep.info(format_args!("steal-time shmem disabled")),
+                UnexpectedRet {
+                    call,
+                    expected,
+                    ret,
+                } => {
+                    rep.error(format_args!("{call}: expected {expected:?}, got {ret:?}"));
+                    result = false;
+                }
+            }
+        });
+        let rep = self.report("SSE");
+        sse::test(self.hartid, |case| {
+            use sse::Case::*;
+            match case {
+                NotExist => rep.not_exist(false),
+                Begin => rep.begin(),
+                Pass => rep.pass(),
+                MaskUnmask => rep.info(format_args!("events masked and unmasked")),
+                Rejected(call) => rep.debug(format_args!("{call} rejected")),
+                EventNotSupported(id) => rep.warn(format_args!("event {id:#x} not supported")),
+                Registered(id) => rep.debug(format_args!("event {id:#x} registered")),
+                Enabled(id) => rep.debug(format_args!("event {id:#x} enabled")),
+                Handled(id) => rep.info(format_args!("event {id:#x} injected and handled")),
+                NotHandled { event_id, count } => {
+                    rep.error(format_args!(
+                        "event {event_id:#x} handled {count} times after injection"
+                    ));
+                    result = false;
+                }
+                Unregistered(id) => rep.debug(format_args!("event {id:#x} unregistered")),
+                StateMismatch {
+                    event_id,
+                    expected,
+                    status,
+                } => {
+                    rep.error(format_args!(
+                        "event {event_id:#x} status = {status:#x}, expected state {expected}"
+                    ));
+                    result = false;
+                }
+                UnexpectedRet {
+                    call,
+                    expected,
+                    ret,
+                } => {
+                    rep.error(format_args!("{call}: expected {expected:?}, got {ret:?}"));
+                    result = false;
+                }
+            }
+        });
+        let rep = self.report("DBTR");
+        dbtr::test(|case| {
+            use dbtr::Case::*;
+            match case {
+                NotExist => rep.not_exist(false),
+                Begin => rep.begin(),
+                Pass => rep.pass(),
+                NumTriggers { total, mcontrol6 } => rep.info(format_args!(
+                    "{total} triggers, {mcontrol6} support mcontrol6"
+                )),
+                NoTriggers => rep.warn(format_args!("no mcontrol6 trigger")),
+                Rejected(call) => rep.debug(format_args!("{call} rejected")),
+                Installed(idx) => rep.debug(format_args!("trigger {idx} installed")),
+                NotMapped(idx) => {
+                    rep.error(format_args!("trigger {idx} not mapped"));
+                    result = false;
+                }
+                Updated(idx) => rep.debug(format_args!("trigger {idx} updated")),
+                Uninstalled(idx) => rep.info(format_args!("trigger {idx} uninstalled")),
+                MachineModeSanitized => rep.debug(format_args!("machine mode trigger sanitized")),
+                MachineModeInstalled(idx) => {
+                    rep.error(format_args!("machine mode trigger {idx} installed"));
+                    result = false;
+                }
+                UnexpectedRet {
+                    call,
+                    expected,
+                    ret,
+                } => {
+                    rep.error(format_args!("{call}: expected {expected:?}, got {ret:?}"));
+                    result = false;
+                }
+            }
+        });
+        // 锁定特性会影响之后的测试，放在最后
+        let rep = self.report("FWFT");
+        fwft::test(|case| {
+            use fwft::Case::*;
+            match case {
+                NotExist => rep.not_exist(false),
+                Begin => rep.begin(),
+                Pass => rep.pass(),
+                Get {
+                    feature,
+                    value: Some(value),
+                } => rep.info(format_args!("feature {feature} = {value:#x}")),
+                Get {
+                    feature,
+                    value: None,
+                } => rep.debug(format_args!("feature {feature} not supported")),
+                Rejected(call) => rep.debug(format_args!("{call} rejected")),
+                MisalignedDelegToggled => {
+                    rep.info(format_args!("misaligned exception delegation toggled"))
+                }
+                MisalignedDelegNotSupported => rep.warn(format_args!(
+                    "misaligned exception delegation not supported"
+                )),
+                Locked(feature) => rep.info(format_args!("feature {feature} locked")),
+                UnexpectedRet {
+                    call,
+                    expected,
+                    ret,
+                } => {
+                    rep.error(format_args!("{call}: expected {expected:?}, got {ret:?}"));
+                    result = false;
+                }
+                ValueMismatch {
+                    feature,
+                    expected,
+                    actual,
+                } => {
+                    rep.error(format_args!(
+                        "feature {feature} = {actual:#x}, expected {expected:#x}"
+                    ));
                     result = false;
                 }
             }
         });
         result
     }
+
+    #[inline]
+    fn report(&self, suite: &'static str) -> Report {
+        Report {
+            format: self.format,
+            suite,
+        }
+    }
+
+    fn finish(&self, passed: bool) {
+        match self.format {
+            Format::Log => {}
+            Format::Json(write) => write(format_args!(r#"{{"event":"end","passed":{passed}}}"#)),
+        }
+    }
+}
+
+/// 一个测试套件的结果输出
+#[derive(Clone, Copy)]
+struct Report {
+    format: Format,
+    suite: &'static str,
+}
+
+impl Report {
+    #[inline]
+    fn begin(self) {
+        self.emit(
+            Level::Info,
+            "begin",
+            format_args!("Testing `{}`", self.suite),
+        );
+    }
+
+    #[inline]
+    fn pass(self) {
+        self.emit(
+            Level::Info,
+            "pass",
+            format_args!("Sbi `{}` test pass", self.suite),
+        );
+    }
+
+    /// 扩展不存在；`required` 为真时视为失败
+    #[inline]
+    fn not_exist(self, required: bool) {
+        let (level, event) = if required {
+            (Level::Error, "fail")
+        } else {
+            (Level::Warn, "skip")
+        };
+        self.emit(level, event, format_args!("Sbi `{}` not exist", self.suite));
+    }
+
+    #[inline]
+    fn error(self, args: fmt::Arguments) {
+        self.emit(Level::Error, "fail", args);
+    }
+
+    #[inline]
+    fn warn(self, args: fmt::Arguments) {
+        self.emit(Level::Warn, "warn", args);
+    }
+
+    #[inline]
+    fn info(self, args: fmt::Arguments) {
+        self.emit(Level::Info, "info", args);
+    }
+
+    #[inline]
+    fn debug(self, args: fmt::Arguments) {
+        self.emit(Level::Debug, "debug", args);
+    }
+
+    fn emit(self, level: Level, event: &str, args: fmt::Arguments) {
+        match self.format {
+            Format::Log => log!(target: TARGET, level, "{args}"),
+            Format::Json(write) => write(format_args!(
+                r#"{{"suite":"{}","event":"{event}","message":"{}"}}"#,
+                self.suite,
+                Escape(args),
+            )),
+        }
+    }
+}
+
+/// 按 JSON 字符串的规则转义
+struct Escape<'a>(fmt::Arguments<'a>);
+
+impl fmt::Display for Escape<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        struct Escaper<'a, 'b>(&'a mut fmt::Formatter<'b>);
+
+        impl Write for Escaper<'_, '_> {
+            fn write_str(&mut self, s: &str) -> fmt::Result {
+                for c in s.chars() {
+                    match c {
+                        '"' => self.0.write_str("\\\"")?,
+                        '\\' => self.0.write_str("\\\\")?,
+                        '\n' => self.0.write_str("\\n")?,
+                        c if (c as u32) < 0x20 => write!(self.0, "\\u{:04x}", c as u32)?,
+                        c => self.0.write_char(c)?,
+                    }
+                }
+                Ok(())
+            }
+        }
+
+        Escaper(f).write_fmt(self.0)
+    }
 }
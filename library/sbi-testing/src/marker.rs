@@ -0,0 +1,88 @@
+//! Persistent state for test suites that do not return on success.
+
+/// Marker area shared by test suites across system reset and system suspend.
+///
+/// Calls such as `system_reset` and `system_suspend` do not return on success;
+/// the firmware re-enters the test kernel instead. The marker records which case
+/// runs next and whether any case has failed, so the test kernel can call the
+/// same suites again upon re-entry and continue where it stopped.
+///
+/// The marker must be placed in memory that is neither loaded from the kernel image
+/// nor cleared on startup, e.g. an uninitialized `.bss` section.
+#[repr(C)]
+pub struct Marker {
+    magic: usize,
+    failed: usize,
+    pub(crate) srst: usize,
+    pub(crate) susp: usize,
+    pub(crate) hartid: usize,
+    pub(crate) opaque: usize,
+}
+
+/// Arguments which the test kernel has been entered with.
+#[derive(Clone, Copy, Debug)]
+pub struct Entry {
+    /// Hart ID in `a0` upon entry.
+    pub hartid: usize,
+    /// Value in `a1` upon entry; also passed as `opaque` to `system_suspend`.
+    pub opaque: usize,
+    /// Physical address the hart resumes at after a system suspend.
+    pub resume_addr: usize,
+}
+
+const MAGIC: usize = 0x5342_4954; // "SBIT"
+
+impl Marker {
+    /// Create an invalid marker.
+    #[inline]
+    pub const fn new() -> Self {
+        Self {
+            magic: 0,
+            failed: 0,
+            srst: 0,
+            susp: 0,
+            hartid: 0,
+            opaque: 0,
+        }
+    }
+
+    /// Returns true if no test is in progress, i.e. the test kernel is not re-entered.
+    #[inline]
+    pub fn is_fresh(&self) -> bool {
+        self.magic != MAGIC
+    }
+
+    /// Start a new testing process.
+    #[inline]
+    pub fn begin(&mut self) {
+        *self = Self {
+            magic: MAGIC,
+            ..Self::new()
+        };
+    }
+
+    /// Record a failed test case.
+    #[inline]
+    pub fn fail(&mut self) {
+        self.failed = 1;
+    }
+
+    /// Returns true if no test case has failed since [`Marker::begin`].
+    #[inline]
+    pub fn passed(&self) -> bool {
+        self.failed == 0
+    }
+
+    /// Finish the testing process; the next entry would be a fresh one.
+    #[inline]
+    pub fn finish(&mut self) {
+        self.magic = 0;
+    }
+}
+
+impl Default for Marker {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
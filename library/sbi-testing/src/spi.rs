@@ -40,7 +40,7 @@ pub fn test(hart_id: usize, mut f: impl FnMut(Case)) {
 
     f(Case::Begin);
     let mut stack = [0usize; 32];
-    let mut thread = Thread::new(ipi as _);
+    let mut thread = Thread::new(ipi as *const () as _);
     *thread.sp_mut() = stack.as_mut_ptr_range().end as _;
     *thread.a_mut(0) = hart_id;
     unsafe {
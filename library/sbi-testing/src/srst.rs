@@ -0,0 +1,153 @@
+//! System reset extension test suite.
+
+use crate::Marker;
+use sbi::SbiRet;
+use sbi_spec::srst::{RESET_REASON_NO_REASON, RESET_TYPE_WARM_REBOOT};
+
+/// System reset extension test cases.
+#[derive(Clone, Debug)]
+pub enum Case {
+    /// Can't proceed test for system reset extension does not exist.
+    NotExist,
+    /// Test begin.
+    Begin,
+    /// Test process for a reserved reset type has been rejected.
+    ReservedTypeRejected(u32),
+    /// Test process for a reserved reset reason has been rejected.
+    ReservedReasonRejected(u32),
+    /// Test failed for reserved reset type or reason is not rejected with `SBI_ERR_INVALID_PARAM`.
+    ReservedNotRejected {
+        /// The reset type passed to `system_reset`.
+        reset_type: u32,
+        /// The reset reason passed to `system_reset`.
+        reset_reason: u32,
+        /// The `SbiRet` value returned by `system_reset`.
+        ret: SbiRet,
+    },
+    /// Test failed for the system has been reset on reserved reset type or reason.
+    UnexpectedReset {
+        /// The reset type passed to `system_reset`.
+        reset_type: u32,
+        /// The reset reason passed to `system_reset`.
+        reset_reason: u32,
+    },
+    /// Test failed for the marker records an unknown stage, and is likely corrupted.
+    ///
+    /// The returned value includes the stage recorded.
+    CorruptedMarker(usize),
+    /// Test process for warm reboot is about to be requested.
+    WarmReboot,
+    /// Test process for warm reboot is not supported on this platform.
+    WarmRebootNotSupported,
+    /// Test failed for warm reboot request has returned with [`SbiRet`] error.
+    WarmRebootFailed(SbiRet),
+    /// Test process for the test kernel has been re-entered after warm reboot.
+    Rebooted,
+    /// All test cases on system reset extension finished.
+    Pass,
+}
+
+/// Reserved reset types and reasons, none of which should reset the system.
+///
+/// Warm reboot is used along with reserved reasons, so that a firmware
+/// accepting them re-enters the test kernel rather than powering off.
+const RESERVED: [(u32, u32); 4] = [
+    (0x0000_0003, RESET_REASON_NO_REASON),
+    (0xEFFF_FFFF, RESET_REASON_NO_REASON),
+    (RESET_TYPE_WARM_REBOOT, 0x0000_0002),
+    (RESET_TYPE_WARM_REBOOT, 0xDFFF_FFFF),
+];
+
+const NOT_STARTED: usize = 0;
+const WARM_REBOOT: usize = RESERVED.len() + 1;
+const FINISHED: usize = RESERVED.len() + 2;
+
+#[derive(Clone, Copy)]
+struct Raw(u32);
+
+impl sbi::ResetType for Raw {
+    #[inline]
+    fn raw(&self) -> u32 {
+        self.0
+    }
+}
+
+impl sbi::ResetReason for Raw {
+    #[inline]
+    fn raw(&self) -> u32 {
+        self.0
+    }
+}
+
+/// Test system reset extension.
+///
+/// The case in progress is recorded in `marker`, and this function should be called
+/// again with the same `marker` after the test kernel is re-entered.
+/// Warm reboot is requested only if `reboot` is true.
+pub fn test(marker: &mut Marker, reboot: bool, mut f: impl FnMut(Case)) {
+    if sbi::probe_extension(sbi::Reset).is_unavailable() {
+        f(Case::NotExist);
+        return;
+    }
+    if marker.is_fresh() {
+        marker.begin();
+    }
+    let next = match marker.srst {
+        NOT_STARTED => {
+            f(Case::Begin);
+            0
+        }
+        WARM_REBOOT => {
+            marker.srst = FINISHED;
+            f(Case::Rebooted);
+            f(Case::Pass);
+            return;
+        }
+        FINISHED => return,
+        // 保留的类型或原因导致了系统复位
+        stage => {
+            marker.fail();
+            let Some(&(reset_type, reset_reason)) = RESERVED.get(stage.wrapping_sub(1)) else {
+                marker.srst = FINISHED;
+                f(Case::CorruptedMarker(stage));
+                return;
+            };
+            f(Case::UnexpectedReset {
+                reset_type,
+                reset_reason,
+            });
+            stage
+        }
+    };
+    for (i, (reset_type, reset_reason)) in RESERVED.iter().copied().enumerate().skip(next) {
+        marker.srst = i + 1;
+        let ret = sbi::system_reset(Raw(reset_type), Raw(reset_reason));
+        if ret != SbiRet::invalid_param() {
+            marker.fail();
+            f(Case::ReservedNotRejected {
+                reset_type,
+                reset_reason,
+                ret,
+            });
+        } else if reset_type == RESET_TYPE_WARM_REBOOT {
+            f(Case::ReservedReasonRejected(reset_reason));
+        } else {
+            f(Case::ReservedTypeRejected(reset_type));
+        }
+    }
+    if reboot {
+        marker.srst = WARM_REBOOT;
+        f(Case::WarmReboot);
+        let ret = sbi::system_reset(sbi::WarmReboot, sbi::NoReason);
+        marker.srst = FINISHED;
+        if ret == SbiRet::not_supported() {
+            f(Case::WarmRebootNotSupported);
+        } else {
+            marker.fail();
+            f(Case::WarmRebootFailed(ret));
+            return;
+        }
+    }
+    marker.srst = FINISHED;
+    f(Case::Pass);
+}
@@ -0,0 +1,282 @@
+//! Supervisor software events extension test suite.
+
+use crate::expect;
+use core::sync::atomic::{AtomicU32, Ordering};
+use sbi::{SbiRet, SharedPtr};
+use sbi_spec::sse::{attr_id, event_id, event_state, status};
+
+/// Supervisor software events extension test cases.
+#[derive(Clone, Debug)]
+pub enum Case {
+    /// Can't proceed test for supervisor software events extension does not exist.
+    NotExist,
+    /// Test begin.
+    Begin,
+    /// Test process for software events have been masked and unmasked on the calling hart.
+    MaskUnmask,
+    /// Test process for an invalid call has been rejected with the expected error.
+    Rejected(&'static str),
+    /// Test process for the event under test is not supported on this platform.
+    ///
+    /// The returned value includes the event ID.
+    EventNotSupported(u32),
+    /// Test process for the event has been registered.
+    Registered(u32),
+    /// Test process for the event has been enabled.
+    Enabled(u32),
+    /// Test process for the event handler has run after the event is injected.
+    Handled(u32),
+    /// Test failed for the event handler has not run, or run more than once after injection.
+    NotHandled {
+        /// The event ID.
+        event_id: u32,
+        /// Times the event handler has run.
+        count: u32,
+    },
+    /// Test process for the event has been disabled and unregistered.
+    Unregistered(u32),
+    /// Test failed for the event is in another state than expected.
+    StateMismatch {
+        /// The event ID.
+        event_id: u32,
+        /// The expected event state.
+        expected: usize,
+        /// The `STATUS` attribute of the event.
+        status: usize,
+    },
+    /// Test failed for an SBI call has returned an unexpected error.
+    UnexpectedRet {
+        /// The case in which the SBI call is made.
+        call: &'static str,
+        /// The expected `SbiRet` value; only `error` field is compared.
+        expected: SbiRet,
+        /// The `SbiRet` value returned by the SBI call.
+        ret: SbiRet,
+    },
+    /// All test cases on supervisor software events extension finished.
+    Pass,
+}
+
+/// A reserved event ID in the local software event range.
+const RESERVED_EVENT: u32 = 0xFFFF_0001;
+/// The first reserved attribute ID.
+const RESERVED_ATTR: u32 = attr_id::INTERRUPTED_A7 + 1;
+
+/// Test supervisor software events extension.
+///
+/// Local software injected event is registered, enabled and injected on `hartid`,
+/// which must be the calling hart.
+pub fn test(hartid: usize, mut f: impl FnMut(Case)) {
+    if sbi::probe_extension(sbi::Sse).is_unavailable() {
+        f(Case::NotExist);
+        return;
+    }
+
+    f(Case::Begin);
+
+    // 事件初始为屏蔽状态，但调用者可能已经解除了屏蔽
+    let ret = sbi::sse_hart_unmask();
+    if ret.error != SbiRet::already_started().error {
+        expect(&mut f, "unmask", SbiRet::success(0), ret);
+    }
+    let mut masked = true;
+    for (call, ret, expected) in [
+        ("mask", sbi::sse_hart_mask(), SbiRet::success(0)),
+        (
+            "mask twice",
+            sbi::sse_hart_mask(),
+            SbiRet::already_stopped(),
+        ),
+        ("unmask", sbi::sse_hart_unmask(), SbiRet::success(0)),
+        (
+            "unmask twice",
+            sbi::sse_hart_unmask(),
+            SbiRet::already_started(),
+        ),
+    ] {
+        masked &= expect(&mut f, call, expected, ret);
+    }
+    if masked {
+        f(Case::MaskUnmask);
+    }
+
+    let event = event_id::LOCAL_SOFTWARE_INJECTED;
+    let entry = handler as *const () as usize;
+    let mut buf = [0usize; 2];
+    let output = SharedPtr::new(buf.as_mut_ptr() as usize, 0);
+    let ret = sbi::sse_read_attrs(event, attr_id::STATUS, 1, output);
+    if ret.error == SbiRet::not_supported().error {
+        f(Case::EventNotSupported(event));
+        f(Case::Pass);
+        return;
+    }
+    for (call, ret, expected) in [
+        (
+            "read attributes of reserved event",
+            sbi::sse_read_attrs(RESERVED_EVENT, attr_id::STATUS, 1, output),
+            SbiRet::invalid_param(),
+        ),
+        (
+            "register reserved event",
+            sbi::sse_register(RESERVED_EVENT, entry, 0),
+            SbiRet::invalid_param(),
+        ),
+        (
+            "read zero attributes",
+            sbi::sse_read_attrs(event, attr_id::STATUS, 0, output),
+            SbiRet::invalid_param(),
+        ),
+        (
+            "read reserved attribute",
+            sbi::sse_read_attrs(event, RESERVED_ATTR, 1, output),
+            SbiRet::bad_range(),
+        ),
+        (
+            "read attributes to misaligned output",
+            sbi::sse_read_attrs(
+                event,
+                attr_id::STATUS,
+                1,
+                SharedPtr::new(buf.as_mut_ptr() as usize + 1, 0),
+            ),
+            SbiRet::invalid_address(),
+        ),
+    ] {
+        if expect(&mut f, call, expected, ret) {
+            f(Case::Rejected(call));
+        }
+    }
+
+    if !check_state(&mut f, event, &mut buf, event_state::UNUSED) {
+        return;
+    }
+    let counter = (&raw const COUNTER) as usize;
+    let ret = sbi::sse_register(event, entry + 1, counter);
+    if expect(
+        &mut f,
+        "register misaligned entry",
+        SbiRet::invalid_param(),
+        ret,
+    ) {
+        f(Case::Rejected("register misaligned entry"));
+    }
+    let ret = sbi::sse_enable(event);
+    if expect(
+        &mut f,
+        "enable unregistered event",
+        SbiRet::invalid_state(),
+        ret,
+    ) {
+        f(Case::Rejected("enable unregistered event"));
+    }
+
+    let ret = sbi::sse_register(event, entry, counter);
+    if !expect(&mut f, "register", SbiRet::success(0), ret) {
+        return;
+    }
+    if check_state(&mut f, event, &mut buf, event_state::REGISTERED) {
+        f(Case::Registered(event));
+    }
+    let ret = sbi::sse_register(event, entry, counter);
+    if expect(&mut f, "register twice", SbiRet::invalid_state(), ret) {
+        f(Case::Rejected("register twice"));
+    }
+    buf[0] = entry;
+    let ret = sbi::sse_write_attrs(event, attr_id::ENTRY_PC, 1, output);
+    if expect(
+        &mut f,
+        "write read-only attribute",
+        SbiRet::bad_range(),
+        ret,
+    ) {
+        f(Case::Rejected("write read-only attribute"));
+    }
+
+    let ret = sbi::sse_enable(event);
+    if expect(&mut f, "enable", SbiRet::success(0), ret)
+        && check_state(&mut f, event, &mut buf, event_state::ENABLED)
+    {
+        f(Case::Enabled(event));
+        // 本地事件在注入调用返回前处理完成
+        COUNTER.store(0, Ordering::Release);
+        let ret = sbi::sse_inject(event, hartid);
+        if expect(&mut f, "inject", SbiRet::success(0), ret) {
+            match COUNTER.load(Ordering::Acquire) {
+                1 => f(Case::Handled(event)),
+                count => f(Case::NotHandled {
+                    event_id: event,
+                    count,
+                }),
+            }
+            check_state(&mut f, event, &mut buf, event_state::ENABLED);
+        }
+        let ret = sbi::sse_disable(event);
+        expect(&mut f, "disable", SbiRet::success(0), ret);
+    }
+
+    let ret = sbi::sse_unregister(event);
+    if expect(&mut f, "unregister", SbiRet::success(0), ret)
+        && check_state(&mut f, event, &mut buf, event_state::UNUSED)
+    {
+        f(Case::Unregistered(event));
+    }
+    let ret = sbi::sse_disable(event);
+    if expect(
+        &mut f,
+        "disable unregistered event",
+        SbiRet::invalid_state(),
+        ret,
+    ) {
+        f(Case::Rejected("disable unregistered event"));
+    }
+
+    f(Case::Pass);
+}
+
+/// 读取事件状态，检查是否符合预期
+fn check_state(
+    f: &mut impl FnMut(Case),
+    event_id: u32,
+    buf: &mut [usize; 2],
+    expected: usize,
+) -> bool {
+    let output = SharedPtr::new(buf.as_mut_ptr() as usize, 0);
+    let ret = sbi::sse_read_attrs(event_id, attr_id::STATUS, 1, output);
+    if !expect(f, "read status", SbiRet::success(0), ret) {
+        return false;
+    }
+    let status = unsafe { core::ptr::read_volatile(&buf[0]) };
+    if status & status::STATE_MASK == expected {
+        true
+    } else {
+        f(Case::StateMismatch {
+            event_id,
+            expected,
+            status,
+        });
+        false
+    }
+}
+
+impl_unexpected_ret!(Case);
+
+/// 事件处理函数运行的次数
+static COUNTER: AtomicU32 = AtomicU32::new(0);
+
+/// 事件处理函数
+///
+/// 进入时 `a6` 为核号，`a7` 为注册时传入的参数，即计数器地址。
+/// 被打断的 `a6`、`a7` 由 SBI 实现保存，完成事件时恢复。
+#[unsafe(naked)]
+unsafe extern "C" fn handler() -> ! {
+    core::arch::naked_asm!(
+        "li       a6, 1",
+        "amoadd.w zero, a6, (a7)", // 计数器加一
+        "li       a7, {eid}",
+        "li       a6, {fid}",
+        "ecall",                   // sse_complete，成功则不返回
+        "unimp",
+        eid = const sbi_spec::sse::EID_SSE,
+        fid = const sbi_spec::sse::COMPLETE,
+    )
+}
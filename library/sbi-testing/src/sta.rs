@@ -0,0 +1,84 @@
+//! Steal-time accounting extension test suite.
+
+use crate::expect;
+use sbi::{SbiRet, SharedPtr};
+
+/// Steal-time accounting extension test cases.
+#[derive(Clone, Debug)]
+pub enum Case {
+    /// Can't proceed test for steal-time accounting extension does not exist.
+    NotExist,
+    /// Test begin.
+    Begin,
+    /// Test process for an invalid call has been rejected with the expected error.
+    Rejected(&'static str),
+    /// Test process for steal-time shared memory has been set and cleared.
+    ShmemSet,
+    /// Test failed for steal-time shared memory is not zeroed on set.
+    ///
+    /// The returned value includes the offset of the first non-zero byte.
+    ShmemNotZeroed(usize),
+    /// Test process for steal-time shared memory has been disabled.
+    ShmemDisabled,
+    /// Test failed for an SBI call has returned an unexpected error.
+    UnexpectedRet {
+        /// The case in which the SBI call is made.
+        call: &'static str,
+        /// The expected `SbiRet` value; only `error` field is compared.
+        expected: SbiRet,
+        /// The `SbiRet` value returned by the SBI call.
+        ret: SbiRet,
+    },
+    /// All test cases on steal-time accounting extension finished.
+    Pass,
+}
+
+/// Test steal-time accounting extension.
+pub fn test(mut f: impl FnMut(Case)) {
+    if sbi::probe_extension(sbi::Sta).is_unavailable() {
+        f(Case::NotExist);
+        return;
+    }
+
+    f(Case::Begin);
+
+    let shmem = &raw mut SHMEM;
+    let addr = shmem as usize;
+
+    let ret = sbi::sta_set_shmem(SharedPtr::new(addr, 0), 1);
+    if expect(
+        &mut f,
+        "set shmem with reserved flags",
+        SbiRet::invalid_param(),
+        ret,
+    ) {
+        f(Case::Rejected("set shmem with reserved flags"));
+    }
+    let ret = sbi::sta_set_shmem(SharedPtr::new(addr + 32, 0), 0);
+    if expect(&mut f, "set misaligned shmem", SbiRet::invalid_param(), ret) {
+        f(Case::Rejected("set misaligned shmem"));
+    }
+    // 填充非零值，设置成功后应被清零
+    unsafe { shmem.write_volatile(Shmem([0xff; 64])) };
+    let ret = sbi::sta_set_shmem(SharedPtr::new(addr, 0), 0);
+    if expect(&mut f, "set shmem", SbiRet::success(0), ret) {
+        let Shmem(bytes) = unsafe { shmem.read_volatile() };
+        match bytes.iter().position(|b| *b != 0) {
+            Some(offset) => f(Case::ShmemNotZeroed(offset)),
+            None => f(Case::ShmemSet),
+        }
+    }
+    let ret = sbi::sta_set_shmem(SharedPtr::new(usize::MAX, usize::MAX), 0);
+    if expect(&mut f, "disable shmem", SbiRet::success(0), ret) {
+        f(Case::ShmemDisabled);
+    }
+
+    f(Case::Pass);
+}
+
+impl_unexpected_ret!(Case);
+
+static mut SHMEM: Shmem = Shmem([0; 64]);
+
+#[repr(C, align(64))]
+struct Shmem([u8; 64]);
@@ -0,0 +1,365 @@
+//! System suspend extension test suite.
+
+use crate::{Entry, Marker};
+use core::{
+    cell::UnsafeCell,
+    sync::atomic::{AtomicU32, Ordering},
+};
+use riscv::register::{sie, time};
+use sbi::SbiRet;
+use sbi_spec::hsm::hart_state;
+
+/// System suspend extension test cases.
+#[derive(Clone, Debug)]
+pub enum Case {
+    /// Can't proceed test for system suspend extension does not exist.
+    NotExist,
+    /// Test begin.
+    Begin,
+    /// Test process for a reserved sleep type has been rejected.
+    ReservedTypeRejected(u32),
+    /// Test failed for reserved sleep type is not rejected with `SBI_ERR_INVALID_PARAM`.
+    ReservedTypeNotRejected {
+        /// The sleep type passed to `system_suspend`.
+        sleep_type: u32,
+        /// The `SbiRet` value returned by `system_suspend`.
+        ret: SbiRet,
+    },
+    /// Test failed for the system has been suspended on a request that should fail.
+    ///
+    /// The returned value includes the sleep type of this request.
+    UnexpectedResume(u32),
+    /// Test failed for the marker records an unknown stage, and is likely corrupted.
+    ///
+    /// The returned value includes the stage recorded.
+    CorruptedMarker(usize),
+    /// Test process for suspend request has been denied while another hart is started.
+    ///
+    /// The returned value includes which hart has been started.
+    Denied(usize),
+    /// Test failed for suspend request is not denied while another hart is started.
+    NotDenied {
+        /// The hart started during this request.
+        hartid: usize,
+        /// The `SbiRet` value returned by `system_suspend`.
+        ret: SbiRet,
+    },
+    /// Test process for suspend request at an invalid `resume_addr` has been rejected.
+    InvalidAddressRejected(usize),
+    /// Test failed for suspend request at an invalid `resume_addr` is not rejected with `SBI_ERR_INVALID_ADDRESS`.
+    InvalidAddressNotRejected {
+        /// The `resume_addr` passed to `system_suspend`.
+        resume_addr: usize,
+        /// The `SbiRet` value returned by `system_suspend`.
+        ret: SbiRet,
+    },
+    /// Test failed for can't start another hart with [`SbiRet`] error.
+    HartStartFailed {
+        /// The target hart ID that has failed to start.
+        hartid: usize,
+        /// The `SbiRet` value for the failed hart start SBI call.
+        ret: SbiRet,
+    },
+    /// Test process for no other stopped hart is available to check entry criteria.
+    NoStoppedHart,
+    /// Test process for suspend to RAM is skipped for no timer is available to wake the system up.
+    NoWakeUpSource,
+    /// Test process for suspend to RAM is about to be requested.
+    Suspend,
+    /// Test process for suspend to RAM is not supported on this platform.
+    SuspendNotSupported,
+    /// Test failed for suspend to RAM request has returned with [`SbiRet`] error.
+    SuspendFailed(SbiRet),
+    /// Test process for the hart has resumed at `resume_addr` with `opaque` in `a1`.
+    Resumed,
+    /// Test failed for the system resumed on another hart.
+    ResumedOnOtherHart {
+        /// The hart which has requested suspend.
+        expected: usize,
+        /// The hart which has resumed.
+        actual: usize,
+    },
+    /// Test failed for `a1` on resume does not match `opaque`.
+    OpaqueMismatch {
+        /// The `opaque` value passed to `system_suspend`.
+        expected: usize,
+        /// The `a1` value on resume.
+        actual: usize,
+    },
+    /// All test cases on system suspend extension finished.
+    Pass,
+}
+
+/// Reserved sleep types, none of which should suspend the system.
+const RESERVED: [u32; 2] = [0x0000_0001, 0x7FFF_FFFF];
+
+const NOT_STARTED: usize = 0;
+const DENIED: usize = RESERVED.len() + 1;
+const INVALID_ADDRESS: usize = RESERVED.len() + 2;
+const SUSPENDED: usize = RESERVED.len() + 3;
+const FINISHED: usize = RESERVED.len() + 4;
+
+/// A resume address beyond any physical memory.
+const INVALID_RESUME_ADDR: usize = usize::MAX & !3;
+
+const SUSPEND_TO_RAM: u32 = 0;
+
+const STARTED: SbiRet = SbiRet::success(hart_state::STARTED);
+const STOPPED: SbiRet = SbiRet::success(hart_state::STOPPED);
+
+#[derive(Clone, Copy)]
+struct Raw(u32);
+
+impl sbi::SleepType for Raw {
+    #[inline]
+    fn raw(&self) -> u32 {
+        self.0
+    }
+}
+
+/// Test system suspend extension.
+///
+/// Entry criteria are checked against the first stopped hart in `hart_mask` other than
+/// `entry.hartid`. The case in progress is recorded in `marker`, and this function
+/// should be called again with the same `marker` after the test kernel is re-entered
+/// at `entry.resume_addr`.
+///
+/// A timer interrupt is armed `delay` ticks ahead to wake the system up from suspend to RAM.
+pub fn test(
+    marker: &mut Marker,
+    entry: Entry,
+    delay: u64,
+    hart_mask: usize,
+    hart_mask_base: usize,
+    mut f: impl FnMut(Case),
+) {
+    if sbi::probe_extension(sbi::Suspend).is_unavailable() {
+        f(Case::NotExist);
+        return;
+    }
+    if marker.is_fresh() {
+        marker.begin();
+    }
+    let next = match marker.susp {
+        NOT_STARTED => {
+            f(Case::Begin);
+            0
+        }
+        SUSPENDED => {
+            marker.susp = FINISHED;
+            disarm_timer();
+            if entry.hartid != marker.hartid {
+                marker.fail();
+                f(Case::ResumedOnOtherHart {
+                    expected: marker.hartid,
+                    actual: entry.hartid,
+                });
+            } else if entry.opaque != marker.opaque {
+                marker.fail();
+                f(Case::OpaqueMismatch {
+                    expected: marker.opaque,
+                    actual: entry.opaque,
+                });
+            } else {
+                f(Case::Resumed);
+                f(Case::Pass);
+            }
+            return;
+        }
+        FINISHED => return,
+        // 本应失败的请求使系统进入了休眠
+        stage => {
+            marker.fail();
+            let sleep_type = match stage {
+                DENIED | INVALID_ADDRESS => SUSPEND_TO_RAM,
+                _ => match RESERVED.get(stage.wrapping_sub(1)) {
+                    Some(&sleep_type) => sleep_type,
+                    None => {
+                        marker.susp = FINISHED;
+                        f(Case::CorruptedMarker(stage));
+                        return;
+                    }
+                },
+            };
+            f(Case::UnexpectedResume(sleep_type));
+            stage
+        }
+    };
+    for (i, sleep_type) in RESERVED.iter().copied().enumerate().skip(next) {
+        marker.susp = i + 1;
+        let ret = sbi::system_suspend(Raw(sleep_type), entry.resume_addr, entry.opaque);
+        if ret == SbiRet::invalid_param() {
+            f(Case::ReservedTypeRejected(sleep_type));
+        } else {
+            marker.fail();
+            f(Case::ReservedTypeNotRejected { sleep_type, ret });
+        }
+    }
+    if next < DENIED {
+        marker.susp = DENIED;
+        test_denied(marker, entry, hart_mask, hart_mask_base, &mut f);
+    }
+    if next < INVALID_ADDRESS {
+        marker.susp = INVALID_ADDRESS;
+        let ret = sbi::system_suspend(sbi::SuspendToRam, INVALID_RESUME_ADDR, entry.opaque);
+        // 平台不支持休眠到内存时留给后续用例报告
+        if ret == SbiRet::invalid_address() {
+            f(Case::InvalidAddressRejected(INVALID_RESUME_ADDR));
+        } else if ret != SbiRet::not_supported() {
+            marker.fail();
+            f(Case::InvalidAddressNotRejected {
+                resume_addr: INVALID_RESUME_ADDR,
+                ret,
+            });
+        }
+    }
+
+    // 没有唤醒源时系统无法从休眠中恢复
+    if sbi::probe_extension(sbi::Timer).is_unavailable() {
+        marker.susp = FINISHED;
+        f(Case::NoWakeUpSource);
+        f(Case::Pass);
+        return;
+    }
+    marker.susp = SUSPENDED;
+    marker.hartid = entry.hartid;
+    marker.opaque = entry.opaque;
+    f(Case::Suspend);
+    // 设置定时器中断作为唤醒源
+    sbi::set_timer(time::read64() + delay);
+    unsafe { sie::set_stimer() };
+    let ret = sbi::system_suspend(sbi::SuspendToRam, entry.resume_addr, entry.opaque);
+    marker.susp = FINISHED;
+    disarm_timer();
+    if ret == SbiRet::not_supported() {
+        f(Case::SuspendNotSupported);
+        f(Case::Pass);
+    } else {
+        marker.fail();
+        f(Case::SuspendFailed(ret));
+    }
+}
+
+/// 撤销作为唤醒源的定时器中断
+fn disarm_timer() {
+    sbi::set_timer(u64::MAX);
+    unsafe { sie::clear_stimer() };
+}
+
+/// 启动另一个核，检查系统休眠的前置条件
+fn test_denied(
+    marker: &mut Marker,
+    entry: Entry,
+    mut hart_mask: usize,
+    hart_mask_base: usize,
+    mut f: impl FnMut(Case),
+) {
+    if sbi::probe_extension(sbi::Hsm).is_unavailable() {
+        f(Case::NoStoppedHart);
+        return;
+    }
+    // 找到一个处于停止状态的副核
+    let mut hartid = hart_mask_base;
+    let hartid = loop {
+        if hart_mask == 0 {
+            f(Case::NoStoppedHart);
+            return;
+        }
+        if hart_mask & 1 == 1 && hartid != entry.hartid && sbi::hart_get_status(hartid) == STOPPED {
+            break hartid;
+        }
+        hart_mask >>= 1;
+        hartid += 1;
+    };
+    let item = &SECONDARY;
+    let ret = sbi::hart_start(hartid, secondary_entry as *const () as _, item.reset() as _);
+    if ret.is_err() {
+        marker.fail();
+        f(Case::HartStartFailed { hartid, ret });
+        return;
+    }
+    // 等待副核完成启动
+    while sbi::hart_get_status(hartid) != STARTED {
+        core::hint::spin_loop();
+    }
+    item.wait_start();
+    let ret = sbi::system_suspend(sbi::SuspendToRam, entry.resume_addr, entry.opaque);
+    if ret == SbiRet::denied() {
+        f(Case::Denied(hartid));
+    } else {
+        marker.fail();
+        f(Case::NotDenied { hartid, ret });
+    }
+    // 关闭副核
+    item.send_signal();
+    while sbi::hart_get_status(hartid) != STOPPED {
+        core::hint::spin_loop();
+    }
+}
+
+static SECONDARY: Secondary = Secondary::ZERO;
+
+#[repr(C, align(512))]
+struct Secondary {
+    started: AtomicU32,
+    signal: AtomicU32,
+    stack: UnsafeCell<[u8; 504]>,
+}
+
+// 栈只由副核使用
+unsafe impl Sync for Secondary {}
+
+impl Secondary {
+    #[allow(clippy::declare_interior_mutable_const)]
+    const ZERO: Self = Self {
+        started: AtomicU32::new(0),
+        signal: AtomicU32::new(0),
+        stack: UnsafeCell::new([0; 504]),
+    };
+
+    #[inline]
+    fn reset(&self) -> *const Secondary {
+        self.started.store(0, Ordering::Relaxed);
+        self.signal.store(0, Ordering::Relaxed);
+        self as _
+    }
+
+    #[inline]
+    fn wait_start(&self) {
+        while self.started.load(Ordering::Acquire) == 0 {
+            core::hint::spin_loop();
+        }
+    }
+
+    #[inline]
+    fn send_signal(&self) {
+        self.signal.store(1, Ordering::Release);
+    }
+
+    #[inline]
+    fn wait_signal(&self) {
+        while self.signal.load(Ordering::Acquire) == 0 {
+            core::hint::spin_loop();
+        }
+    }
+}
+
+/// 副核启动入口
+#[unsafe(naked)]
+unsafe extern "C" fn secondary_entry(hartid: usize, opaque: *const Secondary) -> ! {
+    core::arch::naked_asm!(
+        "csrw sie, zero",   // 关中断
+        "addi sp, a1, 512", // 设置栈
+        "j    {rust_main}", // 进入 rust
+        rust_main = sym secondary_main,
+    )
+}
+
+#[inline(never)]
+extern "C" fn secondary_main(hartid: usize, opaque: *const Secondary) -> ! {
+    let item = unsafe { &*opaque };
+    item.started.store(1, Ordering::Release);
+    item.wait_signal();
+    let ret = sbi::hart_stop();
+    unreachable!("stop [{hartid}] but {ret:?}")
+}
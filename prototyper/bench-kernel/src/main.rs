@@ -56,6 +56,11 @@ unsafe extern "C" fn _boot_header() -> ! {
 
 const STACK_SIZE: usize = 512 * 1024; // 512 KiB
 const MAX_HART_NUM: usize = 128;
+const TEST_ROUNDS: usize = 4;
+const PAGE_SIZE: usize = 4096;
+/// Pages flushed one by one by each hart after its full flush.
+const RANGED_FENCE_PAGES: usize = 16;
+const RANGED_FENCE_BASE: usize = 0x4000_0000;
 
 #[allow(dead_code)]
 #[derive(Copy, Clone)]
@@ -135,24 +140,16 @@ extern "C" fn send_ipi(hartid: usize) -> ! {
                 .assume_init_mut()
                 .swap(true, Ordering::AcqRel);
         };
-        let mut mask = Some(HartMask::from_mask_base(0, 0));
-        for i in 0..unsafe { SMP_COUNT } {
-            if i == unsafe { BOOT_HART_ID } {
-                continue;
-            }
-            if let Some(ref mut mask) = mask {
-                match mask.insert(i) {
-                    Ok(_) => continue,
-                    Err(MaskError::InvalidBit) => {
-                        sbi::remote_sfence_vma(*mask, 0, 0);
-                    }
-                    Err(_) => unreachable!("Failed to construct mask"),
-                }
-            }
-            mask = Some(HartMask::from_mask_base(0b1, i));
-        }
-        if let Some(mask) = mask {
+        let (smp, boot_hart_id) = unsafe { (SMP_COUNT, BOOT_HART_ID) };
+        for_each_hart_mask(smp, boot_hart_id, |mask| {
             sbi::remote_sfence_vma(mask, 0, 0);
+        });
+        // Flush pages one by one like `munmap`, all harts flushing the same pages at once.
+        for page in 0..RANGED_FENCE_PAGES {
+            let start_addr = RANGED_FENCE_BASE + page * PAGE_SIZE;
+            for_each_hart_mask(smp, boot_hart_id, |mask| {
+                sbi::remote_sfence_vma(mask, start_addr, PAGE_SIZE);
+            });
         }
         unsafe {
             WAIT_COUNT.fetch_sub(1, Ordering::AcqRel);
@@ -161,14 +158,14 @@ extern "C" fn send_ipi(hartid: usize) -> ! {
     } else {
         unreachable!("resend {}", hartid);
     }
-    sbi::hart_suspend(sbi::NonRetentive, core_send_ipi as _, unsafe {
+    sbi::hart_suspend(sbi::NonRetentive, core_send_ipi as *const () as _, unsafe {
         core::ptr::addr_of!(HART_STACK[hartid + 1]) as _
     });
     unreachable!()
 }
 
 extern "C" fn init_main(hartid: usize) -> ! {
-    sbi::hart_suspend(sbi::NonRetentive, core_send_ipi as _, unsafe {
+    sbi::hart_suspend(sbi::NonRetentive, core_send_ipi as *const () as _, unsafe {
         core::ptr::addr_of!(HART_STACK[hartid + 1]) as _
     });
     unreachable!()
@@ -176,6 +173,29 @@ extern "C" fn init_main(hartid: usize) -> ! {
 
 static mut WAIT_COUNT: AtomicU64 = AtomicU64::new(0);
 
+/// Calls `f` with masks covering harts `0..smp` except `skip`.
+fn for_each_hart_mask(smp: usize, skip: usize, mut f: impl FnMut(HartMask)) {
+    let mut mask = Some(HartMask::from_mask_base(0, 0));
+    for i in 0..smp {
+        if i == skip {
+            continue;
+        }
+        if let Some(ref mut mask) = mask {
+            match mask.insert(i) {
+                Ok(_) => continue,
+                Err(MaskError::InvalidBit) => {
+                    f(*mask);
+                }
+                Err(_) => unreachable!("Failed to construct mask"),
+            }
+        }
+        mask = Some(HartMask::from_mask_base(0b1, i));
+    }
+    if let Some(mask) = mask {
+        f(mask);
+    }
+}
+
 const SUSPENDED: SbiRet = SbiRet::success(hart_state::SUSPENDED);
 
 fn get_time() -> u64 {
@@ -251,7 +271,8 @@ extern "C" fn rust_main(hartid: usize, dtb_pa: usize) -> ! {
         }
     }
     info!("Starting test");
-    for i in 0..4 {
+    let mut total_time = 0;
+    for i in 0..TEST_ROUNDS {
         info!("Test #{i} started");
         unsafe {
             for (i, ipi_sent) in IPI_SENT.iter_mut().enumerate().take(smp) {
@@ -264,29 +285,15 @@ extern "C" fn rust_main(hartid: usize, dtb_pa: usize) -> ! {
         }
         debug!("send ipi!");
         let start_time = get_time();
-        let mut mask = Some(HartMask::from_mask_base(0, 0));
-        for i in 0..smp {
-            if i == hartid {
-                continue;
-            }
-            if let Some(ref mut mask) = mask {
-                match mask.insert(i) {
-                    Ok(_) => continue,
-                    Err(MaskError::InvalidBit) => {
-                        sbi::send_ipi(*mask);
-                    }
-                    Err(_) => unreachable!("Failed to construct mask"),
-                }
-            }
-            mask = Some(HartMask::from_mask_base(0b1, i));
-        }
-        if let Some(mask) = mask {
+        for_each_hart_mask(smp, hartid, |mask| {
             sbi::send_ipi(mask);
-        }
+        });
         while unsafe { WAIT_COUNT.load(Ordering::Acquire) } != 0 {}
         let end_time = get_time();
         println!("Test #{}: {}", i, end_time - start_time);
+        total_time += end_time - start_time;
     }
+    println!("Average: {}", total_time / TEST_ROUNDS as u64);
     sbi::system_reset(sbi::Shutdown, sbi::NoReason);
     unreachable!()
 }
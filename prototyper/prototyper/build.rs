@@ -1,4 +1,10 @@
-use std::{env, path::PathBuf};
+use std::{
+    env,
+    fmt::Write,
+    path::{Path, PathBuf},
+};
+
+use serde::Deserialize;
 
 fn main() {
     let out = PathBuf::from(env::var_os("OUT_DIR").unwrap());
@@ -9,6 +15,173 @@ fn main() {
     println!("cargo:rerun-if-env-changed=RUST_LOG,PROTOTYPER_FDT,PROTOTYPER_IMAGE");
     println!("cargo:rustc-link-arg=-T{}", ld.display());
     println!("cargo:rustc-link-search={}", out.display());
+
+    if env::var_os("CARGO_FEATURE_BOARD").is_some() {
+        let manifest_dir = PathBuf::from(env::var_os("CARGO_MANIFEST_DIR").unwrap());
+        let config = manifest_dir.join("../../target/config.toml");
+        println!("cargo:rerun-if-changed={}", config.display());
+        std::fs::write(out.join("board.rs"), board_description(&config)).unwrap();
+    }
+}
+
+/// The `[board]` table of the configuration file.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Board {
+    model: String,
+    memory: Vec<[u64; 2]>,
+    harts: Vec<u64>,
+    isa: String,
+    #[allow(dead_code, reason = "only used by `cargo xtask dtb`")]
+    timebase_frequency: Option<u64>,
+    console: Option<Device>,
+    ipi: Option<Device>,
+    reset: Option<Device>,
+    #[serde(default)]
+    pmu: Pmu,
+    #[serde(default)]
+    idle_states: Vec<IdleState>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Device {
+    compatible: String,
+    base: u64,
+    #[allow(dead_code, reason = "only used by `cargo xtask dtb`")]
+    clock_frequency: Option<u64>,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(deny_unknown_fields)]
+struct Pmu {
+    #[serde(default)]
+    event: Vec<PmuEvent>,
+    #[serde(default)]
+    counters: Vec<PmuCounters>,
+    #[serde(default)]
+    raw: Vec<PmuRaw>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct PmuEvent {
+    event: u32,
+    mhpmevent: u64,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct PmuCounters {
+    first: u32,
+    last: u32,
+    counters: u32,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct PmuRaw {
+    event: u64,
+    mask: u64,
+    counters: u32,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct IdleState {
+    suspend_type: u32,
+    #[allow(dead_code, reason = "only used by `cargo xtask dtb`")]
+    entry_latency_us: u32,
+    #[allow(dead_code, reason = "only used by `cargo xtask dtb`")]
+    exit_latency_us: u32,
+    #[allow(dead_code, reason = "only used by `cargo xtask dtb`")]
+    min_residency_us: u32,
+}
+
+#[derive(Deserialize)]
+struct Config {
+    board: Option<Board>,
+}
+
+/// Generates the `cfg::board::Board` value described by the `[board]` table of `config`.
+fn board_description(config: &Path) -> String {
+    let text = std::fs::read_to_string(config).unwrap_or_else(|err| {
+        panic!(
+            "failed to read {}: {err}, build with `cargo prototyper` to copy the configuration",
+            config.display()
+        )
+    });
+    let config: Config = toml::from_str(&text)
+        .unwrap_or_else(|err| panic!("invalid configuration {}: {err}", config.display()));
+    let Some(board) = config.board else {
+        panic!(
+            "the `board` feature needs a [board] table in the configuration, \
+             pass one with `cargo prototyper -c`, such as prototyper/prototyper/config/qemu_virt_board.toml"
+        );
+    };
+
+    let device = |device: &Option<Device>| match device {
+        Some(Device {
+            compatible, base, ..
+        }) => format!("Some(Device {{ compatible: {compatible:?}, base: {base:#x} }})"),
+        None => "None".to_string(),
+    };
+    let mut code = String::from("Board {\n");
+    writeln!(code, "    model: {:?},", board.model).unwrap();
+    write!(code, "    memory: &[").unwrap();
+    for [start, size] in &board.memory {
+        write!(code, "({start:#x}, {size:#x}), ").unwrap();
+    }
+    writeln!(code, "],").unwrap();
+    writeln!(code, "    harts: &{:?},", board.harts).unwrap();
+    writeln!(code, "    isa: {:?},", board.isa).unwrap();
+    writeln!(code, "    console: {},", device(&board.console)).unwrap();
+    writeln!(code, "    ipi: {},", device(&board.ipi)).unwrap();
+    writeln!(code, "    reset: {},", device(&board.reset)).unwrap();
+    write!(code, "    pmu_events: &[").unwrap();
+    for PmuEvent { event, mhpmevent } in &board.pmu.event {
+        write!(
+            code,
+            "PmuEvent {{ event: {event:#x}, mhpmevent: {mhpmevent:#x} }}, "
+        )
+        .unwrap();
+    }
+    writeln!(code, "],").unwrap();
+    write!(code, "    pmu_counters: &[").unwrap();
+    for PmuCounters {
+        first,
+        last,
+        counters,
+    } in &board.pmu.counters
+    {
+        write!(
+            code,
+            "PmuCounters {{ first: {first:#x}, last: {last:#x}, counters: {counters:#x} }}, "
+        )
+        .unwrap();
+    }
+    writeln!(code, "],").unwrap();
+    write!(code, "    pmu_raw: &[").unwrap();
+    for PmuRaw {
+        event,
+        mask,
+        counters,
+    } in &board.pmu.raw
+    {
+        write!(
+            code,
+            "PmuRaw {{ event: {event:#x}, mask: {mask:#x}, counters: {counters:#x} }}, "
+        )
+        .unwrap();
+    }
+    writeln!(code, "],").unwrap();
+    write!(code, "    idle_states: &[").unwrap();
+    for state in &board.idle_states {
+        write!(code, "{:#x}, ", state.suspend_type).unwrap();
+    }
+    writeln!(code, "],").unwrap();
+    code.push('}');
+    code
 }
 
 const LINKER_SCRIPT: &[u8] = b"OUTPUT_ARCH(riscv)
@@ -83,6 +256,13 @@ SECTIONS {
         *(.fdt)
     }
 
+    /* Must not match `.bss.*` above, or the buffer ends up before `sbi_end`. */
+    .fdt_buffer (NOLOAD) : ALIGN(0x1000) {
+        *(.fdt_buffer)
+    }
+    ASSERT(SIZEOF(.fdt_buffer) > 0 && ADDR(.fdt_buffer) >= sbi_end,
+        \"device tree buffer must be after sbi_end\")
+
     .text 0x80200000 : ALIGN(0x1000) {
         *(.payload)
     }
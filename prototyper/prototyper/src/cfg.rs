@@ -23,3 +23,70 @@ pub const JUMP_ADDRESS: usize = CONFIG.jump_address as usize;
 /// TLB_FLUSH_LIMIT defines the TLB refresh range limit.
 /// If the TLB refresh range is greater than TLB_FLUSH_LIMIT, the entire TLB is refreshed.
 pub const TLB_FLUSH_LIMIT: usize = CONFIG.tlb_flush_limit as usize;
+
+/// Board description, used when no device tree is passed to the prototyper.
+///
+/// The build script generates it from the `[board]` table of the configuration file.
+#[cfg(feature = "board")]
+pub mod board {
+    /// A device, by its compatible string and the base address of its registers.
+    pub struct Device {
+        pub compatible: &'static str,
+        pub base: usize,
+    }
+
+    /// Event `event` is selected by the `mhpmevent` value `mhpmevent`.
+    pub struct PmuEvent {
+        pub event: u32,
+        pub mhpmevent: u64,
+    }
+
+    /// Events `first` to `last` can be counted by the counters in bitmap `counters`.
+    pub struct PmuCounters {
+        pub first: u32,
+        pub last: u32,
+        pub counters: u32,
+    }
+
+    /// Raw events matching `event` under `mask` can be counted by the counters in bitmap `counters`.
+    pub struct PmuRaw {
+        pub event: u64,
+        pub mask: u64,
+        pub counters: u32,
+    }
+
+    struct Board {
+        model: &'static str,
+        memory: &'static [(usize, usize)],
+        harts: &'static [usize],
+        isa: &'static str,
+        console: Option<Device>,
+        ipi: Option<Device>,
+        reset: Option<Device>,
+        pmu_events: &'static [PmuEvent],
+        pmu_counters: &'static [PmuCounters],
+        pmu_raw: &'static [PmuRaw],
+        idle_states: &'static [u32],
+    }
+
+    const BOARD: Board = include!(concat!(env!("OUT_DIR"), "/board.rs"));
+
+    /// Model name of the board.
+    pub const MODEL: &str = BOARD.model;
+    /// Memory regions, as `(start, size)` pairs.
+    pub const MEMORY: &[(usize, usize)] = BOARD.memory;
+    /// Hart ids of all harts.
+    pub const HARTS: &[usize] = BOARD.harts;
+    /// ISA string shared by all harts, such as `rv64imafdc_zicsr_sstc`.
+    pub const ISA: &str = BOARD.isa;
+    /// Console, IPI and reset devices, `None` if the board has no such device.
+    pub const CONSOLE: Option<Device> = BOARD.console;
+    pub const IPI: Option<Device> = BOARD.ipi;
+    pub const RESET: Option<Device> = BOARD.reset;
+    /// PMU mappings following the `riscv,pmu` device tree bindings.
+    pub const PMU_EVENTS: &[PmuEvent] = BOARD.pmu_events;
+    pub const PMU_COUNTERS: &[PmuCounters] = BOARD.pmu_counters;
+    pub const PMU_RAW: &[PmuRaw] = BOARD.pmu_raw;
+    /// Suspend types of the hart idle states, the `riscv,sbi-suspend-param` of each state.
+    pub const IDLE_STATES: &[u32] = BOARD.idle_states;
+}
@@ -0,0 +1,382 @@
+//! Device tree fixups applied before jumping to the next stage.
+//!
+//! The flattened device tree is rewritten token by token into a buffer placed after the
+//! firmware, so that the next stage neither maps the firmware memory nor drives devices
+//! owned by machine mode.
+
+use alloc::format;
+use alloc::vec::Vec;
+use core::ops::Range;
+
+use crate::cfg::NUM_HART_MAX;
+use crate::firmware;
+use crate::platform::PLATFORM;
+
+use super::raw::{
+    FDT_BEGIN_NODE, FDT_END, FDT_END_NODE, FDT_HEADER_SIZE, FDT_LAST_COMP_VERSION, FDT_MAGIC,
+    FDT_PROP, FDT_VERSION, Fdt, MAX_DEPTH, NodeProps, Token, read_cells,
+};
+
+/// Size of the buffer holding the fixed-up device tree.
+const FDT_BUFFER_SIZE: usize = 64 * 1024;
+
+#[repr(C, align(8))]
+struct FdtBuffer([u8; FDT_BUFFER_SIZE]);
+
+/// Fixed-up device tree, located after `sbi_end` so it stays accessible to the next stage.
+#[unsafe(link_section = ".fdt_buffer")]
+static mut FDT_BUFFER: FdtBuffer = FdtBuffer([0; FDT_BUFFER_SIZE]);
+
+/// Applies the device tree fixups and returns the address of the fixed-up device tree.
+///
+/// The original device tree address is returned if it can't be fixed up.
+pub fn fixup_device_tree(fdt_address: usize) -> usize {
+    let Some(fdt) = (unsafe { Fdt::from_raw(fdt_address) }) else {
+        warn!(
+            "Device tree at 0x{:x} is malformed, skip fixups",
+            fdt_address
+        );
+        return fdt_address;
+    };
+    let mut fixup = Fixup::new();
+
+    // The first pass finds the removed cpus, the second one the size of the result.
+    let Some(size) = fixup
+        .rewrite(&fdt, None)
+        .and_then(|_| fixup.rewrite(&fdt, None))
+    else {
+        warn!(
+            "Device tree at 0x{:x} is malformed, skip fixups",
+            fdt_address
+        );
+        return fdt_address;
+    };
+    if size > FDT_BUFFER_SIZE {
+        warn!(
+            "Fixed-up device tree needs {} bytes but only {} are available, skip fixups",
+            size, FDT_BUFFER_SIZE
+        );
+        return fdt_address;
+    }
+
+    let buffer = unsafe { &mut FDT_BUFFER.0 };
+    if fixup.rewrite(&fdt, Some(buffer)) != Some(size) {
+        warn!("Failed to fix up device tree at 0x{:x}", fdt_address);
+        return fdt_address;
+    }
+    let new_address = buffer.as_ptr() as usize;
+    info!(
+        "{:<30}: 0x{:x} -> 0x{:x}",
+        "Device tree relocated", fdt_address, new_address
+    );
+    new_address
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum NodeKind {
+    Root,
+    Cpus,
+    Cpu,
+    CpuMap,
+    ReservedMemory,
+    Other,
+}
+
+/// A node on the path from the root to the current node.
+#[derive(Clone, Copy)]
+struct Frame {
+    kind: NodeKind,
+    /// `#address-cells` and `#size-cells` of the children of this node.
+    address_cells: u32,
+    size_cells: u32,
+    /// Whether the `status` property of this node is replaced.
+    disabled: bool,
+}
+
+const EMPTY_FRAME: Frame = Frame {
+    kind: NodeKind::Other,
+    address_cells: 2,
+    size_cells: 1,
+    disabled: false,
+};
+
+struct Fixup {
+    /// Memory reserved for the firmware.
+    firmware: Range<usize>,
+    /// Base addresses of devices driven by the firmware.
+    machine_devices: Vec<usize>,
+    /// Phandles of the removed cpus.
+    removed_cpus: Vec<u32>,
+}
+
+impl Fixup {
+    fn new() -> Self {
+        let info = unsafe { &PLATFORM.info };
+        Self {
+            firmware: firmware::firmware_region(),
+            machine_devices: info.machine_devices(),
+            removed_cpus: Vec::new(),
+        }
+    }
+
+    /// Rewrites the device tree into `buffer`, or only computes the size of the result if
+    /// `buffer` is `None`.
+    ///
+    /// Returns the size of the result, or `None` if the device tree is malformed.
+    fn rewrite(&mut self, fdt: &Fdt, buffer: Option<&mut [u8]>) -> Option<usize> {
+        let mut strings = Strings::new(fdt.strings);
+        let mut w = Writer { buffer, pos: 0 };
+
+        // Header is written after all blocks are placed.
+        w.pos = FDT_HEADER_SIZE;
+        let off_mem_rsvmap = w.pos;
+        w.bytes(fdt.mem_rsvmap);
+        let off_struct = w.pos;
+
+        let mut tokens = fdt.tokens();
+        let mut stack = [EMPTY_FRAME; MAX_DEPTH];
+        let mut depth = 0;
+        let mut reserved = false;
+        loop {
+            match tokens.next_token()? {
+                Token::BeginNode(name) => {
+                    if depth == MAX_DEPTH {
+                        return None;
+                    }
+                    let props = tokens.peek_props()?;
+                    let parent = depth.checked_sub(1).map(|idx| stack[idx]);
+                    let kind = node_kind(parent.map(|frame| frame.kind), name);
+                    let parent = parent.unwrap_or(EMPTY_FRAME);
+                    if self.should_remove(kind, parent, &props) {
+                        if let (NodeKind::Cpu, Some(phandle)) = (kind, props.phandle)
+                            && !self.removed_cpus.contains(&phandle)
+                        {
+                            self.removed_cpus.push(phandle);
+                        }
+                        tokens.skip_node()?;
+                        continue;
+                    }
+                    let disabled = kind == NodeKind::Other
+                        && read_cells(props.reg, parent.address_cells)
+                            .is_some_and(|base| self.machine_devices.contains(&(base as usize)));
+                    w.begin_node(name);
+                    if disabled {
+                        w.prop(strings.offset("status"), b"disabled\0");
+                    }
+                    stack[depth] = Frame {
+                        kind,
+                        address_cells: props.address_cells.unwrap_or(2),
+                        size_cells: props.size_cells.unwrap_or(1),
+                        disabled,
+                    };
+                    depth += 1;
+                }
+                Token::Prop {
+                    name,
+                    name_offset,
+                    value,
+                } => {
+                    let frame = stack[depth.checked_sub(1)?];
+                    if !(frame.disabled && name == b"status") {
+                        w.prop(name_offset, value);
+                    }
+                }
+                Token::Nop => {}
+                Token::EndNode => {
+                    depth = depth.checked_sub(1)?;
+                    let frame = stack[depth];
+                    match frame.kind {
+                        NodeKind::ReservedMemory => {
+                            self.write_firmware_reservation(&mut w, &mut strings, frame);
+                            reserved = true;
+                        }
+                        NodeKind::Root if !reserved => {
+                            let cells = [frame.address_cells, frame.size_cells];
+                            w.begin_node(b"reserved-memory");
+                            w.prop(strings.offset("#address-cells"), &cells[0].to_be_bytes());
+                            w.prop(strings.offset("#size-cells"), &cells[1].to_be_bytes());
+                            w.prop(strings.offset("ranges"), &[]);
+                            self.write_firmware_reservation(&mut w, &mut strings, frame);
+                            w.end_node();
+                            reserved = true;
+                        }
+                        _ => {}
+                    }
+                    w.end_node();
+                }
+                Token::End => {
+                    w.u32(FDT_END);
+                    break;
+                }
+            }
+        }
+        if depth != 0 {
+            return None;
+        }
+        let size_struct = w.pos - off_struct;
+
+        let off_strings = w.pos;
+        w.bytes(fdt.strings);
+        for name in strings.extra() {
+            w.bytes(name.as_bytes());
+            w.bytes(&[0]);
+        }
+        let size_strings = w.pos - off_strings;
+        let total_size = w.pos;
+
+        w.pos = 0;
+        for value in [
+            FDT_MAGIC,
+            total_size as u32,
+            off_struct as u32,
+            off_strings as u32,
+            off_mem_rsvmap as u32,
+            FDT_VERSION,
+            FDT_LAST_COMP_VERSION,
+            fdt.boot_cpuid_phys,
+            size_strings as u32,
+            size_struct as u32,
+        ] {
+            w.u32(value);
+        }
+        Some(total_size)
+    }
+
+    fn should_remove(&self, kind: NodeKind, parent: Frame, props: &NodeProps) -> bool {
+        match kind {
+            // Harts beyond the configured maximum are never started by the firmware.
+            NodeKind::Cpu => read_cells(props.reg, parent.address_cells)
+                .is_some_and(|hart_id| hart_id >= NUM_HART_MAX as u64),
+            // Topology entries must not refer to removed cpus.
+            NodeKind::CpuMap => props
+                .cpu
+                .is_some_and(|phandle| self.removed_cpus.contains(&phandle)),
+            _ => false,
+        }
+    }
+
+    /// Writes a `no-map` reserved memory node covering the firmware.
+    fn write_firmware_reservation(&self, w: &mut Writer, strings: &mut Strings, parent: Frame) {
+        let start = self.firmware.start as u64;
+        let size = (self.firmware.end - self.firmware.start) as u64;
+        let mut reg = [0u8; 16];
+        let address_len = write_cells(&mut reg, start, parent.address_cells);
+        let size_len = write_cells(&mut reg[address_len..], size, parent.size_cells);
+
+        w.begin_node(format!("mmode_resv0@{:x}", start).as_bytes());
+        w.prop(strings.offset("reg"), &reg[..address_len + size_len]);
+        w.prop(strings.offset("no-map"), &[]);
+        w.end_node();
+    }
+}
+
+fn node_kind(parent: Option<NodeKind>, name: &[u8]) -> NodeKind {
+    match (parent, name) {
+        (None, _) => NodeKind::Root,
+        (Some(NodeKind::Root), b"cpus") => NodeKind::Cpus,
+        (Some(NodeKind::Root), b"reserved-memory") => NodeKind::ReservedMemory,
+        (Some(NodeKind::Cpus), b"cpu-map") => NodeKind::CpuMap,
+        (Some(NodeKind::Cpus), name) if name.starts_with(b"cpu@") => NodeKind::Cpu,
+        // Clusters and cores of the cpu map are handled like the map itself.
+        (Some(NodeKind::CpuMap), _) => NodeKind::CpuMap,
+        _ => NodeKind::Other,
+    }
+}
+
+/// The strings block: the original strings followed by names added by fixups.
+struct Strings<'a> {
+    original: &'a [u8],
+    extra: [&'static str; 6],
+    extra_count: usize,
+}
+
+impl<'a> Strings<'a> {
+    fn new(original: &'a [u8]) -> Self {
+        Self {
+            original,
+            extra: [""; 6],
+            extra_count: 0,
+        }
+    }
+
+    /// Gets the offset of `name` in the strings block, adding it if necessary.
+    fn offset(&mut self, name: &'static str) -> u32 {
+        let mut offset = 0;
+        for string in self.original.split(|&byte| byte == 0) {
+            if string == name.as_bytes() {
+                return offset as u32;
+            }
+            offset += string.len() + 1;
+        }
+        let mut offset = self.original.len();
+        for extra in self.extra() {
+            if *extra == name {
+                return offset as u32;
+            }
+            offset += extra.len() + 1;
+        }
+        self.extra[self.extra_count] = name;
+        self.extra_count += 1;
+        offset as u32
+    }
+
+    fn extra(&self) -> &[&'static str] {
+        &self.extra[..self.extra_count]
+    }
+}
+
+/// Writes the device tree, or only counts its size when there is no buffer.
+struct Writer<'b> {
+    buffer: Option<&'b mut [u8]>,
+    pos: usize,
+}
+
+impl Writer<'_> {
+    fn bytes(&mut self, bytes: &[u8]) {
+        if let Some(buffer) = self.buffer.as_deref_mut()
+            && let Some(dst) = buffer.get_mut(self.pos..self.pos + bytes.len())
+        {
+            dst.copy_from_slice(bytes);
+        }
+        self.pos += bytes.len();
+    }
+
+    fn u32(&mut self, value: u32) {
+        self.bytes(&value.to_be_bytes());
+    }
+
+    fn pad(&mut self) {
+        while !self.pos.is_multiple_of(4) {
+            self.bytes(&[0]);
+        }
+    }
+
+    fn begin_node(&mut self, name: &[u8]) {
+        self.u32(FDT_BEGIN_NODE);
+        self.bytes(name);
+        self.bytes(&[0]);
+        self.pad();
+    }
+
+    fn end_node(&mut self) {
+        self.u32(FDT_END_NODE);
+    }
+
+    fn prop(&mut self, name_offset: u32, value: &[u8]) {
+        self.u32(FDT_PROP);
+        self.u32(value.len() as u32);
+        self.u32(name_offset);
+        self.bytes(value);
+        self.pad();
+    }
+}
+
+/// Writes `value` as `cells` cells and returns the number of bytes written.
+fn write_cells(dst: &mut [u8], value: u64, cells: u32) -> usize {
+    let cells = cells.min(2) as usize;
+    for idx in 0..cells {
+        let shift = 32 * (cells - 1 - idx);
+        dst[idx * 4..idx * 4 + 4].copy_from_slice(&((value >> shift) as u32).to_be_bytes());
+    }
+    cells * 4
+}
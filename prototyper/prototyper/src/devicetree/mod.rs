@@ -5,8 +5,12 @@ use serde_device_tree::{
     value::riscv_pmu::{EventToMhpmcounters, EventToMhpmevent, RawEventToMhpcounters},
 };
 
+use alloc::vec::Vec;
 use core::ops::Range;
 
+pub mod fixup;
+pub mod raw;
+
 /// Root device tree structure containing system information.
 #[derive(Deserialize)]
 pub struct Tree<'a> {
@@ -77,29 +81,14 @@ pub fn parse_device_tree(opaque: usize) -> Result<Dtb, ParseDeviceTreeError> {
     Ok(dtb)
 }
 
-pub fn get_compatible_and_range<'de>(node: &Node) -> Option<(StrSeq<'de>, Range<usize>)> {
-    let compatible = node
-        .get_prop("compatible")
-        .map(|prop_item| prop_item.deserialize::<StrSeq<'de>>());
-    let regs = node
-        .get_prop("reg")
+/// Gets all `reg` ranges of a node.
+pub fn get_ranges(node: &Node) -> Vec<Range<usize>> {
+    node.get_prop("reg")
         .map(|prop_item| {
-            let reg = prop_item.deserialize::<serde_device_tree::buildin::Reg>();
-            if let Some(range) = reg.iter().next() {
-                return Some(range);
-            }
-            None
+            let reg = prop_item.deserialize::<Reg>();
+            reg.iter().map(|range| range.0).collect()
         })
-        .map_or_else(|| None, |v| v);
-    if let Some(compatible) = compatible {
-        if let Some(regs) = regs {
-            Some((compatible, regs.0))
-        } else {
-            None
-        }
-    } else {
-        None
-    }
+        .unwrap_or_default()
 }
 
 pub fn get_compatible<'de>(node: &Node) -> Option<StrSeq<'de>> {
@@ -112,3 +101,9 @@ pub fn get_compatible<'de>(node: &Node) -> Option<StrSeq<'de>> {
         None
     }
 }
+
+/// Gets a property of a node holding a single cell.
+pub fn get_u32(node: &Node, name: &str) -> Option<u32> {
+    node.get_prop(name)
+        .map(|prop_item| prop_item.deserialize::<u32>())
+}
@@ -0,0 +1,483 @@
+//! Minimal reader of the flattened device tree format.
+//!
+//! `serde_device_tree` resolves neither phandles nor the raw token stream, which the device
+//! tree fixups and the interrupt target lookups below need.
+
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::ops::Range;
+
+pub(super) const FDT_MAGIC: u32 = 0xd00d_feed;
+pub(super) const FDT_VERSION: u32 = 17;
+pub(super) const FDT_LAST_COMP_VERSION: u32 = 16;
+pub(super) const FDT_HEADER_SIZE: usize = 40;
+
+pub(super) const FDT_BEGIN_NODE: u32 = 0x1;
+pub(super) const FDT_END_NODE: u32 = 0x2;
+pub(super) const FDT_PROP: u32 = 0x3;
+pub(super) const FDT_NOP: u32 = 0x4;
+pub(super) const FDT_END: u32 = 0x9;
+
+/// Maximum node depth handled by the readers.
+pub(super) const MAX_DEPTH: usize = 32;
+
+/// A flattened device tree blob.
+pub(super) struct Fdt<'a> {
+    pub(super) boot_cpuid_phys: u32,
+    pub(super) mem_rsvmap: &'a [u8],
+    pub(super) structure: &'a [u8],
+    pub(super) strings: &'a [u8],
+}
+
+impl<'a> Fdt<'a> {
+    /// Validates the device tree header at `address`.
+    pub(super) unsafe fn from_raw(address: usize) -> Option<Self> {
+        if address == 0 || !address.is_multiple_of(8) {
+            return None;
+        }
+        let header = unsafe { core::slice::from_raw_parts(address as *const u8, FDT_HEADER_SIZE) };
+        if read_u32(header, 0)? != FDT_MAGIC
+            || read_u32(header, 20)? < FDT_VERSION
+            || read_u32(header, 24)? > FDT_VERSION
+        {
+            return None;
+        }
+        let total_size = read_u32(header, 4)? as usize;
+        let data = unsafe { core::slice::from_raw_parts(address as *const u8, total_size) };
+        let off_struct = read_u32(data, 8)? as usize;
+        let off_strings = read_u32(data, 12)? as usize;
+        let off_mem_rsvmap = read_u32(data, 16)? as usize;
+        let size_strings = read_u32(data, 32)? as usize;
+        let size_struct = read_u32(data, 36)? as usize;
+
+        // The memory reservation block ends with an all-zero entry.
+        let mut rsvmap_end = off_mem_rsvmap;
+        loop {
+            let entry = data.get(rsvmap_end..rsvmap_end + 16)?;
+            rsvmap_end += 16;
+            if entry.iter().all(|&byte| byte == 0) {
+                break;
+            }
+        }
+        Some(Self {
+            boot_cpuid_phys: read_u32(data, 28)?,
+            mem_rsvmap: data.get(off_mem_rsvmap..rsvmap_end)?,
+            structure: data.get(off_struct..off_struct.checked_add(size_struct)?)?,
+            strings: data.get(off_strings..off_strings.checked_add(size_strings)?)?,
+        })
+    }
+
+    pub(super) fn tokens(&self) -> Tokens<'a> {
+        Tokens {
+            structure: self.structure,
+            strings: self.strings,
+            pos: 0,
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+pub(super) enum Token<'a> {
+    BeginNode(&'a [u8]),
+    EndNode,
+    Prop {
+        name: &'a [u8],
+        name_offset: u32,
+        value: &'a [u8],
+    },
+    Nop,
+    End,
+}
+
+/// Iterator over the tokens of the structure block.
+#[derive(Clone)]
+pub(super) struct Tokens<'a> {
+    structure: &'a [u8],
+    strings: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Tokens<'a> {
+    /// Reads the next token, or `None` if the structure block is malformed.
+    pub(super) fn next_token(&mut self) -> Option<Token<'a>> {
+        let token = read_u32(self.structure, self.pos)?;
+        self.pos += 4;
+        match token {
+            FDT_BEGIN_NODE => {
+                let name = read_cstr(self.structure, self.pos)?;
+                self.pos = align4(self.pos + name.len() + 1);
+                Some(Token::BeginNode(name))
+            }
+            FDT_END_NODE => Some(Token::EndNode),
+            FDT_PROP => {
+                let len = read_u32(self.structure, self.pos)? as usize;
+                let name_offset = read_u32(self.structure, self.pos + 4)?;
+                let value = self.structure.get(self.pos + 8..self.pos + 8 + len)?;
+                self.pos = align4(self.pos + 8 + len);
+                let name = read_cstr(self.strings, name_offset as usize)?;
+                Some(Token::Prop {
+                    name,
+                    name_offset,
+                    value,
+                })
+            }
+            FDT_NOP => Some(Token::Nop),
+            FDT_END => Some(Token::End),
+            _ => None,
+        }
+    }
+
+    /// Collects the properties of the node whose `FDT_BEGIN_NODE` was just read.
+    pub(super) fn peek_props(&self) -> Option<NodeProps<'a>> {
+        let mut tokens = self.clone();
+        let mut props = NodeProps::default();
+        loop {
+            match tokens.next_token()? {
+                Token::Prop { name, value, .. } => match name {
+                    b"reg" => props.reg = value,
+                    b"compatible" => props.compatible = value,
+                    b"interrupts-extended" => props.interrupts_extended = value,
+                    b"phandle" => props.phandle = read_u32(value, 0),
+                    b"cpu" => props.cpu = read_u32(value, 0),
+                    b"#address-cells" => props.address_cells = read_u32(value, 0),
+                    b"#size-cells" => props.size_cells = read_u32(value, 0),
+                    _ => {}
+                },
+                Token::Nop => {}
+                _ => return Some(props),
+            }
+        }
+    }
+
+    /// Skips the rest of the node whose `FDT_BEGIN_NODE` was just read.
+    pub(super) fn skip_node(&mut self) -> Option<()> {
+        let mut depth = 1;
+        while depth > 0 {
+            match self.next_token()? {
+                Token::BeginNode(_) => depth += 1,
+                Token::EndNode => depth -= 1,
+                Token::End => return None,
+                _ => {}
+            }
+        }
+        Some(())
+    }
+}
+
+/// Properties of a node that the firmware is interested in.
+#[derive(Default)]
+pub(super) struct NodeProps<'a> {
+    pub(super) reg: &'a [u8],
+    pub(super) compatible: &'a [u8],
+    pub(super) interrupts_extended: &'a [u8],
+    pub(super) phandle: Option<u32>,
+    pub(super) cpu: Option<u32>,
+    pub(super) address_cells: Option<u32>,
+    pub(super) size_cells: Option<u32>,
+}
+
+#[inline]
+const fn align4(pos: usize) -> usize {
+    (pos + 3) & !3
+}
+
+pub(super) fn read_u32(data: &[u8], offset: usize) -> Option<u32> {
+    let bytes = data.get(offset..offset + 4)?;
+    Some(u32::from_be_bytes(bytes.try_into().ok()?))
+}
+
+pub(super) fn read_cstr(data: &[u8], offset: usize) -> Option<&[u8]> {
+    let rest = data.get(offset..)?;
+    let len = rest.iter().position(|&byte| byte == 0)?;
+    Some(&rest[..len])
+}
+
+/// Reads the first value of `cells` cells from a property, such as the first address of `reg`.
+pub(super) fn read_cells(value: &[u8], cells: u32) -> Option<u64> {
+    if cells == 0 || cells > 2 {
+        return None;
+    }
+    (0..cells as usize).try_fold(0u64, |acc, idx| {
+        Some((acc << 32) | read_u32(value, idx * 4)? as u64)
+    })
+}
+
+/// Harts targeted by the `interrupts-extended` property of a device node.
+pub struct InterruptTargets {
+    /// First `reg` address of the device node.
+    pub base: usize,
+    /// Hart ids, in the order of the `interrupts-extended` entries.
+    pub harts: Vec<usize>,
+    /// Interrupt numbers at the interrupt controllers of the harts, in the same order.
+    pub irqs: Vec<u32>,
+}
+
+impl InterruptTargets {
+    /// Gets the targeted harts as a range, if they are consecutive.
+    pub fn hart_range(&self) -> Option<Range<usize>> {
+        let first = *self.harts.first()?;
+        let consecutive = self
+            .harts
+            .iter()
+            .enumerate()
+            .all(|(idx, &hart_id)| hart_id == first + idx);
+        consecutive.then_some(first..first + self.harts.len())
+    }
+}
+
+/// Finds the harts targeted by each node compatible with any of `compatible`.
+///
+/// Each `interrupts-extended` entry of such a node refers to the interrupt controller of a
+/// cpu, which is resolved to the hart id of that cpu.
+pub fn interrupt_targets(fdt_address: usize, compatible: &[&str]) -> Vec<InterruptTargets> {
+    let Some(fdt) = (unsafe { Fdt::from_raw(fdt_address) }) else {
+        return Vec::new();
+    };
+    let mut tokens = fdt.tokens();
+    // `#address-cells` of the parent and the hart id of each node on the current path.
+    let mut stack = [(2u32, None::<usize>); MAX_DEPTH];
+    let mut depth = 0;
+    // Interrupt controller phandle to hart id.
+    let mut intc_harts = Vec::new();
+    // Device base address, interrupt controller phandles and interrupt numbers.
+    let mut devices = Vec::new();
+    loop {
+        match tokens.next_token() {
+            Some(Token::BeginNode(name)) => {
+                let Some(props) = tokens.peek_props() else {
+                    return Vec::new();
+                };
+                let (address_cells, parent_hart) = if depth == 0 {
+                    (2, None)
+                } else {
+                    stack[depth - 1]
+                };
+                let hart = match parent_hart {
+                    Some(hart_id) => Some(hart_id),
+                    None if name.starts_with(b"cpu@") => {
+                        read_cells(props.reg, address_cells).map(|hart_id| hart_id as usize)
+                    }
+                    None => None,
+                };
+                if let (Some(hart_id), Some(phandle)) = (parent_hart, props.phandle) {
+                    intc_harts.push((phandle, hart_id));
+                }
+                if props
+                    .compatible
+                    .split(|&byte| byte == 0)
+                    .any(|id| compatible.iter().any(|c| c.as_bytes() == id))
+                    && let Some(base) = read_cells(props.reg, address_cells)
+                {
+                    // The interrupt controllers of cpus use one interrupt cell.
+                    let entries: Vec<(u32, u32)> = (0..props.interrupts_extended.len() / 8)
+                        .filter_map(|idx| {
+                            let phandle = read_u32(props.interrupts_extended, idx * 8)?;
+                            let irq = read_u32(props.interrupts_extended, idx * 8 + 4)?;
+                            Some((phandle, irq))
+                        })
+                        .collect();
+                    devices.push((base as usize, entries));
+                }
+                if depth == MAX_DEPTH {
+                    return Vec::new();
+                }
+                stack[depth] = (props.address_cells.unwrap_or(2), hart);
+                depth += 1;
+            }
+            Some(Token::EndNode) => depth = depth.saturating_sub(1),
+            Some(Token::End) => break,
+            Some(_) => {}
+            None => return Vec::new(),
+        }
+    }
+
+    devices
+        .into_iter()
+        .map(|(base, entries)| {
+            let (harts, irqs) = entries
+                .iter()
+                .filter_map(|(phandle, irq)| {
+                    intc_harts
+                        .iter()
+                        .find(|(intc, _)| intc == phandle)
+                        .map(|(_, hart_id)| (*hart_id, *irq))
+                })
+                .unzip();
+            InterruptTargets { base, harts, irqs }
+        })
+        .collect()
+}
+
+/// Compatible strings of reset nodes acting on the registers of a system controller.
+const SYSCON_REBOOT_COMPATIBLE: &[u8] = b"syscon-reboot";
+const SYSCON_POWEROFF_COMPATIBLE: &[u8] = b"syscon-poweroff";
+/// Compatible strings of reset nodes driving a GPIO line.
+const GPIO_RESTART_COMPATIBLE: &[u8] = b"gpio-restart";
+const GPIO_POWEROFF_COMPATIBLE: &[u8] = b"gpio-poweroff";
+
+/// How a reset node of the device tree acts on the hardware.
+#[derive(Clone, Debug)]
+pub enum ResetAction {
+    /// Updates the bits in `mask` of the 32-bit register at `address` to `value`.
+    Syscon {
+        address: usize,
+        value: u32,
+        mask: u32,
+    },
+    /// Drives `pin` of the GPIO controller at `base` to its active level.
+    Gpio {
+        base: usize,
+        /// Compatible strings of the GPIO controller.
+        compatible: Vec<String>,
+        pin: u32,
+        active_low: bool,
+    },
+}
+
+/// A `syscon-reboot`, `syscon-poweroff`, `gpio-restart` or `gpio-poweroff` node.
+#[derive(Clone, Debug)]
+pub struct ResetNode {
+    /// Whether the node powers off the system instead of rebooting it.
+    pub poweroff: bool,
+    pub action: ResetAction,
+}
+
+/// Finds all reset nodes, resolving the system controllers and GPIO controllers they use.
+pub fn reset_nodes(fdt_address: usize) -> Vec<ResetNode> {
+    let Some(fdt) = (unsafe { Fdt::from_raw(fdt_address) }) else {
+        return Vec::new();
+    };
+    let mut tokens = fdt.tokens();
+    // `#address-cells` of the parent and the base address of each node on the current path.
+    let mut stack = [(2u32, None::<u64>); MAX_DEPTH];
+    let mut depth = 0;
+    // Phandle to base address and compatible strings.
+    let mut phandles = Vec::new();
+    // Reset nodes found, with the controller phandle if any and the base of their parent.
+    let mut nodes = Vec::new();
+    loop {
+        match tokens.next_token() {
+            Some(Token::BeginNode(_)) => {
+                let Some(props) = tokens.peek_props() else {
+                    return Vec::new();
+                };
+                let (address_cells, parent_base) = if depth == 0 {
+                    (2, None)
+                } else {
+                    stack[depth - 1]
+                };
+                let base = read_cells(props.reg, address_cells);
+                if let (Some(phandle), Some(base)) = (props.phandle, base) {
+                    phandles.push((phandle, base as usize, props.compatible));
+                }
+                let mut compatible = props.compatible.split(|&byte| byte == 0);
+                if let Some(id) = compatible.find(|id| {
+                    [
+                        SYSCON_REBOOT_COMPATIBLE,
+                        SYSCON_POWEROFF_COMPATIBLE,
+                        GPIO_RESTART_COMPATIBLE,
+                        GPIO_POWEROFF_COMPATIBLE,
+                    ]
+                    .contains(id)
+                }) {
+                    nodes.push((id, parent_base, reset_props(&tokens)));
+                }
+                if depth == MAX_DEPTH {
+                    return Vec::new();
+                }
+                stack[depth] = (props.address_cells.unwrap_or(2), base);
+                depth += 1;
+            }
+            Some(Token::EndNode) => depth = depth.saturating_sub(1),
+            Some(Token::End) => break,
+            Some(_) => {}
+            None => return Vec::new(),
+        }
+    }
+
+    let controller = |phandle: Option<u32>| {
+        phandles
+            .iter()
+            .find(|(id, _, _)| Some(*id) == phandle)
+            .map(|&(_, base, compatible)| (base, compatible))
+    };
+    nodes
+        .into_iter()
+        .filter_map(|(id, parent_base, props)| {
+            let poweroff = id == SYSCON_POWEROFF_COMPATIBLE || id == GPIO_POWEROFF_COMPATIBLE;
+            let action = if id == SYSCON_REBOOT_COMPATIBLE || id == SYSCON_POWEROFF_COMPATIBLE {
+                // Without `regmap`, the node sits under its system controller.
+                let base = match props.regmap {
+                    Some(_) => controller(props.regmap)?.0,
+                    None => parent_base? as usize,
+                };
+                // Legacy nodes only have a `mask`, which is written as a whole.
+                let (value, mask) = match (props.value, props.mask) {
+                    (Some(value), mask) => (value, mask.unwrap_or(u32::MAX)),
+                    (None, Some(mask)) => (mask, u32::MAX),
+                    (None, None) => return None,
+                };
+                ResetAction::Syscon {
+                    address: base + props.offset? as usize,
+                    value,
+                    mask,
+                }
+            } else {
+                let (base, compatible) = controller(props.gpio_controller)?;
+                ResetAction::Gpio {
+                    base,
+                    compatible: compatible
+                        .split(|&byte| byte == 0)
+                        .filter(|id| !id.is_empty())
+                        .map(|id| String::from_utf8_lossy(id).into_owned())
+                        .collect(),
+                    pin: props.gpio_pin?,
+                    active_low: props.gpio_flags & GPIO_ACTIVE_LOW != 0,
+                }
+            };
+            Some(ResetNode { poweroff, action })
+        })
+        .collect()
+}
+
+/// Flag of the GPIO specifier for lines driven low when active.
+const GPIO_ACTIVE_LOW: u32 = 0x1;
+
+/// Properties of a reset node.
+#[derive(Default)]
+struct ResetProps {
+    regmap: Option<u32>,
+    offset: Option<u32>,
+    value: Option<u32>,
+    mask: Option<u32>,
+    gpio_controller: Option<u32>,
+    gpio_pin: Option<u32>,
+    gpio_flags: u32,
+}
+
+/// Collects the properties of the reset node whose `FDT_BEGIN_NODE` was just read.
+fn reset_props(tokens: &Tokens) -> ResetProps {
+    let mut tokens = tokens.clone();
+    let mut props = ResetProps::default();
+    while let Some(token) = tokens.next_token() {
+        match token {
+            Token::Prop { name, value, .. } => match name {
+                b"regmap" => props.regmap = read_u32(value, 0),
+                // Newer bindings give the offset as the address of the node.
+                b"offset" | b"reg" => props.offset = read_u32(value, 0),
+                b"value" => props.value = read_u32(value, 0),
+                b"mask" => props.mask = read_u32(value, 0),
+                // GPIO controllers are expected to use two cells, the pin and its flags.
+                b"gpios" => {
+                    props.gpio_controller = read_u32(value, 0);
+                    props.gpio_pin = read_u32(value, 4);
+                    props.gpio_flags = read_u32(value, 8).unwrap_or(0);
+                }
+                _ => {}
+            },
+            Token::Nop => {}
+            _ => break,
+        }
+    }
+    props
+}
@@ -69,9 +69,6 @@ cfg_if::cfg_if! {
             if err.invalid_mpp {
                 error!("* dynamic information contains invalid privilege mode");
             }
-            if err.invalid_next_addr {
-                error!("* dynamic information contains invalid next jump address");
-            }
             let explain_next_mode = match err.bad_info.next_mode {
                 3 => "Machine",
                 1 => "Supervisor",
@@ -62,11 +62,6 @@ pub struct DynamicInfo {
 // https://github.com/riscv-software-src/opensbi/blob/019a8e69a1dc0c0f011fabd0372e1ba80e40dd7c/include/sbi/fw_dynamic.h#L75
 
 const DYNAMIC_INFO_INVALID_ADDRESSES: usize = 0x00000000;
-const NEXT_ADDR_VALID_ADDRESSES: [Range<usize>; 2] = [
-    // Qemu Virt pflash address
-    0x20000000..0x22000000,
-    0x80000000..0x90000000,
-];
 pub(crate) const MAGIC: usize = 0x4942534f;
 const SUPPORTED_VERSION: Range<usize> = 0..3;
 
@@ -110,7 +105,6 @@ pub fn read_paddr(paddr: usize) -> Result<DynamicInfo, DynamicReadError> {
 /// Error type for dynamic info validation failures.
 pub struct DynamicError<'a> {
     pub invalid_mpp: bool,
-    pub invalid_next_addr: bool,
     pub bad_info: &'a DynamicInfo,
 }
 
@@ -120,24 +114,14 @@ pub struct DynamicError<'a> {
 pub fn mpp_next_addr(info: &DynamicInfo) -> Result<(mstatus::MPP, usize), DynamicError> {
     let mut error = DynamicError {
         invalid_mpp: false,
-        invalid_next_addr: false,
         bad_info: info,
     };
 
-    // fail safe, errors will be aggregated after whole checking process.
-    let next_addr_valid = NEXT_ADDR_VALID_ADDRESSES
-        .iter()
-        .any(|x| x.contains(&info.next_addr));
+    // The next address is checked against memory regions once the device tree is parsed.
     let mpp_valid = matches!(info.next_mode, 0 | 1 | 3);
 
-    if !next_addr_valid {
-        error.invalid_next_addr = true;
-    }
     if !mpp_valid {
         error.invalid_mpp = true;
-    }
-
-    if !next_addr_valid || !mpp_valid {
         return Err(error);
     }
 
@@ -13,6 +13,8 @@ cfg_if::cfg_if! {
 
 #[allow(unused)]
 use core::arch::{asm, naked_asm};
+
+use crate::platform::PLATFORM;
 use core::ops::Range;
 use riscv::register::mstatus;
 
@@ -37,7 +39,7 @@ pub extern "C" fn raw_fdt() {
 #[inline]
 #[cfg(feature = "fdt")]
 fn get_fdt_address() -> usize {
-    raw_fdt as usize
+    raw_fdt as *const () as usize
 }
 
 /// Gets boot hart information based on opaque and nonstandard_a2 parameters.
@@ -65,7 +67,41 @@ static mut SBI_END_ADDRESS: usize = 0;
 static mut RODATA_START_ADDRESS: usize = 0;
 static mut RODATA_END_ADDRESS: usize = 0;
 
-pub fn set_pmp(memory_range: &Range<usize>) {
+/// Non-memory regions the next stage may be executed in place from.
+const NEXT_ADDR_XIP_ADDRESSES: [Range<usize>; 1] = [
+    // Qemu Virt pflash address
+    0x20000000..0x22000000,
+];
+
+/// Checks whether the next stage address is in memory or in an execute-in-place region.
+pub fn next_addr_valid(next_addr: usize) -> bool {
+    NEXT_ADDR_XIP_ADDRESSES
+        .iter()
+        .any(|range| range.contains(&next_addr))
+        || unsafe { PLATFORM.memory_contains(next_addr, 1) }
+}
+
+/// Gets the address range occupied by the firmware.
+pub fn firmware_region() -> Range<usize> {
+    let (sbi_start, sbi_end): (usize, usize);
+    unsafe {
+        asm!("la {}, sbi_start", out(reg) sbi_start, options(nomem));
+        asm!("la {}, sbi_end", out(reg) sbi_end, options(nomem));
+    }
+    sbi_start..sbi_end
+}
+
+/// Gets the memory region holding the firmware.
+fn firmware_memory_range(memory_ranges: &[Range<usize>]) -> &Range<usize> {
+    let sbi_start = firmware_region().start;
+    memory_ranges
+        .iter()
+        .find(|range| range.contains(&sbi_start))
+        .expect("firmware is not located in any memory region")
+}
+
+pub fn set_pmp(memory_ranges: &[Range<usize>]) {
+    let memory_range = firmware_memory_range(memory_ranges);
     unsafe {
         // [0..memory_range.start] RWX
         // [memory_range.start..sbi_start] RWX
@@ -107,7 +143,15 @@ pub fn set_pmp(memory_range: &Range<usize>) {
     }
 }
 
-pub fn log_pmp_cfg(memory_range: &Range<usize>) {
+/// Checks whether `size` bytes starting at `start` overlap the firmware region,
+/// which PMP makes inaccessible to lower privilege modes.
+pub fn overlaps_firmware(start: usize, size: usize) -> bool {
+    let end = start.saturating_add(size);
+    unsafe { start < SBI_END_ADDRESS && end > SBI_START_ADDRESS }
+}
+
+pub fn log_pmp_cfg(memory_ranges: &[Range<usize>]) {
+    let memory_range = firmware_memory_range(memory_ranges);
     unsafe {
         info!("PMP Configuration");
 
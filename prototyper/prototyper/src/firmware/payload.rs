@@ -27,5 +27,5 @@ pub extern "C" fn payload_image() {
 
 #[inline]
 fn get_image_address() -> usize {
-    payload_image as usize
+    payload_image as *const () as usize
 }
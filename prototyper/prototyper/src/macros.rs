@@ -34,7 +34,7 @@ macro_rules! has_csr {
                 // Backup old mtvec
                 let mtvec = mtvec::read().bits();
                 // Write expected_trap
-                mtvec::write(light_expected_trap as _, mtvec::TrapMode::Direct);
+                mtvec::write(light_expected_trap as *const () as _, mtvec::TrapMode::Direct);
                 asm!("addi a0, zero, 0",
                     "addi a1, zero, 0",
                     "csrr a2, {}",
@@ -30,8 +30,9 @@ use crate::sbi::features::{
 };
 use crate::sbi::hart_context::NextStage;
 use crate::sbi::heap::sbi_heap_init;
-use crate::sbi::hsm::local_remote_hsm;
+use crate::sbi::hsm::{local_hsm, local_remote_hsm, take_powered_off};
 use crate::sbi::ipi;
+use crate::sbi::sse;
 use crate::sbi::trap;
 use crate::sbi::trap_stack;
 
@@ -39,6 +40,16 @@ pub const R_RISCV_RELATIVE: usize = 3;
 
 #[unsafe(no_mangle)]
 extern "C" fn rust_main(_hart_id: usize, opaque: usize, nonstandard_a2: usize) {
+    // Harts powered off through HSM enter the firmware again from reset, keeping their HSM state.
+    if take_powered_off() {
+        trap_stack::prepare_for_warm_boot();
+        firmware::set_pmp(unsafe { &PLATFORM.info.memory_ranges });
+        // Resume a non-retentive suspend, harts started after stop are already pending.
+        local_hsm().wake_non_retentive();
+        hart_csr_init();
+        return;
+    }
+
     // Track whether SBI is initialized and ready.
 
     // Get boot information and prepare for kernel entry.
@@ -78,8 +89,24 @@ extern "C" fn rust_main(_hart_id: usize, opaque: usize, nonstandard_a2: usize) {
             PLATFORM.print_board_info();
         }
 
-        firmware::set_pmp(unsafe { PLATFORM.info.memory_range.as_ref().unwrap() });
-        firmware::log_pmp_cfg(unsafe { PLATFORM.info.memory_range.as_ref().unwrap() });
+        // Check the next stage address against memory regions of the board.
+        if !firmware::next_addr_valid(next_addr) {
+            error!(
+                "Next stage address 0x{:x} is not in any memory region",
+                next_addr
+            );
+            fail::stop();
+        }
+
+        // Hide firmware memory and machine mode devices from the next stage.
+        let fdt_address = if unsafe { PLATFORM.info.from_board } {
+            fdt_address
+        } else {
+            devicetree::fixup::fixup_device_tree(fdt_address)
+        };
+
+        firmware::set_pmp(unsafe { &PLATFORM.info.memory_ranges });
+        firmware::log_pmp_cfg(unsafe { &PLATFORM.info.memory_ranges });
 
         // Log boot hart ID and PMP information
         let hart_id = current_hartid();
@@ -121,9 +148,15 @@ extern "C" fn rust_main(_hart_id: usize, opaque: usize, nonstandard_a2: usize) {
             core::hint::spin_loop()
         }
 
-        firmware::set_pmp(unsafe { PLATFORM.info.memory_range.as_ref().unwrap() });
+        firmware::set_pmp(unsafe { &PLATFORM.info.memory_ranges });
     }
-    // Clear all pending IPIs.
+    hart_csr_init();
+}
+
+/// Prepares the IPI device and machine mode CSRs of the current hart.
+fn hart_csr_init() {
+    // Prepare the IPI device and clear all pending IPIs.
+    ipi::init_local();
     ipi::clear_all();
 
     // Configure CSRs
@@ -135,10 +168,15 @@ extern "C" fn rust_main(_hart_id: usize, opaque: usize, nonstandard_a2: usize) {
         asm!("csrw scounteren, {}", in(reg) !0);
         use ::riscv::register::{medeleg, mtvec};
         // Keep supervisor environment calls and illegal instructions in M-mode.
+        // Misaligned accesses are emulated unless delegated through FWFT.
         medeleg::clear_supervisor_env_call();
         medeleg::clear_load_misaligned();
         medeleg::clear_store_misaligned();
         medeleg::clear_illegal_instruction();
+        // Local counter overflow interrupts are handled by the supervisor for PMU sampling.
+        if hart_extension_probe(current_hartid(), Extension::Sscofpmf) {
+            asm!("csrs mideleg, {}", in(reg) 1usize << sse::LCOFI);
+        }
 
         let hart_priv_version = hart_privileged_version(current_hartid());
         if hart_priv_version >= PrivilegedVersion::Version1_11 {
@@ -155,7 +193,10 @@ extern "C" fn rust_main(_hart_id: usize, opaque: usize, nonstandard_a2: usize) {
             }
         }
         // Set up trap handling.
-        mtvec::write(fast_trap::trap_entry as _, mtvec::TrapMode::Direct);
+        mtvec::write(
+            fast_trap::trap_entry as *const () as _,
+            mtvec::TrapMode::Direct,
+        );
     }
 }
 
@@ -0,0 +1,169 @@
+//! Board description from the configuration file, for boards booting without a device tree.
+
+use alloc::string::ToString;
+use alloc::vec::Vec;
+use core::sync::atomic::Ordering;
+
+use super::{CpuEnableList, Platform};
+use crate::cfg::NUM_HART_MAX;
+use crate::cfg::board::{
+    CONSOLE, Device as BoardDevice, HARTS, IDLE_STATES, IPI, ISA, MEMORY, MODEL, PMU_COUNTERS,
+    PMU_EVENTS, PMU_RAW, PmuCounters, PmuEvent, PmuRaw, RESET,
+};
+use crate::platform::clint::MachineClintType;
+use crate::platform::driver::{Device, DeviceDriver, DeviceRole, find_driver};
+use crate::platform::reset::MachineResetType;
+use crate::sbi::features::extension_detection_from_isa;
+use crate::sbi::logger;
+use crate::sbi::pmu::{
+    EventToCounterMap, RawEventToCounterMap, platform_event, register_platform_event,
+};
+
+/// Finds the driver of a configured device, `None` if the board has no such device.
+fn board_driver(device: Option<BoardDevice>) -> Option<(&'static dyn DeviceDriver, usize)> {
+    let BoardDevice { compatible, base } = device?;
+    match find_driver(compatible) {
+        Some(driver) => Some((driver, base)),
+        None => {
+            error!("No driver for board device {}, ignored", compatible);
+            None
+        }
+    }
+}
+
+impl Platform {
+    /// Initializes the platform from the board description.
+    pub(super) fn init_from_board(&mut self) {
+        self.info.from_board = true;
+
+        // Get console device, init sbi console and logger.
+        let mut console = None;
+        if let Some((driver, base)) = board_driver(CONSOLE)
+            && let Device::Console(device) | Device::ConsoleAndReset(device, _) =
+                driver.create(base)
+        {
+            self.info.console = Some((base, driver.name()));
+            console = Some(device);
+        }
+        self.sbi_console_init(console);
+        logger::Logger::init().unwrap();
+        info!("Hello RustSBI!");
+
+        // Get harts and memory info before any device depends on them.
+        self.board_misc_init();
+
+        // Get clint and reset device, init sbi ipi, reset, hsm, rfence, susp and sse extension.
+        let mut ipi_device = None;
+        if let Some((driver, base)) = board_driver(IPI)
+            && let Device::Ipi(device) = driver.create(base)
+        {
+            self.info.ipi = Some((base, MachineClintType::Clint(driver.name())));
+            ipi_device = Some(device);
+        }
+        let mut reboot_device = None;
+        let mut poweroff_device = None;
+        if let Some((driver, base)) = board_driver(RESET) {
+            let reset_type = MachineResetType::Device(driver.name());
+            if driver.roles().contains(&DeviceRole::Reset) {
+                self.info.reset = Some((base, reset_type));
+                reboot_device = driver.create(base).into_reset();
+            }
+            if driver.roles().contains(&DeviceRole::Power) {
+                self.info.poweroff = Some((base, reset_type));
+                poweroff_device = driver.create(base).into_reset();
+            }
+        }
+        self.sbi_ipi_init(ipi_device);
+        self.sbi_hsm_init();
+        self.sbi_reset_init(reboot_device, poweroff_device);
+        self.sbi_rfence_init();
+        self.sbi_susp_init();
+        self.sbi_sse_init();
+
+        // Initialize time extension
+        self.sbi_timer_init();
+        // Initialize pmu extension
+        self.board_pmu_init();
+        // Initialize fwft extension
+        self.sbi_fwft_init();
+        // Initialize dbtr extension
+        self.sbi_dbtr_init();
+
+        self.ready.swap(true, Ordering::Release);
+    }
+
+    fn board_misc_init(&mut self) {
+        let mut memory_ranges: Vec<_> = MEMORY
+            .iter()
+            .map(|&(start, size)| start..start + size)
+            .collect();
+        memory_ranges.sort_unstable_by_key(|range| range.start);
+        self.info.memory_ranges = memory_ranges;
+
+        self.info.cpu_num = Some(HARTS.len());
+        self.info.model = MODEL.to_string();
+        self.info.idle_states = IDLE_STATES.to_vec();
+
+        let mut cpu_list: CpuEnableList = [false; NUM_HART_MAX];
+        for &hart_id in HARTS {
+            if let Some(x) = cpu_list.get_mut(hart_id) {
+                *x = true;
+            } else {
+                error!(
+                    "The maximum supported hart id is {}, but the hart id {} was obtained. Please check the config!",
+                    NUM_HART_MAX - 1,
+                    hart_id
+                );
+            }
+        }
+        self.info.cpu_enabled = Some(cpu_list);
+
+        extension_detection_from_isa(
+            HARTS
+                .iter()
+                .copied()
+                .filter(|&hart_id| hart_id < NUM_HART_MAX),
+            ISA,
+        );
+    }
+
+    fn board_pmu_init(&mut self) {
+        // Firmware events defined by the prototyper itself.
+        register_platform_event(platform_event::RFENCE_FULL_FLUSH);
+
+        if PMU_EVENTS.is_empty() && PMU_COUNTERS.is_empty() && PMU_RAW.is_empty() {
+            return;
+        }
+        let sbi_pmu = self.sbi.pmu.get_or_insert_default();
+        for &PmuEvent { event, mhpmevent } in PMU_EVENTS {
+            sbi_pmu.insert_event_to_mhpmevent(event, mhpmevent);
+            debug!(
+                "pmu: insert event: 0x{:08x}, mhpmevent: {:#016x}",
+                event, mhpmevent
+            );
+        }
+        for &PmuCounters {
+            first,
+            last,
+            counters,
+        } in PMU_COUNTERS
+        {
+            let event_to_counter = EventToCounterMap::new(counters, first, last);
+            debug!("pmu: insert event_to_mhpmcounter: {:x?}", event_to_counter);
+            sbi_pmu.insert_event_to_mhpmcounter(event_to_counter);
+        }
+        for &PmuRaw {
+            event,
+            mask,
+            counters,
+        } in PMU_RAW
+        {
+            let raw_event_to_counter = RawEventToCounterMap::new(counters, event, mask);
+            debug!(
+                "pmu: insert raw_event_to_mhpmcounter: {:x?}",
+                raw_event_to_counter
+            );
+            sbi_pmu.insert_raw_event_to_mhpmcounter(raw_event_to_counter);
+        }
+    }
+}
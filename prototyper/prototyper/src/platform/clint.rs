@@ -1,18 +1,44 @@
 use aclint::SifiveClint;
+use alloc::boxed::Box;
+use alloc::vec::Vec;
 use core::arch::asm;
+use core::ops::Range;
+use serde_device_tree::buildin::Node;
 use xuantie_riscv::peripheral::clint::THeadClint;
 
+use crate::platform::driver::{
+    Device, DeviceDriver, DevicePart, DeviceRole, create_part, node_base, probe_part,
+};
 use crate::sbi::ipi::IpiDevice;
-pub(crate) const SIFIVE_CLINT_COMPATIBLE: [&str; 3] =
-    ["riscv,clint0", "starfive,jh7110-clint", "sifive,clint0"];
-pub(crate) const THEAD_CLINT_COMPATIBLE: [&str; 1] = ["thead,c900-clint"];
+
+const CLINT_ROLES: &[DeviceRole] = &[DeviceRole::Ipi, DeviceRole::Timer];
+
+/// Offset of `mtime` in an MTIMER device described by a single register region.
+const ACLINT_MTIME_OFFSET: usize = 0x7ff8;
+/// Size of an MTIMER device holding the `mtimecmp` array followed by `mtime`.
+const ACLINT_MTIMER_SIZE: usize = 0x8000;
+/// Size of an MSWI or SSWI device.
+const ACLINT_SWI_SIZE: usize = 0x4000;
 
 #[doc(hidden)]
 #[allow(unused)]
 #[derive(Clone, Copy, Debug)]
 pub enum MachineClintType {
-    SiFiveClint,
-    TheadClint,
+    /// A single device created by the named driver.
+    Clint(&'static str),
+    Aclint,
+    Imsic,
+}
+
+impl MachineClintType {
+    /// Name of the device type, shown in the boot log.
+    pub fn name(&self) -> &'static str {
+        match self {
+            MachineClintType::Clint(name) => name,
+            MachineClintType::Aclint => "Aclint",
+            MachineClintType::Imsic => "Imsic",
+        }
+    }
 }
 
 /// For SiFive Clint
@@ -121,3 +147,321 @@ impl IpiDevice for THeadClintWrap {
         unsafe { (*self.inner).clear_msip(hart_idx) }
     }
 }
+
+/// Driver of SiFive compatible CLINTs.
+pub struct SifiveClintDriver;
+
+impl DeviceDriver for SifiveClintDriver {
+    fn name(&self) -> &'static str {
+        "SiFiveClint"
+    }
+
+    fn compatible(&self) -> &'static [&'static str] {
+        &["riscv,clint0", "starfive,jh7110-clint", "sifive,clint0"]
+    }
+
+    fn roles(&self) -> &'static [DeviceRole] {
+        CLINT_ROLES
+    }
+
+    fn create(&self, base: usize) -> Device {
+        Device::Ipi(Box::new(SifiveClintWrap::new(base)))
+    }
+
+    fn probe(&self, node: &Node) -> Option<Device> {
+        let base = node_base(node)?;
+        // Some CLINTs only support 32-bit accesses like the T-Head one.
+        if node.get_prop("clint,has-no-64bit-mmio").is_some() {
+            return Some(Device::Ipi(Box::new(THeadClintWrap::new(base))));
+        }
+        Some(self.create(base))
+    }
+}
+
+/// Driver of T-Head CLINTs.
+pub struct THeadClintDriver;
+
+impl DeviceDriver for THeadClintDriver {
+    fn name(&self) -> &'static str {
+        "TheadClint"
+    }
+
+    fn compatible(&self) -> &'static [&'static str] {
+        &["thead,c900-clint"]
+    }
+
+    fn roles(&self) -> &'static [DeviceRole] {
+        CLINT_ROLES
+    }
+
+    fn create(&self, base: usize) -> Device {
+        Device::Ipi(Box::new(THeadClintWrap::new(base)))
+    }
+}
+
+/// Driver of ACLINT machine software interrupt devices.
+pub struct AclintMswiDriver;
+
+impl DeviceDriver for AclintMswiDriver {
+    fn name(&self) -> &'static str {
+        "AclintMswi"
+    }
+
+    fn compatible(&self) -> &'static [&'static str] {
+        &["riscv,aclint-mswi", "thead,c900-aclint-mswi"]
+    }
+
+    fn roles(&self) -> &'static [DeviceRole] {
+        &[DeviceRole::Ipi]
+    }
+
+    fn create(&self, base: usize) -> Device {
+        create_part(DevicePart::AclintMswi, base, ACLINT_SWI_SIZE)
+    }
+
+    fn probe(&self, node: &Node) -> Option<Device> {
+        probe_part(DevicePart::AclintMswi, node)
+    }
+}
+
+/// Driver of ACLINT machine timer devices.
+pub struct AclintMtimerDriver;
+
+impl DeviceDriver for AclintMtimerDriver {
+    fn name(&self) -> &'static str {
+        "AclintMtimer"
+    }
+
+    fn compatible(&self) -> &'static [&'static str] {
+        &["riscv,aclint-mtimer"]
+    }
+
+    fn roles(&self) -> &'static [DeviceRole] {
+        &[DeviceRole::Timer]
+    }
+
+    fn create(&self, base: usize) -> Device {
+        create_part(DevicePart::AclintMtimer, base, ACLINT_MTIMER_SIZE)
+    }
+
+    fn probe(&self, node: &Node) -> Option<Device> {
+        probe_part(DevicePart::AclintMtimer, node)
+    }
+}
+
+/// Driver of ACLINT supervisor software interrupt devices.
+pub struct AclintSswiDriver;
+
+impl DeviceDriver for AclintSswiDriver {
+    fn name(&self) -> &'static str {
+        "AclintSswi"
+    }
+
+    fn compatible(&self) -> &'static [&'static str] {
+        &["riscv,aclint-sswi", "thead,c900-aclint-sswi"]
+    }
+
+    fn roles(&self) -> &'static [DeviceRole] {
+        &[DeviceRole::Ipi]
+    }
+
+    fn create(&self, base: usize) -> Device {
+        create_part(DevicePart::AclintSswi, base, ACLINT_SWI_SIZE)
+    }
+
+    fn probe(&self, node: &Node) -> Option<Device> {
+        probe_part(DevicePart::AclintSswi, node)
+    }
+}
+
+/// A software interrupt device of the ACLINT, either MSWI or SSWI.
+#[derive(Clone, Debug)]
+pub struct AclintSwi {
+    /// Base address of the device.
+    pub base: usize,
+    /// Hart ids served by the device, starting from the first register.
+    pub harts: Range<usize>,
+}
+
+/// A timer device of the ACLINT.
+#[derive(Clone, Debug)]
+pub struct AclintMtimer {
+    /// First register address of the device node.
+    pub base: usize,
+    /// Address of the `mtime` register.
+    pub mtime: usize,
+    /// Address of the first `mtimecmp` register.
+    pub mtimecmp: usize,
+    /// Hart ids served by the device, starting from the first `mtimecmp` register.
+    pub harts: Range<usize>,
+}
+
+impl AclintMtimer {
+    /// Creates the timer from the register regions of its device node.
+    ///
+    /// Two regions describe `mtime` and the `mtimecmp` array, while a single region holds
+    /// the `mtimecmp` array followed by `mtime`.
+    pub fn new(regs: &[Range<usize>], harts: Range<usize>) -> Option<Self> {
+        let (mtime, mtimecmp) = match regs {
+            [mtimecmp] => (mtimecmp.start + ACLINT_MTIME_OFFSET, mtimecmp.start),
+            [mtime, mtimecmp, ..] => (mtime.start, mtimecmp.start),
+            [] => return None,
+        };
+        Some(Self {
+            base: regs[0].start,
+            mtime,
+            mtimecmp,
+            harts,
+        })
+    }
+}
+
+/// ACLINT devices. There may be several instances of each device, serving different harts.
+#[derive(Clone, Debug)]
+pub struct AclintInfo {
+    pub mswi: Vec<AclintSwi>,
+    pub mtimer: Vec<AclintMtimer>,
+    pub sswi: Vec<AclintSwi>,
+}
+
+impl AclintInfo {
+    pub const fn new() -> Self {
+        Self {
+            mswi: Vec::new(),
+            mtimer: Vec::new(),
+            sswi: Vec::new(),
+        }
+    }
+
+    /// Whether the ACLINT provides both machine software and timer interrupts.
+    pub fn is_usable(&self) -> bool {
+        !self.mswi.is_empty() && !self.mtimer.is_empty()
+    }
+}
+
+/// For RISC-V ACLINT
+pub struct AclintWrap {
+    info: AclintInfo,
+}
+
+impl AclintWrap {
+    pub fn new(info: AclintInfo) -> Self {
+        Self { info }
+    }
+
+    /// Finds the software interrupt register of `hart_id` in `devices`.
+    fn swi_register(devices: &[AclintSwi], hart_id: usize) -> Option<*mut u32> {
+        devices
+            .iter()
+            .find(|device| device.harts.contains(&hart_id))
+            .map(|device| (device.base + 4 * (hart_id - device.harts.start)) as *mut u32)
+    }
+
+    /// Finds the `mtimecmp` register of `hart_id`.
+    fn mtimecmp_register(&self, hart_id: usize) -> Option<usize> {
+        self.info
+            .mtimer
+            .iter()
+            .find(|timer| timer.harts.contains(&hart_id))
+            .map(|timer| timer.mtimecmp + 8 * (hart_id - timer.harts.start))
+    }
+}
+
+impl IpiDevice for AclintWrap {
+    #[inline(always)]
+    fn read_mtime(&self) -> u64 {
+        // All timers share the same time base.
+        self.info
+            .mtimer
+            .first()
+            .map_or(0, |timer| unsafe { read_u64(timer.mtime) })
+    }
+
+    #[inline(always)]
+    fn write_mtime(&self, val: u64) {
+        for timer in self.info.mtimer.iter() {
+            unsafe { write_u64(timer.mtime, val) }
+        }
+    }
+
+    #[inline(always)]
+    fn read_mtimecmp(&self, hart_idx: usize) -> u64 {
+        self.mtimecmp_register(hart_idx)
+            .map_or(u64::MAX, |addr| unsafe { read_u64(addr) })
+    }
+
+    #[inline(always)]
+    fn write_mtimecmp(&self, hart_idx: usize, val: u64) {
+        if let Some(addr) = self.mtimecmp_register(hart_idx) {
+            unsafe { write_u64(addr, val) }
+        }
+    }
+
+    #[inline(always)]
+    fn has_mtimer(&self, hart_idx: usize) -> bool {
+        self.mtimecmp_register(hart_idx).is_some()
+    }
+
+    #[inline(always)]
+    fn read_msip(&self, hart_idx: usize) -> bool {
+        Self::swi_register(&self.info.mswi, hart_idx)
+            .is_some_and(|reg| unsafe { reg.read_volatile() } & 1 != 0)
+    }
+
+    #[inline(always)]
+    fn set_msip(&self, hart_idx: usize) {
+        if let Some(reg) = Self::swi_register(&self.info.mswi, hart_idx) {
+            unsafe { reg.write_volatile(1) }
+        }
+    }
+
+    #[inline(always)]
+    fn clear_msip(&self, hart_idx: usize) {
+        if let Some(reg) = Self::swi_register(&self.info.mswi, hart_idx) {
+            unsafe { reg.write_volatile(0) }
+        }
+    }
+
+    #[inline(always)]
+    fn set_ssip(&self, hart_idx: usize) -> bool {
+        match Self::swi_register(&self.info.sswi, hart_idx) {
+            Some(reg) => {
+                unsafe { reg.write_volatile(1) };
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+/// Reads a 64-bit timer register, as two halves on 32-bit harts.
+unsafe fn read_u64(addr: usize) -> u64 {
+    if cfg!(target_pointer_width = "64") {
+        return unsafe { (addr as *const u64).read_volatile() };
+    }
+    let lo = addr as *const u32;
+    let hi = (addr + 4) as *const u32;
+    loop {
+        let (high, low, high_again) =
+            unsafe { (hi.read_volatile(), lo.read_volatile(), hi.read_volatile()) };
+        // Read again if the low half carried into the high half.
+        if high == high_again {
+            return ((high as u64) << 32) | low as u64;
+        }
+    }
+}
+
+/// Writes a 64-bit timer register, as two halves on 32-bit harts.
+unsafe fn write_u64(addr: usize, val: u64) {
+    if cfg!(target_pointer_width = "64") {
+        return unsafe { (addr as *mut u64).write_volatile(val) };
+    }
+    let lo = addr as *mut u32;
+    let hi = (addr + 4) as *mut u32;
+    // Keep the intermediate value from being smaller than both the old and the new one.
+    unsafe {
+        lo.write_volatile(u32::MAX);
+        hi.write_volatile((val >> 32) as u32);
+        lo.write_volatile(val as u32);
+    }
+}
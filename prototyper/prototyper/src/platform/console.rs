@@ -1,50 +1,136 @@
+use alloc::boxed::Box;
 use bouffalo_hal::uart::RegisterBlock as BflbUartRegisterBlock;
+use serde_device_tree::buildin::Node;
 use uart_sifive::MmioUartSifive;
 use uart_xilinx::MmioUartAxiLite;
-use uart16550::{Register, Uart16550};
 
+use crate::devicetree::get_u32;
+use crate::platform::driver::{Device, DeviceDriver, DeviceRole, node_base};
 use crate::sbi::console::ConsoleDevice;
 
-pub(crate) const UART16650U8_COMPATIBLE: [&str; 1] = ["ns16550a"];
-pub(crate) const UART16650U32_COMPATIBLE: [&str; 1] = ["snps,dw-apb-uart"];
-pub(crate) const UARTAXILITE_COMPATIBLE: [&str; 1] = ["xlnx,xps-uartlite-1.00.a"];
-pub(crate) const UARTBFLB_COMPATIBLE: [&str; 1] = ["bflb,bl808-uart"];
-pub(crate) const UARTSIFIVE_COMPATIBLE: [&str; 1] = ["sifive,uart0"];
+const CONSOLE_ROLES: &[DeviceRole] = &[DeviceRole::Console];
 
-#[doc(hidden)]
-#[allow(unused)]
-#[derive(Clone, Copy, Debug)]
-pub enum MachineConsoleType {
-    Uart16550U8,
-    Uart16550U32,
-    UartAxiLite,
-    UartBflb,
-    UartSifive,
-}
+/// Registers of 8250 and 16550 compatible UARTs, in units of the register stride.
+const UART_RBR_THR_DLL: usize = 0;
+const UART_IER_DLM: usize = 1;
+const UART_FCR: usize = 2;
+const UART_LCR: usize = 3;
+const UART_MCR: usize = 4;
+const UART_LSR: usize = 5;
+
+const UART_LCR_8N1: u32 = 0x03;
+const UART_LCR_DLAB: u32 = 0x80;
+const UART_FCR_ENABLE_CLEAR: u32 = 0x07;
+const UART_MCR_DTR_RTS: u32 = 0x03;
+const UART_LSR_DR: u32 = 0x01;
+const UART_LSR_THRE: u32 = 0x20;
+
+/// Baud rate programmed when the device tree has no `current-speed`.
+const UART_DEFAULT_BAUD_RATE: u32 = 115200;
 
-/// For Uart 16550
-pub struct Uart16550Wrap<R: Register> {
-    inner: *const Uart16550<R>,
+/// For Uart 16550, with registers `1 << reg_shift` bytes apart and accessed `reg_io_width`
+/// bytes at a time.
+pub struct Uart16550Wrap {
+    base: usize,
+    reg_shift: u32,
+    reg_io_width: u32,
 }
 
-impl<R: Register> Uart16550Wrap<R> {
-    pub fn new(base: usize) -> Self {
+impl Uart16550Wrap {
+    pub fn new(base: usize, reg_shift: u32, reg_io_width: u32) -> Self {
         Self {
-            inner: base as *const Uart16550<R>,
+            base,
+            reg_shift,
+            reg_io_width,
         }
     }
+
+    #[inline]
+    fn read_reg(&self, reg: usize) -> u32 {
+        let addr = self.base + (reg << self.reg_shift);
+        unsafe {
+            match self.reg_io_width {
+                4 => (addr as *const u32).read_volatile(),
+                2 => (addr as *const u16).read_volatile() as u32,
+                _ => (addr as *const u8).read_volatile() as u32,
+            }
+        }
+    }
+
+    #[inline]
+    fn write_reg(&self, reg: usize, val: u32) {
+        let addr = self.base + (reg << self.reg_shift);
+        unsafe {
+            match self.reg_io_width {
+                4 => (addr as *mut u32).write_volatile(val),
+                2 => (addr as *mut u16).write_volatile(val as u16),
+                _ => (addr as *mut u8).write_volatile(val as u8),
+            }
+        }
+    }
+
+    /// Programs the UART as 8N1 at `baud_rate` if the previous stage left its divisor unset.
+    pub fn init_if_unset(&self, clock_frequency: u32, baud_rate: u32) {
+        let lcr = self.read_reg(UART_LCR);
+        self.write_reg(UART_LCR, lcr | UART_LCR_DLAB);
+        let divisor =
+            (self.read_reg(UART_RBR_THR_DLL) & 0xff) | ((self.read_reg(UART_IER_DLM) & 0xff) << 8);
+        if divisor != 0 || baud_rate == 0 {
+            self.write_reg(UART_LCR, lcr);
+            return;
+        }
+        // Round to the closest divisor like Linux does.
+        let divisor = ((clock_frequency + 8 * baud_rate) / (16 * baud_rate)).max(1);
+        self.write_reg(UART_RBR_THR_DLL, divisor & 0xff);
+        self.write_reg(UART_IER_DLM, (divisor >> 8) & 0xff);
+        self.write_reg(UART_LCR, UART_LCR_8N1);
+        self.write_reg(UART_IER_DLM, 0);
+        self.write_reg(UART_FCR, UART_FCR_ENABLE_CLEAR);
+        self.write_reg(UART_MCR, UART_MCR_DTR_RTS);
+    }
 }
 
-impl<R: Register> ConsoleDevice for Uart16550Wrap<R> {
+impl ConsoleDevice for Uart16550Wrap {
     fn read(&self, buf: &mut [u8]) -> usize {
-        unsafe { (*self.inner).read(buf) }
+        let mut count = 0;
+        for slot in buf.iter_mut() {
+            if self.read_reg(UART_LSR) & UART_LSR_DR == 0 {
+                break;
+            }
+            *slot = self.read_reg(UART_RBR_THR_DLL) as u8;
+            count += 1;
+        }
+        count
     }
 
     fn write(&self, buf: &[u8]) -> usize {
-        unsafe { (*self.inner).write(buf) }
+        let mut count = 0;
+        for &byte in buf {
+            if self.read_reg(UART_LSR) & UART_LSR_THRE == 0 {
+                break;
+            }
+            self.write_reg(UART_RBR_THR_DLL, byte as u32);
+            count += 1;
+        }
+        count
     }
 }
 
+/// Creates a 16550 UART from its node, honoring the standard 8250 properties.
+fn probe_uart16550(node: &Node, reg_shift: u32, reg_io_width: u32) -> Option<Device> {
+    let base = node_base(node)?;
+    let uart = Uart16550Wrap::new(
+        base,
+        get_u32(node, "reg-shift").unwrap_or(reg_shift),
+        get_u32(node, "reg-io-width").unwrap_or(reg_io_width),
+    );
+    if let Some(clock_frequency) = get_u32(node, "clock-frequency") {
+        let baud_rate = get_u32(node, "current-speed").unwrap_or(UART_DEFAULT_BAUD_RATE);
+        uart.init_if_unset(clock_frequency, baud_rate);
+    }
+    Some(Device::Console(Box::new(uart)))
+}
+
 /// For Uart AxiLite
 impl ConsoleDevice for MmioUartAxiLite {
     fn read(&self, buf: &mut [u8]) -> usize {
@@ -127,3 +213,116 @@ impl ConsoleDevice for UartBflbWrap {
         count
     }
 }
+
+/// Driver of 16550 UARTs, with 8-bit registers unless the device tree says otherwise.
+pub struct Uart16550U8Driver;
+
+impl DeviceDriver for Uart16550U8Driver {
+    fn name(&self) -> &'static str {
+        "Uart16550U8"
+    }
+
+    fn compatible(&self) -> &'static [&'static str] {
+        &["ns16550a"]
+    }
+
+    fn roles(&self) -> &'static [DeviceRole] {
+        CONSOLE_ROLES
+    }
+
+    fn create(&self, base: usize) -> Device {
+        Device::Console(Box::new(Uart16550Wrap::new(base, 0, 1)))
+    }
+
+    fn probe(&self, node: &Node) -> Option<Device> {
+        probe_uart16550(node, 0, 1)
+    }
+}
+
+/// Driver of DesignWare 16550 UARTs, with 32-bit registers unless the device tree says otherwise.
+pub struct Uart16550U32Driver;
+
+impl DeviceDriver for Uart16550U32Driver {
+    fn name(&self) -> &'static str {
+        "Uart16550U32"
+    }
+
+    fn compatible(&self) -> &'static [&'static str] {
+        &["snps,dw-apb-uart"]
+    }
+
+    fn roles(&self) -> &'static [DeviceRole] {
+        CONSOLE_ROLES
+    }
+
+    fn create(&self, base: usize) -> Device {
+        Device::Console(Box::new(Uart16550Wrap::new(base, 2, 4)))
+    }
+
+    fn probe(&self, node: &Node) -> Option<Device> {
+        probe_uart16550(node, 2, 4)
+    }
+}
+
+/// Driver of Xilinx AXI UART Lite.
+pub struct UartAxiLiteDriver;
+
+impl DeviceDriver for UartAxiLiteDriver {
+    fn name(&self) -> &'static str {
+        "UartAxiLite"
+    }
+
+    fn compatible(&self) -> &'static [&'static str] {
+        &["xlnx,xps-uartlite-1.00.a"]
+    }
+
+    fn roles(&self) -> &'static [DeviceRole] {
+        CONSOLE_ROLES
+    }
+
+    fn create(&self, base: usize) -> Device {
+        Device::Console(Box::new(MmioUartAxiLite::new(base)))
+    }
+}
+
+/// Driver of Bouffalo Lab UARTs.
+pub struct UartBflbDriver;
+
+impl DeviceDriver for UartBflbDriver {
+    fn name(&self) -> &'static str {
+        "UartBflb"
+    }
+
+    fn compatible(&self) -> &'static [&'static str] {
+        &["bflb,bl808-uart"]
+    }
+
+    fn roles(&self) -> &'static [DeviceRole] {
+        CONSOLE_ROLES
+    }
+
+    fn create(&self, base: usize) -> Device {
+        Device::Console(Box::new(UartBflbWrap::new(base)))
+    }
+}
+
+/// Driver of SiFive UARTs.
+pub struct UartSifiveDriver;
+
+impl DeviceDriver for UartSifiveDriver {
+    fn name(&self) -> &'static str {
+        "UartSifive"
+    }
+
+    fn compatible(&self) -> &'static [&'static str] {
+        &["sifive,uart0"]
+    }
+
+    fn roles(&self) -> &'static [DeviceRole] {
+        CONSOLE_ROLES
+    }
+
+    fn create(&self, base: usize) -> Device {
+        Device::Console(Box::new(UartSifiveWrap::new(base)))
+    }
+}
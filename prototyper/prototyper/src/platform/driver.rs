@@ -0,0 +1,129 @@
+//! Registry of drivers for devices described in the device tree.
+//!
+//! Each driver lives next to its device and declares the compatible strings it handles, the
+//! SBI roles its devices fill and how to create a device from its node. Drivers of devices
+//! assembled from several nodes, such as the ACLINT or the IMSIC, create one part per node,
+//! which the platform puts together once it knows the harts served by every node.
+
+use alloc::boxed::Box;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::ops::Range;
+use serde_device_tree::buildin::Node;
+
+use crate::devicetree::get_ranges;
+use crate::platform::clint::{
+    AclintMswiDriver, AclintMtimerDriver, AclintSswiDriver, SifiveClintDriver, THeadClintDriver,
+};
+use crate::platform::console::{
+    Uart16550U8Driver, Uart16550U32Driver, UartAxiLiteDriver, UartBflbDriver, UartSifiveDriver,
+};
+use crate::platform::htif::HtifDriver;
+use crate::platform::imsic::ImsicDriver;
+use crate::platform::reset::SifiveTestDriver;
+use crate::sbi::console::ConsoleDevice;
+use crate::sbi::ipi::IpiDevice;
+use crate::sbi::reset::ResetDevice;
+
+/// SBI role filled by a device.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DeviceRole {
+    Console,
+    Ipi,
+    Timer,
+    Reset,
+    Power,
+}
+
+/// A device created by a driver.
+pub enum Device {
+    Console(Box<dyn ConsoleDevice>),
+    /// Provides both IPIs and timers.
+    Ipi(Box<dyn IpiDevice>),
+    /// Provides reset, power off or both.
+    Reset(Box<dyn ResetDevice>),
+    /// Provides a console as well as reset, power off or both.
+    ConsoleAndReset(Box<dyn ConsoleDevice>, Box<dyn ResetDevice>),
+    /// One node of a device assembled from several nodes, with its register regions.
+    Part(DevicePart, Vec<Range<usize>>),
+}
+
+/// Kind of a node of a device assembled from several nodes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DevicePart {
+    AclintMswi,
+    AclintMtimer,
+    AclintSswi,
+    Imsic,
+}
+
+impl Device {
+    /// Gets the reset device, if this device provides one.
+    pub fn into_reset(self) -> Option<Box<dyn ResetDevice>> {
+        match self {
+            Device::Reset(device) | Device::ConsoleAndReset(_, device) => Some(device),
+            Device::Console(_) | Device::Ipi(_) | Device::Part(..) => None,
+        }
+    }
+}
+
+/// A driver for devices described in the device tree.
+pub trait DeviceDriver: Sync {
+    /// Name of the driver, shown in the boot log.
+    fn name(&self) -> &'static str;
+    /// Compatible strings of the devices handled by this driver.
+    fn compatible(&self) -> &'static [&'static str];
+    /// SBI roles filled by the devices of this driver.
+    fn roles(&self) -> &'static [DeviceRole];
+    /// Gets the base address of the device described by `node`.
+    fn base(&self, node: &Node) -> Option<usize> {
+        node_base(node)
+    }
+    /// Creates the device at `base` with its default configuration.
+    fn create(&self, base: usize) -> Device;
+    /// Creates the device described by `node`, or `None` if the node is not usable.
+    fn probe(&self, node: &Node) -> Option<Device> {
+        self.base(node).map(|base| self.create(base))
+    }
+}
+
+/// All drivers known to the prototyper, in order of preference.
+static DRIVERS: &[&dyn DeviceDriver] = &[
+    &Uart16550U8Driver,
+    &Uart16550U32Driver,
+    &UartAxiLiteDriver,
+    &UartBflbDriver,
+    &UartSifiveDriver,
+    &SifiveClintDriver,
+    &THeadClintDriver,
+    &AclintMswiDriver,
+    &AclintMtimerDriver,
+    &AclintSswiDriver,
+    &ImsicDriver,
+    &SifiveTestDriver,
+    &HtifDriver,
+];
+
+/// Finds the driver handling the `compatible` string.
+pub fn find_driver(compatible: &str) -> Option<&'static dyn DeviceDriver> {
+    DRIVERS
+        .iter()
+        .find(|driver| driver.compatible().contains(&compatible))
+        .copied()
+}
+
+/// Gets the base address of a device node.
+pub fn node_base(node: &Node) -> Option<usize> {
+    get_ranges(node).first().map(|range| range.start)
+}
+
+/// Creates a part of `size` bytes at `base`, for boards which only give the base address.
+pub fn create_part(part: DevicePart, base: usize, size: usize) -> Device {
+    Device::Part(part, vec![base..base + size])
+}
+
+/// Creates the part described by `node`, with all its register regions.
+pub fn probe_part(part: DevicePart, node: &Node) -> Option<Device> {
+    let regs = get_ranges(node);
+    (!regs.is_empty()).then(|| Device::Part(part, regs))
+}
@@ -0,0 +1,153 @@
+use alloc::boxed::Box;
+use core::sync::atomic::{AtomicI32, Ordering};
+use serde_device_tree::buildin::Node;
+
+use crate::platform::driver::{Device, DeviceDriver, DeviceRole, node_base};
+use crate::sbi::console::ConsoleDevice;
+use crate::sbi::reset::ResetDevice;
+
+/// Host-target interface registers, found by the simulator through these ELF symbols when
+/// the device tree does not give their address.
+#[allow(non_upper_case_globals)]
+#[unsafe(no_mangle)]
+#[used]
+static mut tohost: u64 = 0;
+#[allow(non_upper_case_globals)]
+#[unsafe(no_mangle)]
+#[used]
+static mut fromhost: u64 = 0;
+
+const HTIF_DEV_SYSTEM: u64 = 0;
+const HTIF_DEV_CONSOLE: u64 = 1;
+const HTIF_CONSOLE_CMD_GETC: u64 = 0;
+const HTIF_CONSOLE_CMD_PUTC: u64 = 1;
+
+/// Console input state: `-1` while a read request is pending, otherwise one plus the
+/// received byte, with `0` meaning no byte and no request.
+static CONSOLE_BUF: AtomicI32 = AtomicI32::new(0);
+
+/// For the host-target interface of Spike and other simulators.
+pub struct HtifWrap {
+    tohost: *mut u64,
+    fromhost: *mut u64,
+}
+
+impl HtifWrap {
+    /// Uses the registers at `base`, `fromhost` following `tohost`.
+    ///
+    /// A `base` of `0` or of the `tohost` symbol selects the registers exported by the prototyper.
+    pub fn new(base: usize) -> Self {
+        if base == 0 || base == &raw const tohost as usize {
+            return Self {
+                tohost: &raw mut tohost,
+                fromhost: &raw mut fromhost,
+            };
+        }
+        Self {
+            tohost: base as *mut u64,
+            fromhost: (base + 8) as *mut u64,
+        }
+    }
+
+    /// Handles a response of the host, if any.
+    fn check_fromhost(&self) {
+        let fh = unsafe { self.fromhost.read_volatile() };
+        if fh == 0 {
+            return;
+        }
+        unsafe { self.fromhost.write_volatile(0) };
+        let (device, command) = (fh >> 56, (fh >> 48) & 0xff);
+        if device == HTIF_DEV_CONSOLE && command == HTIF_CONSOLE_CMD_GETC {
+            CONSOLE_BUF.store(1 + (fh as u8) as i32, Ordering::Relaxed);
+        }
+    }
+
+    /// Sends a command once the host has consumed the previous one.
+    fn set_tohost(&self, device: u64, command: u64, data: u64) {
+        while unsafe { self.tohost.read_volatile() } != 0 {
+            self.check_fromhost();
+        }
+        unsafe {
+            self.tohost
+                .write_volatile((device << 56) | (command << 48) | data)
+        };
+    }
+
+    fn exit(&self, code: u64) -> ! {
+        loop {
+            self.set_tohost(HTIF_DEV_SYSTEM, 0, (code << 1) | 1);
+        }
+    }
+}
+
+impl ConsoleDevice for HtifWrap {
+    fn read(&self, buf: &mut [u8]) -> usize {
+        let Some(slot) = buf.first_mut() else {
+            return 0;
+        };
+        self.check_fromhost();
+        let ch = CONSOLE_BUF.load(Ordering::Relaxed);
+        if ch < 0 {
+            return 0;
+        }
+        // Request the next byte, the host answers through `fromhost`.
+        CONSOLE_BUF.store(-1, Ordering::Relaxed);
+        self.set_tohost(HTIF_DEV_CONSOLE, HTIF_CONSOLE_CMD_GETC, 0);
+        if ch == 0 {
+            return 0;
+        }
+        *slot = (ch - 1) as u8;
+        1
+    }
+
+    fn write(&self, buf: &[u8]) -> usize {
+        for &byte in buf {
+            self.set_tohost(HTIF_DEV_CONSOLE, HTIF_CONSOLE_CMD_PUTC, byte as u64);
+        }
+        buf.len()
+    }
+}
+
+impl ResetDevice for HtifWrap {
+    #[inline]
+    fn fail(&self, code: u16) -> ! {
+        self.exit(code.max(1) as u64)
+    }
+
+    #[inline]
+    fn pass(&self) -> ! {
+        self.exit(0)
+    }
+
+    /// The host can only end the simulation.
+    #[inline]
+    fn reset(&self) -> ! {
+        self.exit(0)
+    }
+}
+
+/// Driver of the host-target interface, for both console and power off.
+pub struct HtifDriver;
+
+impl DeviceDriver for HtifDriver {
+    fn name(&self) -> &'static str {
+        "Htif"
+    }
+
+    fn compatible(&self) -> &'static [&'static str] {
+        &["ucb,htif0"]
+    }
+
+    fn roles(&self) -> &'static [DeviceRole] {
+        &[DeviceRole::Console, DeviceRole::Reset, DeviceRole::Power]
+    }
+
+    /// Falls back to the `tohost` symbol, which is what Spike uses.
+    fn base(&self, node: &Node) -> Option<usize> {
+        node_base(node).or(Some(&raw const tohost as usize))
+    }
+
+    fn create(&self, base: usize) -> Device {
+        Device::ConsoleAndReset(Box::new(HtifWrap::new(base)), Box::new(HtifWrap::new(base)))
+    }
+}
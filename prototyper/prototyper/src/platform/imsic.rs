@@ -0,0 +1,179 @@
+use alloc::vec::Vec;
+use core::arch::asm;
+use core::ops::Range;
+use serde_device_tree::buildin::Node;
+
+use crate::platform::clint::{AclintInfo, AclintWrap};
+use crate::platform::driver::{
+    Device, DeviceDriver, DevicePart, DeviceRole, create_part, probe_part,
+};
+use crate::riscv::csr::{mireg, mtopei};
+use crate::riscv::current_hartid;
+use crate::sbi::ipi::IpiDevice;
+
+/// Interrupt identity of machine mode IPIs.
+pub const IMSIC_IPI_ID: usize = 1;
+
+/// Size of an interrupt file, which starts with the `seteipnum_le` register.
+const IMSIC_FILE_SIZE: usize = 0x1000;
+
+/// Indirect registers of the interrupt file.
+const IMSIC_EIDELIVERY: usize = 0x70;
+const IMSIC_EITHRESHOLD: usize = 0x72;
+const IMSIC_EIP0: usize = 0x80;
+const IMSIC_EIE0: usize = 0xc0;
+
+/// Machine level interrupt files of the IMSIC.
+#[derive(Clone, Debug)]
+pub struct ImsicInfo {
+    /// Base address of the device node.
+    pub base: usize,
+    /// Register regions holding consecutive interrupt files.
+    pub regions: Vec<Range<usize>>,
+    /// Hart ids owning the interrupt files, in order.
+    pub harts: Vec<usize>,
+}
+
+/// Driver of machine level IMSIC interrupt files.
+pub struct ImsicDriver;
+
+impl DeviceDriver for ImsicDriver {
+    fn name(&self) -> &'static str {
+        "Imsic"
+    }
+
+    fn compatible(&self) -> &'static [&'static str] {
+        &["riscv,imsics", "qemu,imsics"]
+    }
+
+    fn roles(&self) -> &'static [DeviceRole] {
+        &[DeviceRole::Ipi]
+    }
+
+    fn create(&self, base: usize) -> Device {
+        create_part(DevicePart::Imsic, base, IMSIC_FILE_SIZE)
+    }
+
+    fn probe(&self, node: &Node) -> Option<Device> {
+        probe_part(DevicePart::Imsic, node)
+    }
+}
+
+/// For RISC-V AIA IMSIC, with timers from the ACLINT if any.
+pub struct ImsicWrap {
+    imsic: ImsicInfo,
+    timer: AclintWrap,
+    has_timer: bool,
+}
+
+impl ImsicWrap {
+    pub fn new(imsic: ImsicInfo, aclint: AclintInfo) -> Self {
+        let has_timer = !aclint.mtimer.is_empty();
+        Self {
+            imsic,
+            timer: AclintWrap::new(aclint),
+            has_timer,
+        }
+    }
+
+    /// Finds the `seteipnum_le` register of the interrupt file of `hart_id`.
+    fn seteipnum(&self, hart_id: usize) -> Option<*mut u32> {
+        let mut idx = self.imsic.harts.iter().position(|&hart| hart == hart_id)?;
+        for region in self.imsic.regions.iter() {
+            let files = (region.end - region.start) / IMSIC_FILE_SIZE;
+            if idx < files {
+                return Some((region.start + idx * IMSIC_FILE_SIZE) as *mut u32);
+            }
+            idx -= files;
+        }
+        None
+    }
+}
+
+/// Gets the `eip` or `eie` register and the bit of an interrupt identity.
+#[inline]
+fn register_and_bit(base: usize, id: usize) -> (usize, usize) {
+    // Only even registers exist on RV64, each holding 64 identities.
+    let register = base + id / usize::BITS as usize * (usize::BITS as usize / 32);
+    (register, 1 << (id % usize::BITS as usize))
+}
+
+impl IpiDevice for ImsicWrap {
+    #[inline(always)]
+    fn read_mtime(&self) -> u64 {
+        if self.has_timer {
+            return self.timer.read_mtime();
+        }
+        let mtime: usize;
+        unsafe { asm!("rdtime {}", out(reg) mtime) };
+        mtime as u64
+    }
+
+    #[inline(always)]
+    fn write_mtime(&self, val: u64) {
+        self.timer.write_mtime(val)
+    }
+
+    #[inline(always)]
+    fn read_mtimecmp(&self, hart_idx: usize) -> u64 {
+        self.timer.read_mtimecmp(hart_idx)
+    }
+
+    #[inline(always)]
+    fn write_mtimecmp(&self, hart_idx: usize, val: u64) {
+        self.timer.write_mtimecmp(hart_idx, val)
+    }
+
+    #[inline(always)]
+    fn has_mtimer(&self, hart_idx: usize) -> bool {
+        self.timer.has_mtimer(hart_idx)
+    }
+
+    /// The interrupt file can only be read by its own hart.
+    #[inline(always)]
+    fn read_msip(&self, hart_idx: usize) -> bool {
+        if hart_idx != current_hartid() {
+            return false;
+        }
+        let (register, bit) = register_and_bit(IMSIC_EIP0, IMSIC_IPI_ID);
+        mireg::read(register) & bit != 0
+    }
+
+    #[inline(always)]
+    fn set_msip(&self, hart_idx: usize) {
+        if let Some(reg) = self.seteipnum(hart_idx) {
+            unsafe { reg.write_volatile((IMSIC_IPI_ID as u32).to_le()) }
+        }
+    }
+
+    /// The interrupt file can only be cleared by its own hart.
+    #[inline(always)]
+    fn clear_msip(&self, hart_idx: usize) {
+        if hart_idx != current_hartid() {
+            return;
+        }
+        let (register, bit) = register_and_bit(IMSIC_EIP0, IMSIC_IPI_ID);
+        mireg::clear_bits(register, bit);
+    }
+
+    #[inline(always)]
+    fn init_local(&self) {
+        let (register, bit) = register_and_bit(IMSIC_EIE0, IMSIC_IPI_ID);
+        mireg::write(IMSIC_EIDELIVERY, 1);
+        mireg::write(IMSIC_EITHRESHOLD, 0);
+        mireg::set_bits(register, bit);
+        unsafe { riscv::register::mie::set_mext() };
+    }
+
+    #[inline(always)]
+    fn claim_ipi(&self) -> bool {
+        match mtopei::claim() {
+            IMSIC_IPI_ID => true,
+            0 => false,
+            id => {
+                warn!("Unexpected IMSIC interrupt identity {}", id);
+                false
+            }
+        }
+    }
+}
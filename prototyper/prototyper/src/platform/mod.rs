@@ -1,67 +1,142 @@
 use alloc::string::String;
+use alloc::vec::Vec;
 use alloc::{boxed::Box, string::ToString};
-use clint::{SifiveClintWrap, THeadClintWrap};
+use clint::{AclintInfo, AclintMtimer, AclintSwi, AclintWrap};
 use core::{
     ops::Range,
     sync::atomic::{AtomicBool, Ordering},
 };
-use reset::SifiveTestDeviceWrap;
+use imsic::{ImsicInfo, ImsicWrap};
+use serde_device_tree::buildin::Node;
 use spin::Mutex;
-use uart_xilinx::MmioUartAxiLite;
 
 use crate::cfg::NUM_HART_MAX;
+use crate::devicetree::raw::{interrupt_targets, reset_nodes};
 use crate::devicetree::*;
 use crate::fail;
-use crate::platform::clint::{MachineClintType, SIFIVE_CLINT_COMPATIBLE, THEAD_CLINT_COMPATIBLE};
-use crate::platform::console::Uart16550Wrap;
-use crate::platform::console::UartBflbWrap;
-use crate::platform::console::UartSifiveWrap;
-use crate::platform::console::{
-    MachineConsoleType, UART16650U8_COMPATIBLE, UART16650U32_COMPATIBLE, UARTAXILITE_COMPATIBLE,
-    UARTBFLB_COMPATIBLE, UARTSIFIVE_COMPATIBLE,
-};
-use crate::platform::reset::SIFIVETEST_COMPATIBLE;
+use crate::firmware;
+use crate::platform::clint::MachineClintType;
+use crate::platform::driver::{Device, DeviceDriver, DevicePart, DeviceRole, find_driver};
+use crate::platform::reset::{MachineResetType, reset_action_device};
+use crate::platform::suspend::{IDLE_STATE_COMPATIBLE, WfiHartPower, WfiSuspend};
 use crate::sbi::SBI;
-use crate::sbi::console::SbiConsole;
+use crate::sbi::console::{ConsoleDevice, SbiConsole};
+use crate::sbi::dbtr::SbiDbtr;
 use crate::sbi::features::extension_detection;
+use crate::sbi::fwft::SbiFwft;
 use crate::sbi::hsm::SbiHsm;
-use crate::sbi::ipi::SbiIpi;
+use crate::sbi::ipi::{IpiDevice, SbiIpi, SbiTimer};
 use crate::sbi::logger;
-use crate::sbi::pmu::{EventToCounterMap, RawEventToCounterMap};
-use crate::sbi::reset::SbiReset;
+use crate::sbi::pmu::{
+    EventToCounterMap, RawEventToCounterMap, platform_event, register_platform_event,
+};
+use crate::sbi::reset::{ResetDevice, SbiReset};
 use crate::sbi::rfence::SbiRFence;
+use crate::sbi::sse::SbiSse;
 use crate::sbi::suspend::SbiSuspend;
 
+#[cfg(feature = "board")]
+mod board;
 mod clint;
 mod console;
+mod driver;
+mod htif;
+mod imsic;
 mod reset;
+mod suspend;
 
 type BaseAddress = usize;
 
 type CpuEnableList = [bool; NUM_HART_MAX];
 
 pub struct BoardInfo {
-    pub memory_range: Option<Range<usize>>,
-    pub console: Option<(BaseAddress, MachineConsoleType)>,
-    pub reset: Option<BaseAddress>,
+    pub memory_ranges: Vec<Range<usize>>,
+    /// Base address and driver name of the console.
+    pub console: Option<(BaseAddress, &'static str)>,
+    /// Base address and type of the reboot device.
+    pub reset: Option<(BaseAddress, MachineResetType)>,
+    /// Base address and type of the power off device.
+    pub poweroff: Option<(BaseAddress, MachineResetType)>,
     pub ipi: Option<(BaseAddress, MachineClintType)>,
+    pub aclint: AclintInfo,
+    pub imsic: Option<ImsicInfo>,
     pub cpu_num: Option<usize>,
     pub cpu_enabled: Option<CpuEnableList>,
     pub model: String,
+    /// Suspend types of the hart idle states, given by `riscv,sbi-suspend-param`.
+    pub idle_states: Vec<u32>,
+    /// Whether the board is described by the configuration instead of a device tree.
+    pub from_board: bool,
 }
 
 impl BoardInfo {
     pub const fn new() -> Self {
         BoardInfo {
-            memory_range: None,
+            memory_ranges: Vec::new(),
             console: None,
             reset: None,
+            poweroff: None,
             ipi: None,
+            aclint: AclintInfo::new(),
+            imsic: None,
             cpu_enabled: None,
             cpu_num: None,
             model: String::new(),
+            idle_states: Vec::new(),
+            from_board: false,
+        }
+    }
+
+    /// Base addresses of the devices driven by machine mode.
+    pub fn machine_devices(&self) -> Vec<BaseAddress> {
+        let mut devices = Vec::new();
+        match self.ipi {
+            Some((_, MachineClintType::Aclint)) => {
+                devices.extend(self.aclint.mswi.iter().map(|mswi| mswi.base));
+                devices.extend(self.aclint.mtimer.iter().map(|mtimer| mtimer.base));
+            }
+            Some((base, MachineClintType::Imsic)) => {
+                devices.push(base);
+                devices.extend(self.aclint.mtimer.iter().map(|mtimer| mtimer.base));
+            }
+            Some((base, _)) => devices.push(base),
+            None => {}
+        }
+        // System controllers and GPIO controllers are still used by the next stage.
+        for (base, reset_type) in [self.reset, self.poweroff].into_iter().flatten() {
+            if let MachineResetType::Device(_) = reset_type
+                && !devices.contains(&base)
+            {
+                devices.push(base);
+            }
+        }
+        devices
+    }
+}
+
+/// Collects the compatible strings of `drivers`, to find their nodes in the raw device tree.
+fn driver_compatible<'a>(drivers: impl Iterator<Item = &'a dyn DeviceDriver>) -> Vec<&'static str> {
+    drivers
+        .flat_map(|driver| driver.compatible().iter().copied())
+        .collect()
+}
+
+/// Creates the console described by `node`, with its base address and driver name.
+fn probe_console(node: &Node) -> Option<(BaseAddress, &'static str, Box<dyn ConsoleDevice>)> {
+    for device_id in get_compatible(node)?.iter() {
+        let Some(driver) = find_driver(device_id) else {
+            continue;
+        };
+        if !driver.roles().contains(&DeviceRole::Console) {
+            continue;
+        }
+        if let (Some(base), Some(Device::Console(device) | Device::ConsoleAndReset(device, _))) =
+            (driver.base(node), driver.probe(node))
+        {
+            return Some((base, driver.name(), device));
         }
     }
+    None
 }
 
 pub struct Platform {
@@ -80,95 +155,239 @@ impl Platform {
     }
 
     pub fn init(&mut self, fdt_address: usize) {
-        let dtb = parse_device_tree(fdt_address).unwrap_or_else(fail::device_tree_format);
+        let dtb = match parse_device_tree(fdt_address) {
+            Ok(dtb) => dtb,
+            #[cfg(feature = "board")]
+            Err(_) => return self.init_from_board(),
+            #[cfg(not(feature = "board"))]
+            Err(err) => fail::device_tree_format(err),
+        };
         let dtb = dtb.share();
 
-        let root: serde_device_tree::buildin::Node = serde_device_tree::from_raw_mut(&dtb)
+        let root: Node = serde_device_tree::from_raw_mut(&dtb)
             .unwrap_or_else(fail::device_tree_deserialize_root);
         let tree: Tree = root.deserialize();
 
         // Get console device, init sbi console and logger.
         self.sbi_find_and_init_console(&root);
-        // Get clint and reset device, init sbi ipi, reset, hsm, rfence and susp extension.
-        self.sbi_init_ipi_reset_hsm_rfence(&root);
+        // Get clint and reset device, init sbi ipi, reset, hsm, rfence, susp and sse extension.
+        self.sbi_init_ipi_reset_hsm_rfence(&root, fdt_address);
         // Initialize pmu extension
         self.sbi_init_pmu(&root);
         // Get other info
         self.sbi_misc_init(&tree);
+        // Initialize time extension
+        self.sbi_timer_init();
+        // Initialize fwft extension
+        self.sbi_fwft_init();
+        // Initialize dbtr extension
+        self.sbi_dbtr_init();
 
         self.ready.swap(true, Ordering::Release);
     }
 
-    fn sbi_find_and_init_console(&mut self, root: &serde_device_tree::buildin::Node) {
+    fn sbi_find_and_init_console(&mut self, root: &Node) {
         //  Get console device info
-        if let Some(stdout_path) = root.chosen_stdout_path() {
-            if let Some(node) = root.find(stdout_path) {
-                let info = get_compatible_and_range(&node);
-                if let Some((compatible, regs)) = info {
-                    for device_id in compatible.iter() {
-                        if UART16650U8_COMPATIBLE.contains(&device_id) {
-                            self.info.console = Some((regs.start, MachineConsoleType::Uart16550U8));
-                        }
-                        if UART16650U32_COMPATIBLE.contains(&device_id) {
-                            self.info.console =
-                                Some((regs.start, MachineConsoleType::Uart16550U32));
-                        }
-                        if UARTAXILITE_COMPATIBLE.contains(&device_id) {
-                            self.info.console = Some((regs.start, MachineConsoleType::UartAxiLite));
-                        }
-                        if UARTBFLB_COMPATIBLE.contains(&device_id) {
-                            self.info.console = Some((regs.start, MachineConsoleType::UartBflb));
-                        }
-                        if UARTSIFIVE_COMPATIBLE.contains(&device_id) {
-                            self.info.console = Some((regs.start, MachineConsoleType::UartSifive));
-                        }
-                    }
+        let mut console = None;
+        if let Some(stdout_path) = root.chosen_stdout_path()
+            && let Some(node) = root.find(stdout_path)
+        {
+            console = probe_console(&node);
+        }
+        // Without `stdout-path`, such as on Spike, use the first console device.
+        if console.is_none() && root.chosen_stdout_path().is_none() {
+            root.search(&mut |node: &Node| {
+                if console.is_none() {
+                    console = probe_console(node);
                 }
-            }
+            });
         }
+        let console = console.map(|(base, name, device)| {
+            self.info.console = Some((base, name));
+            device
+        });
 
         // init console and logger
-        self.sbi_console_init();
+        self.sbi_console_init(console);
         logger::Logger::init().unwrap();
         info!("Hello RustSBI!");
     }
 
-    fn sbi_init_ipi_reset_hsm_rfence(&mut self, root: &serde_device_tree::buildin::Node) {
+    fn sbi_init_ipi_reset_hsm_rfence(&mut self, root: &Node, fdt_address: usize) {
         // Get ipi and reset device info
-        let mut find_device = |node: &serde_device_tree::buildin::Node| {
-            let info = get_compatible_and_range(node);
-            if let Some(info) = info {
-                let (compatible, regs) = info;
-                let base_address = regs.start;
-                for device_id in compatible.iter() {
-                    // Initialize clint device.
-                    if SIFIVE_CLINT_COMPATIBLE.contains(&device_id) {
-                        if node.get_prop("clint,has-no-64bit-mmio").is_some() {
-                            self.info.ipi = Some((base_address, MachineClintType::TheadClint));
-                        } else {
-                            self.info.ipi = Some((base_address, MachineClintType::SiFiveClint));
+        let mut ipi_device = None;
+        let mut reboot_device = None;
+        let mut poweroff_device = None;
+        let mut aclint_nodes = Vec::new();
+        let mut imsic_nodes = Vec::new();
+        let mut find_device = |node: &Node| {
+            let Some(compatible) = get_compatible(node) else {
+                return;
+            };
+            for device_id in compatible.iter() {
+                // Consoles are selected by `stdout-path` instead.
+                if let Some(driver) = find_driver(device_id)
+                    && driver.roles() != [DeviceRole::Console]
+                    && let Some(base) = driver.base(node)
+                {
+                    match driver.probe(node) {
+                        Some(Device::Ipi(device)) => {
+                            self.info.ipi = Some((base, MachineClintType::Clint(driver.name())));
+                            ipi_device = Some(device);
                         }
-                    } else if THEAD_CLINT_COMPATIBLE.contains(&device_id) {
-                        self.info.ipi = Some((base_address, MachineClintType::TheadClint));
-                    }
-                    // Initialize reset device.
-                    if SIFIVETEST_COMPATIBLE.contains(&device_id) {
-                        self.info.reset = Some(base_address);
+                        Some(device @ (Device::Reset(_) | Device::ConsoleAndReset(..))) => {
+                            let reset_type = MachineResetType::Device(driver.name());
+                            let mut device = device.into_reset();
+                            if driver.roles().contains(&DeviceRole::Reset)
+                                && reboot_device.is_none()
+                            {
+                                self.info.reset = Some((base, reset_type));
+                                reboot_device = device.take();
+                            }
+                            if driver.roles().contains(&DeviceRole::Power)
+                                && poweroff_device.is_none()
+                            {
+                                self.info.poweroff = Some((base, reset_type));
+                                poweroff_device = device
+                                    .or_else(|| driver.probe(node).and_then(Device::into_reset));
+                            }
+                        }
+                        // Parts of the ACLINT and the IMSIC, their harts are resolved afterwards.
+                        Some(Device::Part(DevicePart::Imsic, regions)) => {
+                            imsic_nodes.push((driver, regions));
+                        }
+                        Some(Device::Part(part, regs)) => aclint_nodes.push((driver, part, regs)),
+                        Some(Device::Console(_)) | None => continue,
                     }
+                    break;
+                }
+                if IDLE_STATE_COMPATIBLE.contains(&device_id)
+                    && let Some(suspend_type) = get_u32(node, "riscv,sbi-suspend-param")
+                {
+                    self.info.idle_states.push(suspend_type);
                 }
             }
         };
         root.search(&mut find_device);
-        self.sbi_ipi_init();
+        self.sbi_find_aclint(fdt_address, aclint_nodes);
+        self.sbi_find_imsic(fdt_address, imsic_nodes);
+        self.sbi_ipi_init(ipi_device);
         self.sbi_hsm_init();
-        self.sbi_reset_init();
+        self.sbi_find_reset_nodes(fdt_address, &mut reboot_device, &mut poweroff_device);
+        self.sbi_reset_init(reboot_device, poweroff_device);
         self.sbi_rfence_init();
         self.sbi_susp_init();
+        self.sbi_sse_init();
+    }
+
+    fn sbi_find_reset_nodes(
+        &mut self,
+        fdt_address: usize,
+        reboot_device: &mut Option<Box<dyn ResetDevice>>,
+        poweroff_device: &mut Option<Box<dyn ResetDevice>>,
+    ) {
+        // Reset nodes only fill the roles no reset device has taken.
+        for node in reset_nodes(fdt_address) {
+            let (info, device) = if node.poweroff {
+                (&mut self.info.poweroff, &mut *poweroff_device)
+            } else {
+                (&mut self.info.reset, &mut *reboot_device)
+            };
+            if device.is_some() {
+                continue;
+            }
+            if let Some((base, reset_type, reset_device)) = reset_action_device(&node.action) {
+                *info = Some((base, reset_type));
+                *device = Some(reset_device);
+            }
+        }
     }
 
-    fn sbi_init_pmu(&mut self, root: &serde_device_tree::buildin::Node) {
+    fn sbi_find_aclint(
+        &mut self,
+        fdt_address: usize,
+        aclint_nodes: Vec<(&dyn DeviceDriver, DevicePart, Vec<Range<usize>>)>,
+    ) {
+        if aclint_nodes.is_empty() {
+            return;
+        }
+        let compatible = driver_compatible(aclint_nodes.iter().map(|(driver, ..)| *driver));
+        let targets = interrupt_targets(fdt_address, &compatible);
+        for (_, part, regs) in aclint_nodes {
+            let Some(base) = regs.first().map(|reg| reg.start) else {
+                continue;
+            };
+            let Some(harts) = targets
+                .iter()
+                .find(|target| target.base == base)
+                .and_then(|target| target.hart_range())
+            else {
+                warn!(
+                    "ACLINT device at 0x{:x} does not serve consecutive harts, ignored",
+                    base
+                );
+                continue;
+            };
+            match part {
+                DevicePart::AclintMswi => self.info.aclint.mswi.push(AclintSwi { base, harts }),
+                DevicePart::AclintSswi => self.info.aclint.sswi.push(AclintSwi { base, harts }),
+                DevicePart::AclintMtimer => {
+                    self.info
+                        .aclint
+                        .mtimer
+                        .extend(AclintMtimer::new(&regs, harts));
+                }
+                DevicePart::Imsic => {}
+            }
+        }
+        // A CLINT covers everything the ACLINT would provide.
+        if self.info.ipi.is_none() && self.info.aclint.is_usable() {
+            self.info.ipi = Some((self.info.aclint.mswi[0].base, MachineClintType::Aclint));
+        }
+    }
+
+    fn sbi_find_imsic(
+        &mut self,
+        fdt_address: usize,
+        imsic_nodes: Vec<(&dyn DeviceDriver, Vec<Range<usize>>)>,
+    ) {
+        if imsic_nodes.is_empty() {
+            return;
+        }
+        // Interrupt files of machine level are wired to machine external interrupts.
+        const IRQ_M_EXT: u32 = 11;
+        let compatible = driver_compatible(imsic_nodes.iter().map(|(driver, _)| *driver));
+        let targets = interrupt_targets(fdt_address, &compatible);
+        for (_, regions) in imsic_nodes {
+            let Some(base) = regions.first().map(|region| region.start) else {
+                continue;
+            };
+            let Some(target) = targets.iter().find(|target| target.base == base) else {
+                continue;
+            };
+            if target.harts.is_empty() || target.irqs.iter().any(|&irq| irq != IRQ_M_EXT) {
+                continue;
+            }
+            self.info.imsic = Some(ImsicInfo {
+                base,
+                regions,
+                harts: target.harts.clone(),
+            });
+        }
+        // Software interrupt devices are preferred over the IMSIC.
+        if self.info.ipi.is_none()
+            && let Some(imsic) = &self.info.imsic
+        {
+            self.info.ipi = Some((imsic.base, MachineClintType::Imsic));
+        }
+    }
+
+    fn sbi_init_pmu(&mut self, root: &Node) {
+        // Firmware events defined by the prototyper itself.
+        register_platform_event(platform_event::RFENCE_FULL_FLUSH);
+
         let mut pmu_node: Option<Pmu> = None;
-        let mut find_pmu = |node: &serde_device_tree::buildin::Node| {
+        let mut find_pmu = |node: &Node| {
             let info = get_compatible(node);
             if let Some(compatible_strseq) = info {
                 let compatible_iter = compatible_strseq.iter();
@@ -227,17 +446,14 @@ impl Platform {
     }
 
     fn sbi_misc_init(&mut self, tree: &Tree) {
-        // Get memory info
-        // TODO: More than one memory node or range?
-        let memory_reg = tree
-            .memory
-            .iter()
-            .next()
-            .unwrap()
-            .deserialize::<Memory>()
-            .reg;
-        let memory_range = memory_reg.iter().next().unwrap().0;
-        self.info.memory_range = Some(memory_range);
+        // Get memory info from all memory nodes and ranges
+        let mut memory_ranges = Vec::new();
+        for memory in tree.memory.iter() {
+            let memory_reg = memory.deserialize::<Memory>().reg;
+            memory_ranges.extend(memory_reg.iter().map(|reg| reg.0));
+        }
+        memory_ranges.sort_unstable_by_key(|range| range.start);
+        self.info.memory_ranges = memory_ranges;
 
         // Get cpu number info
         self.info.cpu_num = Some(tree.cpus.cpu.len());
@@ -272,61 +488,64 @@ impl Platform {
         self.info.cpu_enabled = Some(cpu_list);
     }
 
-    fn sbi_console_init(&mut self) {
-        if let Some((base, console_type)) = self.info.console {
-            self.sbi.console = match console_type {
-                MachineConsoleType::Uart16550U8 => Some(SbiConsole::new(Mutex::new(Box::new(
-                    Uart16550Wrap::<u8>::new(base),
-                )))),
-                MachineConsoleType::Uart16550U32 => Some(SbiConsole::new(Mutex::new(Box::new(
-                    Uart16550Wrap::<u32>::new(base),
-                )))),
-                MachineConsoleType::UartAxiLite => Some(SbiConsole::new(Mutex::new(Box::new(
-                    MmioUartAxiLite::new(base),
-                )))),
-                MachineConsoleType::UartBflb => Some(SbiConsole::new(Mutex::new(Box::new(
-                    UartBflbWrap::new(base),
-                )))),
-                MachineConsoleType::UartSifive => Some(SbiConsole::new(Mutex::new(Box::new(
-                    UartSifiveWrap::new(base),
-                )))),
-            };
-        } else {
-            self.sbi.console = None;
-        }
+    fn sbi_console_init(&mut self, device: Option<Box<dyn ConsoleDevice>>) {
+        self.sbi.console = device.map(|device| SbiConsole::new(Mutex::new(device)));
     }
 
-    fn sbi_reset_init(&mut self) {
-        if let Some(base) = self.info.reset {
-            self.sbi.reset = Some(SbiReset::new(Mutex::new(Box::new(
-                SifiveTestDeviceWrap::new(base),
-            ))));
-        } else {
-            self.sbi.reset = None;
-        }
+    fn sbi_reset_init(
+        &mut self,
+        reboot_device: Option<Box<dyn ResetDevice>>,
+        poweroff_device: Option<Box<dyn ResetDevice>>,
+    ) {
+        self.sbi.reset = (reboot_device.is_some() || poweroff_device.is_some())
+            .then(|| SbiReset::new(reboot_device, poweroff_device));
     }
 
-    fn sbi_ipi_init(&mut self) {
-        if let Some((base, clint_type)) = self.info.ipi {
-            self.sbi.ipi = match clint_type {
-                MachineClintType::SiFiveClint => Some(SbiIpi::new(
-                    Mutex::new(Box::new(SifiveClintWrap::new(base))),
-                    self.info.cpu_num.unwrap_or(NUM_HART_MAX),
-                )),
-                MachineClintType::TheadClint => Some(SbiIpi::new(
-                    Mutex::new(Box::new(THeadClintWrap::new(base))),
-                    self.info.cpu_num.unwrap_or(NUM_HART_MAX),
-                )),
-            };
+    fn sbi_ipi_init(&mut self, device: Option<Box<dyn IpiDevice>>) {
+        let device: Option<Box<dyn IpiDevice>> = match self.info.ipi {
+            Some((_, MachineClintType::Clint(_))) => device,
+            Some((_, MachineClintType::Aclint)) => {
+                Some(Box::new(AclintWrap::new(self.info.aclint.clone())))
+            }
+            Some((_, MachineClintType::Imsic)) => self.info.imsic.clone().map(|imsic| {
+                Box::new(ImsicWrap::new(imsic, self.info.aclint.clone())) as Box<dyn IpiDevice>
+            }),
+            None => None,
+        };
+        let max_hart_id = self.info.cpu_num.unwrap_or(NUM_HART_MAX);
+        self.sbi.ipi = device.map(|device| SbiIpi::new(Mutex::new(device), max_hart_id));
+    }
+
+    /// Provides the TIME extension if every enabled hart has a machine timer or Sstc.
+    ///
+    /// Must run after extension detection.
+    fn sbi_timer_init(&mut self) {
+        let Some(ipi) = self.sbi.ipi.as_ref() else {
+            self.sbi.timer = None;
+            return;
+        };
+        let enabled_harts = self.info.cpu_enabled.iter().flat_map(|list| {
+            list.iter()
+                .enumerate()
+                .filter_map(|(hart_id, enabled)| enabled.then_some(hart_id))
+        });
+        let mut missing = enabled_harts.filter(|&hart_id| !ipi.timer_available(hart_id));
+        if let Some(hart_id) = missing.next() {
+            warn!(
+                "Hart {} has neither a machine timer nor Sstc, TIME extension disabled",
+                hart_id
+            );
+            self.sbi.timer = None;
         } else {
-            self.sbi.ipi = None;
+            self.sbi.timer = Some(SbiTimer);
         }
     }
 
     fn sbi_hsm_init(&mut self) {
         // TODO: Can HSM work properly when there is no ipi device?
         if self.info.ipi.is_some() {
-            self.sbi.hsm = Some(SbiHsm);
+            let power = WfiHartPower::new(self.info.idle_states.clone());
+            self.sbi.hsm = Some(SbiHsm::new(Box::new(power)));
         } else {
             self.sbi.hsm = None;
         }
@@ -343,12 +562,29 @@ impl Platform {
 
     fn sbi_susp_init(&mut self) {
         if self.sbi.hsm.is_some() {
-            self.sbi.susp = Some(SbiSuspend);
+            self.sbi.susp = Some(SbiSuspend::new(Box::new(WfiSuspend)));
         } else {
             self.sbi.susp = None;
         }
     }
 
+    fn sbi_fwft_init(&mut self) {
+        self.sbi.fwft = Some(SbiFwft);
+    }
+
+    fn sbi_dbtr_init(&mut self) {
+        self.sbi.dbtr = Some(SbiDbtr);
+    }
+
+    fn sbi_sse_init(&mut self) {
+        // Events on other harts are delivered over IPI.
+        if self.sbi.ipi.is_some() {
+            self.sbi.sse = Some(SbiSse::new());
+        } else {
+            self.sbi.sse = None;
+        }
+    }
+
     pub fn print_board_info(&self) {
         info!("RustSBI version {}", rustsbi::VERSION);
         rustsbi::LOGO.lines().for_each(|line| info!("{}", line));
@@ -391,11 +627,15 @@ impl Platform {
     #[inline]
     fn print_device_info(&self) {
         self.print_clint_info();
+        self.print_timer_info();
         self.print_console_info();
         self.print_reset_info();
         self.print_hsm_info();
         self.print_rfence_info();
         self.print_susp_info();
+        self.print_sse_info();
+        self.print_fwft_info();
+        self.print_dbtr_info();
         self.print_pmu_info();
     }
 
@@ -404,20 +644,47 @@ impl Platform {
         match self.info.ipi {
             Some((base, device)) => {
                 info!(
-                    "{:<30}: {:?} (Base Address: 0x{:x})",
-                    "Platform IPI Extension", device, base
+                    "{:<30}: {} (Base Address: 0x{:x})",
+                    "Platform IPI Extension",
+                    device.name(),
+                    base
                 );
+                if let MachineClintType::Aclint | MachineClintType::Imsic = device {
+                    self.print_aclint_info();
+                }
             }
             None => warn!("{:<30}: Not Available", "Platform IPI Device"),
         }
     }
 
+    fn print_aclint_info(&self) {
+        let aclint = &self.info.aclint;
+        for mswi in aclint.mswi.iter() {
+            info!(
+                "{:<30}: HARTs {:?} (Base Address: 0x{:x})",
+                "ACLINT MSWI", mswi.harts, mswi.base
+            );
+        }
+        for mtimer in aclint.mtimer.iter() {
+            info!(
+                "{:<30}: HARTs {:?} (Base Address: 0x{:x})",
+                "ACLINT MTIMER", mtimer.harts, mtimer.base
+            );
+        }
+        for sswi in aclint.sswi.iter() {
+            info!(
+                "{:<30}: HARTs {:?} (Base Address: 0x{:x})",
+                "ACLINT SSWI", sswi.harts, sswi.base
+            );
+        }
+    }
+
     #[inline]
     fn print_console_info(&self) {
         match self.info.console {
             Some((base, device)) => {
                 info!(
-                    "{:<30}: {:?} (Base Address: 0x{:x})",
+                    "{:<30}: {} (Base Address: 0x{:x})",
                     "Platform Console Extension", device, base
                 );
             }
@@ -427,20 +694,38 @@ impl Platform {
 
     #[inline]
     fn print_reset_info(&self) {
-        if let Some(base) = self.info.reset {
+        if let Some((base, reset_type)) = self.info.reset {
             info!(
-                "{:<30}: Available (Base Address: 0x{:x})",
-                "Platform Reset Extension", base
+                "{:<30}: {} (Base Address: 0x{:x})",
+                "Platform Reset Extension",
+                reset_type.name(),
+                base
             );
         } else {
             warn!("{:<30}: Not Available", "Platform Reset Device");
         }
+        if let Some((base, reset_type)) = self.info.poweroff {
+            info!(
+                "{:<30}: {} (Base Address: 0x{:x})",
+                "Platform Power Off Device",
+                reset_type.name(),
+                base
+            );
+        } else {
+            warn!("{:<30}: Not Available", "Platform Power Off Device");
+        }
     }
 
     #[inline]
     fn print_hsm_info(&self) {
         if self.have_hsm() {
             info!("{:<30}: {}", "Platform HSM Extension", "Available");
+            if !self.info.idle_states.is_empty() {
+                info!(
+                    "{:<30}: {:x?}",
+                    "Platform HART Idle States", self.info.idle_states
+                );
+            }
         } else {
             warn!("{:<30}: {}", "Platform HSM Extension", "Not Available");
         }
@@ -464,6 +749,42 @@ impl Platform {
         }
     }
 
+    #[inline]
+    fn print_sse_info(&self) {
+        if self.have_sse() {
+            info!("{:<30}: {}", "Platform SSE Extension", "Available");
+        } else {
+            warn!("{:<30}: {}", "Platform SSE Extension", "Not Available");
+        }
+    }
+
+    #[inline]
+    fn print_fwft_info(&self) {
+        if self.have_fwft() {
+            info!("{:<30}: {}", "Platform FWFT Extension", "Available");
+        } else {
+            warn!("{:<30}: {}", "Platform FWFT Extension", "Not Available");
+        }
+    }
+
+    #[inline]
+    fn print_timer_info(&self) {
+        if self.have_timer() {
+            info!("{:<30}: {}", "Platform TIME Extension", "Available");
+        } else {
+            warn!("{:<30}: {}", "Platform TIME Extension", "Not Available");
+        }
+    }
+
+    #[inline]
+    fn print_dbtr_info(&self) {
+        if self.have_dbtr() {
+            info!("{:<30}: {}", "Platform DBTR Extension", "Available");
+        } else {
+            warn!("{:<30}: {}", "Platform DBTR Extension", "Not Available");
+        }
+    }
+
     #[inline]
     fn print_pmu_info(&self) {
         if self.have_pmu() {
@@ -475,13 +796,14 @@ impl Platform {
 
     #[inline]
     fn print_memory_info(&self) {
-        if let Some(memory_range) = &self.info.memory_range {
+        if self.info.memory_ranges.is_empty() {
+            warn!("{:<30}: Not Available", "Memory range");
+        }
+        for memory_range in &self.info.memory_ranges {
             info!(
                 "{:<30}: 0x{:x} - 0x{:x}",
                 "Memory range", memory_range.start, memory_range.end
             );
-        } else {
-            warn!("{:<30}: Not Available", "Memory range");
         }
     }
 
@@ -515,6 +837,10 @@ impl Platform {
         self.sbi.ipi.is_some()
     }
 
+    pub fn have_timer(&self) -> bool {
+        self.sbi.timer.is_some()
+    }
+
     pub fn have_hsm(&self) -> bool {
         self.sbi.hsm.is_some()
     }
@@ -527,6 +853,18 @@ impl Platform {
         self.sbi.susp.is_some()
     }
 
+    pub fn have_sse(&self) -> bool {
+        self.sbi.sse.is_some()
+    }
+
+    pub fn have_fwft(&self) -> bool {
+        self.sbi.fwft.is_some()
+    }
+
+    pub fn have_dbtr(&self) -> bool {
+        self.sbi.dbtr.is_some()
+    }
+
     pub fn have_pmu(&self) -> bool {
         self.sbi.pmu.is_some()
     }
@@ -534,6 +872,18 @@ impl Platform {
     pub fn ready(&self) -> bool {
         self.ready.load(Ordering::Acquire)
     }
+
+    /// Checks whether `size` bytes starting at physical address `start` are in memory.
+    pub fn memory_contains(&self, start: usize, size: usize) -> bool {
+        self.info.memory_ranges.iter().any(|range| {
+            start >= range.start && start.checked_add(size).is_some_and(|end| end <= range.end)
+        })
+    }
+
+    /// Checks whether `size` bytes starting at `start` can be shared with supervisor software.
+    pub fn shmem_accessible(&self, start: usize, size: usize) -> bool {
+        self.memory_contains(start, size) && !firmware::overlaps_firmware(start, size)
+    }
 }
 
 pub(crate) static mut PLATFORM: Platform = Platform::new();
@@ -1,7 +1,39 @@
+use alloc::boxed::Box;
 use sifive_test_device::SifiveTestDevice;
 
+use crate::devicetree::raw::ResetAction;
+use crate::platform::driver::{Device, DeviceDriver, DeviceRole};
 use crate::sbi::reset::ResetDevice;
-pub(crate) const SIFIVETEST_COMPATIBLE: [&str; 1] = ["sifive,test0"];
+
+/// Compatible string of the GPIO controllers usable by `gpio-restart` and `gpio-poweroff`.
+const SIFIVE_GPIO_COMPATIBLE: &str = "sifive,gpio0";
+
+/// Registers of the SiFive GPIO controller.
+const SIFIVE_GPIO_INPUT_EN: usize = 0x04;
+const SIFIVE_GPIO_OUTPUT_EN: usize = 0x08;
+const SIFIVE_GPIO_OUTPUT_VAL: usize = 0x0c;
+
+#[doc(hidden)]
+#[derive(Clone, Copy, Debug)]
+pub enum MachineResetType {
+    /// A single device created by the named driver.
+    Device(&'static str),
+    /// A register of a system controller shared with the next stage.
+    Syscon,
+    /// A line of a GPIO controller shared with the next stage.
+    Gpio,
+}
+
+impl MachineResetType {
+    /// Name of the device type, shown in the boot log.
+    pub fn name(&self) -> &'static str {
+        match self {
+            MachineResetType::Device(name) => name,
+            MachineResetType::Syscon => "Syscon",
+            MachineResetType::Gpio => "Gpio",
+        }
+    }
+}
 
 pub struct SifiveTestDeviceWrap {
     inner: *const SifiveTestDevice,
@@ -32,3 +64,162 @@ impl ResetDevice for SifiveTestDeviceWrap {
         unsafe { (*self.inner).reset() }
     }
 }
+
+/// For `syscon-reboot` and `syscon-poweroff`, which only perform their own action.
+pub struct SysconResetWrap {
+    address: *mut u32,
+    value: u32,
+    mask: u32,
+}
+
+impl SysconResetWrap {
+    pub fn new(address: usize, value: u32, mask: u32) -> Self {
+        Self {
+            address: address as *mut u32,
+            value,
+            mask,
+        }
+    }
+
+    fn trigger(&self) -> ! {
+        unsafe {
+            let old = self.address.read_volatile();
+            self.address
+                .write_volatile((old & !self.mask) | (self.value & self.mask));
+        }
+        loop {
+            core::hint::spin_loop()
+        }
+    }
+}
+
+impl ResetDevice for SysconResetWrap {
+    #[inline]
+    fn fail(&self, _code: u16) -> ! {
+        self.trigger()
+    }
+
+    #[inline]
+    fn pass(&self) -> ! {
+        self.trigger()
+    }
+
+    #[inline]
+    fn reset(&self) -> ! {
+        self.trigger()
+    }
+}
+
+/// For `gpio-restart` and `gpio-poweroff` on SiFive GPIO controllers, which only perform
+/// their own action.
+pub struct GpioResetWrap {
+    base: usize,
+    pin: u32,
+    active_low: bool,
+}
+
+impl GpioResetWrap {
+    pub fn new(base: usize, pin: u32, active_low: bool) -> Self {
+        Self {
+            base,
+            pin,
+            active_low,
+        }
+    }
+
+    fn update(&self, reg: usize, set: bool) {
+        let reg = (self.base + reg) as *mut u32;
+        unsafe {
+            let old = reg.read_volatile();
+            let new = if set {
+                old | (1 << self.pin)
+            } else {
+                old & !(1 << self.pin)
+            };
+            reg.write_volatile(new);
+        }
+    }
+
+    fn trigger(&self) -> ! {
+        self.update(SIFIVE_GPIO_OUTPUT_VAL, !self.active_low);
+        self.update(SIFIVE_GPIO_INPUT_EN, false);
+        self.update(SIFIVE_GPIO_OUTPUT_EN, true);
+        loop {
+            core::hint::spin_loop()
+        }
+    }
+}
+
+impl ResetDevice for GpioResetWrap {
+    #[inline]
+    fn fail(&self, _code: u16) -> ! {
+        self.trigger()
+    }
+
+    #[inline]
+    fn pass(&self) -> ! {
+        self.trigger()
+    }
+
+    #[inline]
+    fn reset(&self) -> ! {
+        self.trigger()
+    }
+}
+
+/// Creates the device performing a reset action of the device tree, if it is supported.
+pub fn reset_action_device(
+    action: &ResetAction,
+) -> Option<(usize, MachineResetType, Box<dyn ResetDevice>)> {
+    match action {
+        &ResetAction::Syscon {
+            address,
+            value,
+            mask,
+        } => Some((
+            address,
+            MachineResetType::Syscon,
+            Box::new(SysconResetWrap::new(address, value, mask)),
+        )),
+        ResetAction::Gpio {
+            base,
+            compatible,
+            pin,
+            active_low,
+        } if compatible.iter().any(|id| id == SIFIVE_GPIO_COMPATIBLE) && *pin < u32::BITS => {
+            Some((
+                *base,
+                MachineResetType::Gpio,
+                Box::new(GpioResetWrap::new(*base, *pin, *active_low)),
+            ))
+        }
+        ResetAction::Gpio { base, .. } => {
+            warn!(
+                "GPIO controller at 0x{:x} is not supported for reset, ignored",
+                base
+            );
+            None
+        }
+    }
+}
+
+/// Driver of the SiFive test device, which resets and powers off the system.
+pub struct SifiveTestDriver;
+
+impl DeviceDriver for SifiveTestDriver {
+    fn name(&self) -> &'static str {
+        "SifiveTest"
+    }
+
+    fn compatible(&self) -> &'static [&'static str] {
+        &["sifive,test0"]
+    }
+
+    fn roles(&self) -> &'static [DeviceRole] {
+        &[DeviceRole::Reset, DeviceRole::Power]
+    }
+
+    fn create(&self, base: usize) -> Device {
+        Device::Reset(Box::new(SifiveTestDeviceWrap::new(base)))
+    }
+}
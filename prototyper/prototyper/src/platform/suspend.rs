@@ -0,0 +1,60 @@
+use alloc::vec::Vec;
+use riscv::register::{mie, mip};
+
+use crate::platform::PLATFORM;
+use crate::sbi::hsm::HartPowerDevice;
+use crate::sbi::suspend::{SUSPEND_TO_RAM, SystemSuspendDevice};
+use crate::sbi::trap::handler::msoft_ipi_handler;
+
+pub(crate) const IDLE_STATE_COMPATIBLE: [&str; 1] = ["riscv,idle-state"];
+
+/// Suspend to RAM for platforms without a power controller, such as QEMU `virt`.
+///
+/// Memory is never powered down, so the hart just waits for an IPI or any other enabled interrupt.
+pub struct WfiSuspend;
+
+impl SystemSuspendDevice for WfiSuspend {
+    fn supports(&self, sleep_type: u32) -> bool {
+        sleep_type == SUSPEND_TO_RAM
+    }
+
+    fn suspend(&self, _sleep_type: u32) -> bool {
+        if unsafe { PLATFORM.sbi.ipi.is_none() } {
+            return false;
+        }
+        // Handle IPIs sent before suspending, so they don't wake the hart at once.
+        msoft_ipi_handler();
+        unsafe {
+            mie::set_msoft();
+        }
+        while mip::read().bits() & mie::read().bits() == 0 {
+            riscv::asm::wfi();
+        }
+        msoft_ipi_handler();
+        true
+    }
+}
+
+/// Hart idle states of platforms without a power controller, such as QEMU `virt`.
+///
+/// Each of the platform-specific suspend types described by the board waits for an interrupt,
+/// and harts are never powered off.
+pub struct WfiHartPower {
+    suspend_types: Vec<u32>,
+}
+
+impl WfiHartPower {
+    pub fn new(suspend_types: Vec<u32>) -> Self {
+        Self { suspend_types }
+    }
+}
+
+impl HartPowerDevice for WfiHartPower {
+    fn supports(&self, suspend_type: u32) -> bool {
+        self.suspend_types.contains(&suspend_type)
+    }
+
+    fn suspend(&self, _suspend_type: u32) {
+        riscv::asm::wfi();
+    }
+}
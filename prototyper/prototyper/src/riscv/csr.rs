@@ -4,9 +4,17 @@ use pastey::paste;
 use seq_macro::seq;
 
 /// CSR addresses
+pub const CSR_VSTART: u16 = 0x008;
 pub const CSR_STIMECMP: u16 = 0x14D;
 pub const CSR_MCOUNTEREN: u16 = 0x306;
 pub const CSR_MENVCFG: u16 = 0x30a;
+pub const CSR_MISELECT: u16 = 0x350;
+pub const CSR_MIREG: u16 = 0x351;
+pub const CSR_MTOPEI: u16 = 0x35c;
+pub const CSR_SCOUNTOVF: u16 = 0xda0;
+pub const CSR_TSELECT: u16 = 0x7a0;
+pub const CSR_TDATA1: u16 = 0x7a1;
+pub const CSR_TINFO: u16 = 0x7a4;
 pub const CSR_MCYCLE: u16 = 0xb00;
 pub const CSR_MINSTRET: u16 = 0xb02;
 seq!(N in 3..32 {
@@ -26,6 +34,10 @@ pub const CSR_INSTRET: u16 = 0xc02;
 seq!(N in 3..32 {
     pub const CSR_HPMCOUNTER~N: u16 = 0xc00 + N;
 });
+/* Vector */
+pub const CSR_VL: u16 = 0xc20;
+pub const CSR_VTYPE: u16 = 0xc21;
+pub const CSR_VLENB: u16 = 0xc22;
 /// MHPMEVENT
 pub const CSR_MCOUNTINHIBIT: u16 = 0x320;
 pub const CSR_MCYCLECFG: u16 = 0x321;
@@ -56,6 +68,12 @@ pub mod menvcfg {
     pub const CBCFE: usize = 0x1 << 6;
     /// Cache block zero for enclave.
     pub const CBZE: usize = 0x1 << 7;
+    /// Pointer masking mode for supervisor mode.
+    pub const PMM: usize = 0x3 << 32;
+    /// Supervisor double trap enable.
+    pub const DTE: usize = 0x1 << 59;
+    /// Hardware A/D bit updating enable.
+    pub const ADUE: usize = 0x1 << 61;
     /// Page-based memory types enable.
     pub const PBMTE: usize = 0x1 << 62;
     /// Supervisor timer counter enable.
@@ -81,6 +99,22 @@ pub mod menvcfg {
             asm!("csrw menvcfg, {}", in(reg) bits, options(nomem));
         }
     }
+
+    /// Clears specified bits in menvcfg register.
+    pub fn clear_bits(option: usize) {
+        unsafe {
+            asm!("csrc menvcfg, {}", in(reg) option, options(nomem));
+        }
+    }
+
+    /// Reads the menvcfg register.
+    pub fn read() -> usize {
+        let bits: usize;
+        unsafe {
+            asm!("csrr {}, menvcfg", out(reg) bits, options(nomem));
+        }
+        bits
+    }
 }
 
 /// Supervisor timer compare register operations.
@@ -95,6 +129,125 @@ pub mod stimecmp {
     }
 }
 
+/// Machine indirect register access, used for the IMSIC interrupt file of the hart.
+pub mod mireg {
+    use super::{CSR_MIREG, CSR_MISELECT};
+    use core::arch::asm;
+
+    /// Reads the indirect register selected by `select`.
+    pub fn read(select: usize) -> usize {
+        let bits: usize;
+        unsafe {
+            asm!("csrw {csr}, {}", in(reg) select, csr = const CSR_MISELECT, options(nomem));
+            asm!("csrr {}, {csr}", out(reg) bits, csr = const CSR_MIREG, options(nomem));
+        }
+        bits
+    }
+
+    /// Writes the indirect register selected by `select`.
+    pub fn write(select: usize, value: usize) {
+        unsafe {
+            asm!("csrw {csr}, {}", in(reg) select, csr = const CSR_MISELECT, options(nomem));
+            asm!("csrw {csr}, {}", in(reg) value, csr = const CSR_MIREG, options(nomem));
+        }
+    }
+
+    /// Sets specified bits in the indirect register selected by `select`.
+    pub fn set_bits(select: usize, bits: usize) {
+        unsafe {
+            asm!("csrw {csr}, {}", in(reg) select, csr = const CSR_MISELECT, options(nomem));
+            asm!("csrs {csr}, {}", in(reg) bits, csr = const CSR_MIREG, options(nomem));
+        }
+    }
+
+    /// Clears specified bits in the indirect register selected by `select`.
+    pub fn clear_bits(select: usize, bits: usize) {
+        unsafe {
+            asm!("csrw {csr}, {}", in(reg) select, csr = const CSR_MISELECT, options(nomem));
+            asm!("csrc {csr}, {}", in(reg) bits, csr = const CSR_MIREG, options(nomem));
+        }
+    }
+}
+
+/// Machine top external interrupt register operations.
+pub mod mtopei {
+    use super::CSR_MTOPEI;
+    use core::arch::asm;
+
+    /// Claims the highest priority pending interrupt and returns its identity.
+    pub fn claim() -> usize {
+        let bits: usize;
+        unsafe {
+            asm!("csrrw {}, {csr}, zero", out(reg) bits, csr = const CSR_MTOPEI, options(nomem));
+        }
+        bits >> 16
+    }
+}
+
+/// Vector extension state, used to emulate misaligned vector accesses.
+pub mod vector {
+    use super::{CSR_VL, CSR_VLENB, CSR_VSTART, CSR_VTYPE};
+    use core::arch::asm;
+
+    /// Reads the index of the first element to access.
+    pub fn vstart() -> usize {
+        let bits: usize;
+        unsafe {
+            asm!("csrr {}, {csr}", out(reg) bits, csr = const CSR_VSTART, options(nomem));
+        }
+        bits
+    }
+
+    /// Writes the index of the first element to access.
+    pub fn set_vstart(value: usize) {
+        unsafe {
+            asm!("csrw {csr}, {}", in(reg) value, csr = const CSR_VSTART, options(nomem));
+        }
+    }
+
+    /// Reads the vector length.
+    pub fn vl() -> usize {
+        let bits: usize;
+        unsafe {
+            asm!("csrr {}, {csr}", out(reg) bits, csr = const CSR_VL, options(nomem));
+        }
+        bits
+    }
+
+    /// Sets the vector length to `avl`, which must not exceed the current one, keeping `vtype`.
+    pub fn set_vl(avl: usize, vtype: usize) {
+        unsafe {
+            asm!(
+                ".option push",
+                ".option arch, +v",
+                "vsetvl zero, {}, {}",
+                ".option pop",
+                in(reg) avl,
+                in(reg) vtype,
+                options(nomem),
+            );
+        }
+    }
+
+    /// Reads the vector data type register.
+    pub fn vtype() -> usize {
+        let bits: usize;
+        unsafe {
+            asm!("csrr {}, {csr}", out(reg) bits, csr = const CSR_VTYPE, options(nomem));
+        }
+        bits
+    }
+
+    /// Reads the length of a vector register in bytes.
+    pub fn vlenb() -> usize {
+        let bits: usize;
+        unsafe {
+            asm!("csrr {}, {csr}", out(reg) bits, csr = const CSR_VLENB, options(nomem));
+        }
+        bits
+    }
+}
+
 pub mod mcycle {
     use core::arch::asm;
     pub fn write(value: u64) {
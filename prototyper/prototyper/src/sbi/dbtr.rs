@@ -0,0 +1,472 @@
+use core::arch::asm;
+use rustsbi::{SbiRet, SharedPtr, TriggerMask};
+
+use crate::platform::PLATFORM;
+use crate::riscv::current_hartid;
+use crate::sbi::features::{TRIGGER_MAX, hart_trigger_count, hart_trigger_types};
+use crate::sbi::trap_stack::hart_context_mut;
+
+/// Number of XLEN-bit words in a shared memory entry.
+const ENTRY_WORDS: usize = 4;
+
+/// `tdata1.type` field.
+const TDATA1_TYPE_SHIFT: u32 = usize::BITS - 4;
+/// `tdata1.dmode` bit, making the trigger accessible only in debug mode.
+const TDATA1_DMODE: usize = 1 << (usize::BITS - 5);
+
+/// Address/data match trigger.
+const TYPE_MCONTROL: usize = 2;
+/// Address/data match trigger, with hypervisor support.
+const TYPE_MCONTROL6: usize = 6;
+
+/// Fields shared by `mcontrol` and `mcontrol6`.
+const MCONTROL_U: usize = 1 << 3;
+const MCONTROL_S: usize = 1 << 4;
+const MCONTROL_M: usize = 1 << 6;
+const MCONTROL_CHAIN: usize = 1 << 11;
+const MCONTROL_ACTION: usize = 0xf << 12;
+const MCONTROL6_VU: usize = 1 << 23;
+const MCONTROL6_VS: usize = 1 << 24;
+
+/// `trig_state` fields.
+const STATE_MAPPED: usize = 1 << 0;
+const STATE_U: usize = 1 << 1;
+const STATE_S: usize = 1 << 2;
+const STATE_VU: usize = 1 << 3;
+const STATE_VS: usize = 1 << 4;
+const STATE_HAVE_HW_TRIG: usize = 1 << 5;
+const STATE_HW_TRIG_IDX_SHIFT: u32 = 8;
+
+/// Privilege mode bits of `tdata1` and their copies in `trig_state`.
+const PRIV_BITS: [(usize, usize); 4] = [
+    (MCONTROL_U, STATE_U),
+    (MCONTROL_S, STATE_S),
+    (MCONTROL6_VU, STATE_VU),
+    (MCONTROL6_VS, STATE_VS),
+];
+
+/// A debug trigger of the current hart.
+///
+/// Each `trig_idx` maps to the hardware trigger with the same index.
+#[derive(Clone, Copy)]
+struct Trigger {
+    state: usize,
+}
+
+impl Trigger {
+    const fn new() -> Self {
+        Self { state: 0 }
+    }
+
+    #[inline]
+    const fn is_mapped(&self) -> bool {
+        self.state & STATE_MAPPED != 0
+    }
+}
+
+/// Per-hart state of the debug triggers.
+pub(crate) struct DbtrState {
+    /// Physical address of the shared memory, if enabled.
+    shmem: Option<usize>,
+    triggers: [Trigger; TRIGGER_MAX],
+}
+
+impl DbtrState {
+    pub const fn new() -> Self {
+        Self {
+            shmem: None,
+            triggers: [Trigger::new(); TRIGGER_MAX],
+        }
+    }
+
+    /// Uninstalls all triggers and disables the shared memory on the current hart.
+    pub fn reset(&mut self) {
+        for (idx, trigger) in self.triggers.iter_mut().enumerate() {
+            if trigger.is_mapped() {
+                clear_hw_trigger(idx);
+                *trigger = Trigger::new();
+            }
+        }
+        self.shmem = None;
+    }
+}
+
+#[inline]
+fn local_dbtr() -> &'static mut DbtrState {
+    &mut hart_context_mut(current_hartid()).dbtr
+}
+
+#[inline]
+fn trig_max() -> usize {
+    hart_trigger_count(current_hartid())
+}
+
+#[inline]
+const fn tdata1_type(tdata1: usize) -> usize {
+    tdata1 >> TDATA1_TYPE_SHIFT
+}
+
+/// Checks a trigger configuration from supervisor software and returns the `tdata1` to install.
+///
+/// Machine mode and debug mode bits are always cleared, so that supervisor software can never
+/// install triggers firing in M-mode or taking the hart into debug mode.
+fn sanitize_tdata1(tdata1: usize) -> Result<usize, SbiRet> {
+    match tdata1_type(tdata1) {
+        TYPE_MCONTROL | TYPE_MCONTROL6 => {}
+        _ => return Err(SbiRet::not_supported()),
+    }
+    // Only the breakpoint exception action is allowed.
+    if tdata1 & MCONTROL_ACTION != 0 {
+        return Err(SbiRet::invalid_param());
+    }
+    Ok(tdata1 & !(TDATA1_DMODE | MCONTROL_M))
+}
+
+/// Privilege mode bits to save in `trig_state` from `tdata1`.
+#[inline]
+fn priv_state(tdata1: usize) -> usize {
+    let bits: &[(usize, usize)] = if tdata1_type(tdata1) == TYPE_MCONTROL6 {
+        &PRIV_BITS
+    } else {
+        &PRIV_BITS[..2]
+    };
+    bits.iter()
+        .filter(|(tdata1_bit, _)| tdata1 & tdata1_bit != 0)
+        .fold(0, |state, (_, state_bit)| state | state_bit)
+}
+
+/// Privilege mode bits of `tdata1` saved in `trig_state`.
+#[inline]
+fn priv_tdata1(tdata1: usize, state: usize) -> usize {
+    let bits: &[(usize, usize)] = if tdata1_type(tdata1) == TYPE_MCONTROL6 {
+        &PRIV_BITS
+    } else {
+        &PRIV_BITS[..2]
+    };
+    bits.iter()
+        .filter(|(_, state_bit)| state & state_bit != 0)
+        .fold(0, |tdata1, (tdata1_bit, _)| tdata1 | tdata1_bit)
+}
+
+/// Privilege mode bits in `tdata1` of this trigger type.
+#[inline]
+fn priv_mask(tdata1: usize) -> usize {
+    priv_tdata1(tdata1, STATE_U | STATE_S | STATE_VU | STATE_VS)
+}
+
+#[inline]
+fn mapped_state(idx: usize, tdata1: usize) -> usize {
+    STATE_MAPPED | STATE_HAVE_HW_TRIG | priv_state(tdata1) | idx << STATE_HW_TRIG_IDX_SHIFT
+}
+
+#[inline]
+fn select(idx: usize) {
+    unsafe { asm!("csrw tselect, {}", in(reg) idx) };
+}
+
+#[inline]
+fn read_hw_trigger(idx: usize) -> [usize; 3] {
+    let (tdata1, tdata2, tdata3): (usize, usize, usize);
+    select(idx);
+    unsafe {
+        asm!("csrr {}, tdata1", out(reg) tdata1);
+        asm!("csrr {}, tdata2", out(reg) tdata2);
+        asm!("csrr {}, tdata3", out(reg) tdata3);
+    }
+    [tdata1, tdata2, tdata3]
+}
+
+/// Programs a hardware trigger, returning the `tdata1` value accepted by the hardware.
+#[inline]
+fn write_hw_trigger(idx: usize, [tdata1, tdata2, tdata3]: [usize; 3]) -> usize {
+    let accepted: usize;
+    select(idx);
+    unsafe {
+        // Disable the trigger while changing its configuration.
+        asm!("csrw tdata1, zero");
+        asm!("csrw tdata2, {}", in(reg) tdata2);
+        asm!("csrw tdata3, {}", in(reg) tdata3);
+        asm!("csrw tdata1, {}", in(reg) tdata1);
+        asm!("csrr {}, tdata1", out(reg) accepted);
+    }
+    accepted
+}
+
+#[inline]
+fn clear_hw_trigger(idx: usize) {
+    select(idx);
+    unsafe {
+        asm!("csrw tdata1, zero");
+        asm!("csrw tdata2, zero");
+        asm!("csrw tdata3, zero");
+    }
+}
+
+#[inline]
+fn read_entry(shmem: usize, i: usize) -> [usize; ENTRY_WORDS] {
+    let entry = (shmem as *const usize).wrapping_add(i * ENTRY_WORDS);
+    core::array::from_fn(|word| unsafe { entry.add(word).read_volatile() })
+}
+
+#[inline]
+fn write_entry(shmem: usize, i: usize, words: [usize; ENTRY_WORDS]) {
+    let entry = (shmem as *mut usize).wrapping_add(i * ENTRY_WORDS);
+    for (word, value) in words.into_iter().enumerate() {
+        unsafe { entry.add(word).write_volatile(value) };
+    }
+}
+
+/// Gets the trigger indices selected by `triggers`, checking they are all installed.
+fn selected_triggers(
+    dbtr: &DbtrState,
+    triggers: TriggerMask,
+) -> Result<impl Iterator<Item = usize> + use<>, SbiRet> {
+    let (mask, base) = triggers.into_inner();
+    let selected = move || {
+        (0..usize::BITS as usize)
+            .filter(move |bit| mask & (1 << bit) != 0)
+            .map(move |bit| base.checked_add(bit))
+    };
+    let installed = |idx: Option<usize>| {
+        idx.is_some_and(|idx| idx < trig_max() && dbtr.triggers[idx].is_mapped())
+    };
+    if !selected().all(installed) {
+        return Err(SbiRet::invalid_param());
+    }
+    Ok(selected().flatten())
+}
+
+/// Implementation of SBI Debug Triggers extension.
+pub(crate) struct SbiDbtr;
+
+impl rustsbi::Dbtr for SbiDbtr {
+    fn num_triggers(&self, trig_tdata1: usize) -> SbiRet {
+        if trig_tdata1 == 0 {
+            return SbiRet::success(trig_max());
+        }
+        let ty = tdata1_type(trig_tdata1);
+        let count = (0..trig_max())
+            .filter(|idx| hart_trigger_types(current_hartid(), *idx) & (1 << ty) != 0)
+            .count();
+        SbiRet::success(count)
+    }
+
+    fn set_shmem(&self, shmem: SharedPtr<u8>, flags: usize) -> SbiRet {
+        if flags != 0 {
+            return SbiRet::invalid_param();
+        }
+        let dbtr = local_dbtr();
+        let (lo, hi) = (shmem.phys_addr_lo(), shmem.phys_addr_hi());
+        if lo == usize::MAX && hi == usize::MAX {
+            dbtr.shmem = None;
+            return SbiRet::success(0);
+        }
+        if !lo.is_multiple_of(size_of::<usize>()) {
+            return SbiRet::invalid_param();
+        }
+        let size = trig_max() * ENTRY_WORDS * size_of::<usize>();
+        if hi != 0 || !unsafe { PLATFORM.shmem_accessible(lo, size) } {
+            return SbiRet::invalid_address();
+        }
+        dbtr.shmem = Some(lo);
+        SbiRet::success(0)
+    }
+
+    fn read_triggers(&self, trig_idx_base: usize, trig_count: usize) -> SbiRet {
+        let dbtr = local_dbtr();
+        let Some(shmem) = dbtr.shmem else {
+            return SbiRet::no_shmem();
+        };
+        if !trig_idx_base
+            .checked_add(trig_count)
+            .is_some_and(|end| trig_idx_base < trig_max() && end <= trig_max())
+        {
+            return SbiRet::bad_range();
+        }
+        for i in 0..trig_count {
+            let idx = trig_idx_base + i;
+            let trigger = &dbtr.triggers[idx];
+            let [tdata1, tdata2, tdata3] = if trigger.is_mapped() {
+                read_hw_trigger(idx)
+            } else {
+                [0; 3]
+            };
+            write_entry(shmem, i, [trigger.state, tdata1, tdata2, tdata3]);
+        }
+        SbiRet::success(0)
+    }
+
+    fn install_triggers(&self, trig_count: usize) -> SbiRet {
+        let dbtr = local_dbtr();
+        let Some(shmem) = dbtr.shmem else {
+            return SbiRet::no_shmem();
+        };
+        if trig_count > trig_max() {
+            return SbiRet::bad_range();
+        }
+        let hart_id = current_hartid();
+
+        // Check all configurations and assign hardware triggers before installing any.
+        let mut configs = [[0; 3]; TRIGGER_MAX];
+        let mut assigned = [0; TRIGGER_MAX];
+        let mut used: u32 = dbtr
+            .triggers
+            .iter()
+            .enumerate()
+            .filter(|(_, trigger)| trigger.is_mapped())
+            .fold(0, |used, (idx, _)| used | 1 << idx);
+        let mut i = 0;
+        while i < trig_count {
+            // A chain is a run of configurations with `chain` set, plus the one ending it.
+            let mut end = i;
+            loop {
+                let [_, tdata1, tdata2, tdata3] = read_entry(shmem, end);
+                match sanitize_tdata1(tdata1) {
+                    Ok(tdata1) => configs[end] = [tdata1, tdata2, tdata3],
+                    Err(err) => return SbiRet { value: end, ..err },
+                }
+                end += 1;
+                if tdata1 & MCONTROL_CHAIN == 0 {
+                    break;
+                }
+                if end == trig_count {
+                    return SbiRet {
+                        value: end - 1,
+                        ..SbiRet::invalid_param()
+                    };
+                }
+            }
+            let len = end - i;
+            let fits = |start: usize| {
+                (0..len).all(|k| {
+                    let idx = start + k;
+                    used & (1 << idx) == 0
+                        && hart_trigger_types(hart_id, idx) & (1 << tdata1_type(configs[i + k][0]))
+                            != 0
+                })
+            };
+            let Some(start) = (0..=trig_max().saturating_sub(len)).find(|start| fits(*start))
+            else {
+                return SbiRet {
+                    value: i,
+                    ..SbiRet::failed()
+                };
+            };
+            for k in 0..len {
+                assigned[i + k] = start + k;
+                used |= 1 << (start + k);
+            }
+            i = end;
+        }
+
+        for i in 0..trig_count {
+            let idx = assigned[i];
+            let accepted = write_hw_trigger(idx, configs[i]);
+            if tdata1_type(accepted) != tdata1_type(configs[i][0]) {
+                // Roll back triggers installed by this call.
+                for &idx in &assigned[..=i] {
+                    clear_hw_trigger(idx);
+                    dbtr.triggers[idx] = Trigger::new();
+                }
+                return SbiRet {
+                    value: i,
+                    ..SbiRet::not_supported()
+                };
+            }
+            dbtr.triggers[idx].state = mapped_state(idx, configs[i][0]);
+        }
+        for (i, &idx) in assigned[..trig_count].iter().enumerate() {
+            let [_, tdata1, tdata2, tdata3] = read_entry(shmem, i);
+            write_entry(shmem, i, [idx, tdata1, tdata2, tdata3]);
+        }
+        SbiRet::success(0)
+    }
+
+    fn update_triggers(&self, trig_count: usize) -> SbiRet {
+        let dbtr = local_dbtr();
+        let Some(shmem) = dbtr.shmem else {
+            return SbiRet::no_shmem();
+        };
+        if trig_count > trig_max() {
+            return SbiRet::bad_range();
+        }
+
+        // Check all configurations before updating any.
+        let mut configs = [[0; 3]; TRIGGER_MAX];
+        let mut indices = [0; TRIGGER_MAX];
+        for i in 0..trig_count {
+            let [idx, tdata1, tdata2, tdata3] = read_entry(shmem, i);
+            if idx >= trig_max() {
+                return SbiRet {
+                    value: i,
+                    ..SbiRet::invalid_param()
+                };
+            }
+            if !dbtr.triggers[idx].is_mapped() {
+                return SbiRet {
+                    value: i,
+                    ..SbiRet::failed()
+                };
+            }
+            let tdata1 = match sanitize_tdata1(tdata1) {
+                Ok(tdata1) => tdata1,
+                Err(err) => return SbiRet { value: i, ..err },
+            };
+            if hart_trigger_types(current_hartid(), idx) & (1 << tdata1_type(tdata1)) == 0 {
+                return SbiRet {
+                    value: i,
+                    ..SbiRet::not_supported()
+                };
+            }
+            configs[i] = [tdata1, tdata2, tdata3];
+            indices[i] = idx;
+        }
+
+        for i in 0..trig_count {
+            let idx = indices[i];
+            write_hw_trigger(idx, configs[i]);
+            dbtr.triggers[idx].state = mapped_state(idx, configs[i][0]);
+        }
+        SbiRet::success(0)
+    }
+
+    fn uninstall_triggers(&self, triggers: TriggerMask) -> SbiRet {
+        let dbtr = local_dbtr();
+        let selected = match selected_triggers(dbtr, triggers) {
+            Ok(selected) => selected,
+            Err(err) => return err,
+        };
+        for idx in selected {
+            clear_hw_trigger(idx);
+            dbtr.triggers[idx] = Trigger::new();
+        }
+        SbiRet::success(0)
+    }
+
+    fn enable_triggers(&self, triggers: TriggerMask) -> SbiRet {
+        let dbtr = local_dbtr();
+        let selected = match selected_triggers(dbtr, triggers) {
+            Ok(selected) => selected,
+            Err(err) => return err,
+        };
+        for idx in selected {
+            let [tdata1, tdata2, tdata3] = read_hw_trigger(idx);
+            let tdata1 = tdata1 | priv_tdata1(tdata1, dbtr.triggers[idx].state);
+            write_hw_trigger(idx, [tdata1, tdata2, tdata3]);
+        }
+        SbiRet::success(0)
+    }
+
+    fn disable_triggers(&self, triggers: TriggerMask) -> SbiRet {
+        let dbtr = local_dbtr();
+        let selected = match selected_triggers(dbtr, triggers) {
+            Ok(selected) => selected,
+            Err(err) => return err,
+        };
+        for idx in selected {
+            let [tdata1, tdata2, tdata3] = read_hw_trigger(idx);
+            write_hw_trigger(idx, [tdata1 & !priv_mask(tdata1), tdata2, tdata3]);
+        }
+        SbiRet::success(0)
+    }
+}
@@ -9,11 +9,16 @@ use crate::sbi::trap_stack::{hart_context, hart_context_mut};
 
 use super::early_trap::csr_swap;
 
+/// Maximum number of debug triggers managed per hart.
+pub const TRIGGER_MAX: usize = 32;
+
 pub struct HartFeatures {
     extensions: [bool; Extension::COUNT],
     privileged_version: PrivilegedVersion,
     mhpm_mask: u32,
     mhpm_bits: u32,
+    trigger_count: usize,
+    trigger_types: [u16; TRIGGER_MAX],
 }
 
 impl HartFeatures {
@@ -34,15 +39,23 @@ pub enum PrivilegedVersion {
 pub enum Extension {
     Sstc = 0,
     Hypervisor = 1,
+    Sscofpmf = 2,
+    Svadu = 3,
+    Ssdbltrp = 4,
+    Smnpm = 5,
 }
 
 impl Extension {
-    pub const COUNT: usize = 2;
+    pub const COUNT: usize = 6;
 
     pub const fn as_str(&self) -> &'static str {
         match self {
             Self::Sstc => "sstc",
             Self::Hypervisor => "h",
+            Self::Sscofpmf => "sscofpmf",
+            Self::Svadu => "svadu",
+            Self::Ssdbltrp => "ssdbltrp",
+            Self::Smnpm => "smnpm",
         }
     }
 
@@ -52,7 +65,15 @@ impl Extension {
     }
 
     pub fn iter() -> impl Iterator<Item = Self> {
-        [Self::Sstc, Self::Hypervisor].into_iter()
+        [
+            Self::Sstc,
+            Self::Hypervisor,
+            Self::Sscofpmf,
+            Self::Svadu,
+            Self::Ssdbltrp,
+            Self::Smnpm,
+        ]
+        .into_iter()
     }
 }
 
@@ -74,6 +95,18 @@ pub fn hart_mhpm_mask(hart_id: usize) -> u32 {
     hart_context(hart_id).features.mhpm_mask
 }
 
+/// Gets the number of debug triggers for the given hart.
+#[inline]
+pub fn hart_trigger_count(hart_id: usize) -> usize {
+    hart_context(hart_id).features.trigger_count
+}
+
+/// Gets the bitmask of `tdata1` types supported by a debug trigger of the given hart.
+#[inline]
+pub fn hart_trigger_types(hart_id: usize, trig_idx: usize) -> u16 {
+    hart_context(hart_id).features.trigger_types[trig_idx]
+}
+
 /// Detects RISC-V extensions from the device tree for all harts.
 #[cfg(not(feature = "nemu"))]
 pub fn extension_detection(cpus: &NodeSeq) {
@@ -82,25 +115,35 @@ pub fn extension_detection(cpus: &NodeSeq) {
     for cpu_iter in cpus.iter() {
         let cpu_data = cpu_iter.deserialize::<Cpu>();
         let hart_id = cpu_data.reg.iter().next().unwrap().0.start;
-        let mut extensions = [false; Extension::COUNT];
 
         for ext in Extension::iter() {
-            let ext_index = ext.index();
-            let ext_name = ext.as_str();
-
-            let dt_supported = check_extension_in_device_tree(ext_name, &cpu_data);
-            extensions[ext_index] = match ext {
-                Extension::Hypervisor if hart_id == current_hartid() => {
-                    misa::read().unwrap().has_extension('H')
-                }
-                _ => dt_supported,
-            };
+            let dt_supported = check_extension_in_device_tree(ext.as_str(), &cpu_data);
+            set_extension(hart_id, ext, dt_supported);
         }
+    }
+}
 
-        hart_context_mut(hart_id).features.extensions = extensions;
+/// Detects RISC-V extensions from an ISA string shared by all `harts`.
+#[cfg(feature = "board")]
+pub fn extension_detection_from_isa(harts: impl Iterator<Item = usize>, isa: &str) {
+    for hart_id in harts {
+        for ext in Extension::iter() {
+            set_extension(hart_id, ext, isa_has_extension(isa, ext.as_str()));
+        }
     }
 }
 
+fn set_extension(hart_id: usize, ext: Extension, supported: bool) {
+    hart_context_mut(hart_id).features.extensions[ext.index()] = match ext {
+        Extension::Hypervisor if hart_id == current_hartid() => {
+            misa::read().unwrap().has_extension('H')
+        }
+        // Sscofpmf is probed by each hart in `hart_features_detection`.
+        Extension::Sscofpmf => return,
+        _ => supported,
+    };
+}
+
 fn check_extension_in_device_tree(ext: &str, cpu: &crate::devicetree::Cpu) -> bool {
     // Check isa-extensions first (preferred, list of strings)
     if let Some(isa_exts) = &cpu.isa_extensions {
@@ -112,13 +155,15 @@ fn check_extension_in_device_tree(ext: &str, cpu: &crate::devicetree::Cpu) -> bo
         .iter()
         .next()
         .and_then(|isa| isa.iter().next())
-        .map(|isa| {
-            isa.split('_')
-                .any(|part| part == ext || (ext.len() == 1 && part.contains(ext)))
-        })
+        .map(|isa| isa_has_extension(isa, ext))
         .unwrap_or(false)
 }
 
+fn isa_has_extension(isa: &str, ext: &str) -> bool {
+    isa.split('_')
+        .any(|part| part == ext || (ext.len() == 1 && part.contains(ext)))
+}
+
 fn privileged_version_detection() {
     let mut current_priv_ver = PrivilegedVersion::Unknown;
     {
@@ -171,9 +216,62 @@ fn mhpm_detection() {
     hart_context_mut(current_hartid()).features.mhpm_bits = 64;
 }
 
+fn sscofpmf_detection() {
+    // `scountovf` is only implemented with Sscofpmf.
+    let has_sscofpmf = has_csr!(CSR_SCOUNTOVF);
+    hart_context_mut(current_hartid()).features.extensions[Extension::Sscofpmf.index()] =
+        has_sscofpmf;
+}
+
+fn trigger_detection() {
+    let mut trap_info: TrapInfo = TrapInfo::default();
+    let mut trigger_count = 0;
+    let mut trigger_types = [0u16; TRIGGER_MAX];
+
+    unsafe {
+        while trigger_count < TRIGGER_MAX {
+            // Triggers are numbered contiguously, stop at the first index `tselect` can't hold.
+            csr_write_allow::<CSR_TSELECT>(&mut trap_info, trigger_count);
+            if trap_info.mcause != usize::MAX
+                || csr_read_allow::<CSR_TSELECT>(&mut trap_info) != trigger_count
+                || trap_info.mcause != usize::MAX
+            {
+                break;
+            }
+            let tinfo = csr_read_allow::<CSR_TINFO>(&mut trap_info);
+            let types = if trap_info.mcause == usize::MAX {
+                tinfo & 0xffff
+            } else {
+                // Without `tinfo`, the trigger only supports the type currently in `tdata1`.
+                let tdata1 = csr_read_allow::<CSR_TDATA1>(&mut trap_info);
+                if trap_info.mcause != usize::MAX {
+                    break;
+                }
+                1 << (tdata1 >> (usize::BITS - 4))
+            };
+            // Type 0 means there is no trigger, type 15 means it is unavailable.
+            let types = types & !(1 << 0 | 1 << 15);
+            if types == 0 {
+                break;
+            }
+            trigger_types[trigger_count] = types as u16;
+            trigger_count += 1;
+        }
+        if trigger_count > 0 {
+            csr_write_allow::<CSR_TSELECT>(&mut trap_info, 0);
+        }
+    }
+
+    let features = &mut hart_context_mut(current_hartid()).features;
+    features.trigger_count = trigger_count;
+    features.trigger_types = trigger_types;
+}
+
 pub fn hart_features_detection() {
     privileged_version_detection();
     mhpm_detection();
+    sscofpmf_detection();
+    trigger_detection();
 }
 
 #[cfg(feature = "nemu")]
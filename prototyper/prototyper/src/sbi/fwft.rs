@@ -0,0 +1,194 @@
+use riscv::register::medeleg;
+use rustsbi::SbiRet;
+use sbi_spec::fwft::{feature_type, flags::SetFlags};
+
+use crate::riscv::csr::menvcfg;
+use crate::riscv::current_hartid;
+use crate::sbi::features::{
+    Extension, PrivilegedVersion, hart_extension_probe, hart_privileged_version,
+};
+use crate::sbi::trap_stack::hart_context_mut;
+
+/// Firmware features handled by the prototyper.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum Feature {
+    MisalignedExcDeleg,
+    LandingPad,
+    ShadowStack,
+    DoubleTrap,
+    PteAdHwUpdating,
+    PointerMaskingPmlen,
+}
+
+impl Feature {
+    /// Gets the feature from its ID, or `None` if the ID is reserved or platform-specific.
+    const fn from_id(feature: u32) -> Option<Self> {
+        match feature as usize {
+            feature_type::MISALIGNED_EXC_DELEG => Some(Self::MisalignedExcDeleg),
+            feature_type::LANDING_PAD => Some(Self::LandingPad),
+            feature_type::SHADOW_STACK => Some(Self::ShadowStack),
+            feature_type::DOUBLE_TRAP => Some(Self::DoubleTrap),
+            feature_type::PTE_AD_HW_UPDATING => Some(Self::PteAdHwUpdating),
+            feature_type::POINTER_MASKING_PMLEN => Some(Self::PointerMaskingPmlen),
+            _ => None,
+        }
+    }
+
+    /// Checks whether the current hart supports this feature.
+    fn is_supported(&self) -> bool {
+        let hart_id = current_hartid();
+        let has_menvcfg = hart_privileged_version(hart_id) >= PrivilegedVersion::Version1_12;
+        match self {
+            Self::MisalignedExcDeleg => true,
+            // Zicfilp and Zicfiss are not managed by the prototyper yet.
+            Self::LandingPad | Self::ShadowStack => false,
+            Self::DoubleTrap => has_menvcfg && hart_extension_probe(hart_id, Extension::Ssdbltrp),
+            Self::PteAdHwUpdating => has_menvcfg && hart_extension_probe(hart_id, Extension::Svadu),
+            Self::PointerMaskingPmlen => {
+                cfg!(target_pointer_width = "64")
+                    && has_menvcfg
+                    && hart_extension_probe(hart_id, Extension::Smnpm)
+            }
+        }
+    }
+
+    #[inline]
+    const fn lock_bit(&self) -> u32 {
+        1 << *self as u32
+    }
+
+    fn set(&self, value: usize) -> SbiRet {
+        match self {
+            Self::MisalignedExcDeleg => match value {
+                0 => unsafe {
+                    medeleg::clear_load_misaligned();
+                    medeleg::clear_store_misaligned();
+                },
+                1 => unsafe {
+                    medeleg::set_load_misaligned();
+                    medeleg::set_store_misaligned();
+                },
+                _ => return SbiRet::invalid_param(),
+            },
+            Self::DoubleTrap => return set_menvcfg_flag(menvcfg::DTE, value),
+            Self::PteAdHwUpdating => return set_menvcfg_flag(menvcfg::ADUE, value),
+            Self::PointerMaskingPmlen => return set_pmlen(value),
+            Self::LandingPad | Self::ShadowStack => return SbiRet::not_supported(),
+        }
+        SbiRet::success(0)
+    }
+
+    fn get(&self) -> usize {
+        match self {
+            Self::MisalignedExcDeleg => {
+                let medeleg = medeleg::read();
+                (medeleg.load_misaligned() && medeleg.store_misaligned()) as usize
+            }
+            Self::DoubleTrap => (menvcfg::read() & menvcfg::DTE != 0) as usize,
+            Self::PteAdHwUpdating => (menvcfg::read() & menvcfg::ADUE != 0) as usize,
+            Self::PointerMaskingPmlen => pmm_to_pmlen(menvcfg::read() & menvcfg::PMM),
+            Self::LandingPad | Self::ShadowStack => 0,
+        }
+    }
+}
+
+/// Sets or clears a single-bit feature in `menvcfg`.
+fn set_menvcfg_flag(bit: usize, value: usize) -> SbiRet {
+    match value {
+        0 => menvcfg::clear_bits(bit),
+        1 => menvcfg::set_bits(bit),
+        _ => return SbiRet::invalid_param(),
+    }
+    SbiRet::success(0)
+}
+
+/// `menvcfg.PMM` encodings and the pointer masking lengths they select, smallest first.
+const PMM_PMLEN: [(usize, usize); 3] = [(0, 0), (2 << 32, 7), (3 << 32, 16)];
+
+fn pmm_to_pmlen(pmm: usize) -> usize {
+    PMM_PMLEN
+        .iter()
+        .find(|(encoding, _)| *encoding == pmm)
+        .map_or(0, |(_, pmlen)| *pmlen)
+}
+
+/// Selects the smallest supported PMLEN no less than the requested one.
+///
+/// `menvcfg.PMM` is WARL, so each candidate is written and read back to probe whether
+/// the hart implements it.
+fn set_pmlen(value: usize) -> SbiRet {
+    let old = menvcfg::read() & menvcfg::PMM;
+    for (encoding, _) in PMM_PMLEN.iter().filter(|(_, pmlen)| *pmlen >= value) {
+        menvcfg::clear_bits(menvcfg::PMM);
+        menvcfg::set_bits(*encoding);
+        if menvcfg::read() & menvcfg::PMM == *encoding {
+            return SbiRet::success(0);
+        }
+    }
+    menvcfg::clear_bits(menvcfg::PMM);
+    menvcfg::set_bits(old);
+    SbiRet::invalid_param()
+}
+
+/// Per-hart firmware feature state.
+pub(crate) struct FwftState {
+    /// Features locked on this hart, one bit per `Feature`.
+    locked: u32,
+}
+
+impl FwftState {
+    pub const fn new() -> Self {
+        Self { locked: 0 }
+    }
+
+    /// Unlocks all features and restores their default values on the current hart.
+    pub fn reset(&mut self) {
+        self.locked = 0;
+        for feature in [
+            Feature::MisalignedExcDeleg,
+            Feature::DoubleTrap,
+            Feature::PteAdHwUpdating,
+            Feature::PointerMaskingPmlen,
+        ] {
+            if feature.is_supported() {
+                feature.set(0);
+            }
+        }
+    }
+}
+
+/// Implementation of SBI Firmware Features extension.
+pub(crate) struct SbiFwft;
+
+impl rustsbi::Fwft for SbiFwft {
+    fn set(&self, feature: u32, value: usize, flags: usize) -> SbiRet {
+        let Some(flags) = SetFlags::from_bits(flags) else {
+            return SbiRet::invalid_param();
+        };
+        let Some(feature) = Feature::from_id(feature) else {
+            return SbiRet::denied();
+        };
+        if !feature.is_supported() {
+            return SbiRet::not_supported();
+        }
+        let state = &mut hart_context_mut(current_hartid()).fwft;
+        if state.locked & feature.lock_bit() != 0 {
+            return SbiRet::denied_locked();
+        }
+        let ret = feature.set(value);
+        if ret.is_ok() && flags.contains(SetFlags::LOCK) {
+            state.locked |= feature.lock_bit();
+        }
+        ret
+    }
+
+    fn get(&self, feature: u32) -> SbiRet {
+        let Some(feature) = Feature::from_id(feature) else {
+            return SbiRet::denied();
+        };
+        if !feature.is_supported() {
+            return SbiRet::not_supported();
+        }
+        SbiRet::success(feature.get())
+    }
+}
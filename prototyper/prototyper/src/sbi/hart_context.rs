@@ -8,7 +8,10 @@ use core::sync::atomic::Ordering;
 use fast_trap::FlowContext;
 use riscv::register::mstatus;
 
+use super::dbtr::DbtrState;
+use super::fwft::FwftState;
 use super::pmu::PmuState;
+use super::sse::SseState;
 
 /// Context for managing hart (hardware thread) state and operations.
 pub(crate) struct HartContext {
@@ -24,6 +27,12 @@ pub(crate) struct HartContext {
     pub features: HartFeatures,
     /// PMU State
     pub pmu_state: PmuState,
+    /// Supervisor software events state.
+    pub sse: SseState,
+    /// Firmware features state.
+    pub fwft: FwftState,
+    /// Debug triggers state.
+    pub dbtr: DbtrState,
 }
 
 impl HartContext {
@@ -33,6 +42,9 @@ impl HartContext {
         self.hsm = HsmCell::new();
         self.rfence = RFenceCell::new();
         self.pmu_state = PmuState::new();
+        self.sse = SseState::new();
+        self.fwft = FwftState::new();
+        self.dbtr = DbtrState::new();
     }
 
     /// Get a non-null pointer to the trap context.
@@ -46,6 +58,9 @@ impl HartContext {
         self.ipi_reset();
         self.rfence_reset();
         self.pmu_state_reset();
+        self.sse_reset();
+        self.fwft.reset();
+        self.dbtr.reset();
     }
 
     #[inline]
@@ -58,6 +73,11 @@ impl HartContext {
         self.ipi_type.store(0, Ordering::Relaxed);
     }
 
+    #[inline]
+    fn sse_reset(&mut self) {
+        self.sse = SseState::new();
+    }
+
     #[inline]
     fn pmu_state_reset(&mut self) {
         // stop all hardware pmu event
@@ -1,11 +1,13 @@
+use alloc::boxed::Box;
 use core::{
     cell::UnsafeCell,
     hint::spin_loop,
-    sync::atomic::{AtomicUsize, Ordering},
+    sync::atomic::{AtomicBool, AtomicUsize, Ordering},
 };
 use riscv::register::mstatus::MPP;
 use rustsbi::{SbiRet, spec::hsm::hart_state};
 
+use crate::cfg::NUM_HART_MAX;
 use crate::platform::PLATFORM;
 use crate::riscv::current_hartid;
 use crate::sbi::hart_context::NextStage;
@@ -17,6 +19,9 @@ use super::{trap::boot::boot, trap_stack::hart_context};
 /// Special state indicating a hart is in the process of starting.
 const HART_STATE_START_PENDING_EXT: usize = usize::MAX;
 
+/// Harts which may be powered off by the platform, and then enter the firmware again from reset.
+static POWERING_OFF: [AtomicBool; NUM_HART_MAX] = [const { AtomicBool::new(false) }; NUM_HART_MAX];
+
 type HsmState = AtomicUsize;
 
 /// Cell for managing hart state and shared data between harts.
@@ -103,6 +108,32 @@ impl<T> LocalHsmCell<'_, T> {
     pub fn resume(&self) {
         self.0.status.store(hart_state::STARTED, Ordering::Relaxed)
     }
+
+    /// Transitions hart to SUSPENDED state, keeping the data to resume with after a non-retentive
+    /// suspend.
+    #[inline]
+    pub fn suspend_non_retentive(&self, t: T) {
+        unsafe { *self.0.inner.get() = Some(t) };
+        self.0
+            .status
+            .store(hart_state::SUSPENDED, Ordering::Release)
+    }
+
+    /// Transitions hart from SUSPENDED to START_PENDING state after a non-retentive suspend.
+    ///
+    /// Returns false if hart was not in SUSPENDED state.
+    #[inline]
+    pub fn wake_non_retentive(&self) -> bool {
+        self.0
+            .status
+            .compare_exchange(
+                hart_state::SUSPENDED,
+                hart_state::START_PENDING,
+                Ordering::AcqRel,
+                Ordering::Relaxed,
+            )
+            .is_ok()
+    }
 }
 
 impl<T: core::fmt::Debug> RemoteHsmCell<'_, T> {
@@ -199,8 +230,46 @@ pub(crate) fn remote_hsm(hart_id: usize) -> Option<RemoteHsmCell<'static, NextSt
     }
 }
 
+/// Checks whether the current hart has been powered off, clearing the mark.
+///
+/// Harts powered off enter the firmware again from reset once woken up or started.
+pub(crate) fn take_powered_off() -> bool {
+    POWERING_OFF[current_hartid()].swap(false, Ordering::AcqRel)
+}
+
+/// Runs `f` which may power off the current hart.
+fn power_off_with(f: impl FnOnce()) {
+    POWERING_OFF[current_hartid()].store(true, Ordering::Release);
+    f();
+    POWERING_OFF[current_hartid()].store(false, Ordering::Release);
+}
+
+/// Platform device controlling hart power states.
+pub trait HartPowerDevice {
+    /// Checks if the platform implements the platform-specific `suspend_type`.
+    fn supports(&self, suspend_type: u32) -> bool;
+
+    /// Enters the platform-specific `suspend_type` on the current hart, returning once woken up.
+    ///
+    /// The hart may also be powered off on non-retentive types, entering the firmware again
+    /// from reset.
+    fn suspend(&self, suspend_type: u32);
+
+    /// Powers off the current hart after it has been stopped.
+    ///
+    /// Returns if the hart is kept powered, it then waits for an IPI to start.
+    fn stop(&self) {}
+
+    /// Powers on a hart before it is started.
+    ///
+    /// The hart may not have been powered off by `stop` yet.
+    fn start(&self, _hartid: usize) {}
+}
+
 /// Implementation of SBI HSM (Hart State Management) extension.
-pub(crate) struct SbiHsm;
+pub(crate) struct SbiHsm {
+    power: Box<dyn HartPowerDevice>,
+}
 
 impl rustsbi::Hsm for SbiHsm {
     /// Starts execution on a stopped hart.
@@ -212,6 +281,7 @@ impl rustsbi::Hsm for SbiHsm {
                     opaque,
                     next_mode: MPP::Supervisor,
                 }) {
+                    self.power.start(hartid);
                     unsafe {
                         PLATFORM.sbi.ipi.as_ref().unwrap().set_msip(hartid);
                     }
@@ -227,7 +297,10 @@ impl rustsbi::Hsm for SbiHsm {
     /// Stops execution on the current hart.
     #[inline]
     fn hart_stop(&self) -> SbiRet {
+        // Firmware features return to their defaults when the hart is started again.
+        hart_context_mut(current_hartid()).fwft.reset();
         local_hsm().stop();
+        power_off_with(|| self.power.stop());
         unsafe {
             riscv::register::mie::clear_msoft();
         }
@@ -246,11 +319,21 @@ impl rustsbi::Hsm for SbiHsm {
 
     /// Suspends execution on the current hart.
     fn hart_suspend(&self, suspend_type: u32, resume_addr: usize, opaque: usize) -> SbiRet {
-        use rustsbi::spec::hsm::suspend_type::{NON_RETENTIVE, RETENTIVE};
+        use rustsbi::spec::hsm::suspend_type::NON_RETENTIVE;
 
-        if !matches!(suspend_type, NON_RETENTIVE | RETENTIVE) {
+        // Non-retentive types have the top bit set, platform-specific types follow the defaults.
+        let retentive = suspend_type & NON_RETENTIVE == 0;
+        let platform_specific = match suspend_type & !NON_RETENTIVE {
+            0 => false,
+            0x1000_0000..=0x7FFF_FFFF => true,
+            _ => return SbiRet::invalid_param(),
+        };
+        if platform_specific && !self.power.supports(suspend_type) {
             return SbiRet::invalid_param();
         }
+        if !retentive && !unsafe { PLATFORM.shmem_accessible(resume_addr, 1) } {
+            return SbiRet::invalid_address();
+        }
 
         crate::sbi::trap::handler::msoft_ipi_handler();
         unsafe {
@@ -264,24 +347,41 @@ impl rustsbi::Hsm for SbiHsm {
         unsafe {
             riscv::register::mie::set_msoft();
         }
-        local_hsm().suspend();
-        riscv::asm::wfi();
+        if retentive {
+            local_hsm().suspend();
+        } else {
+            local_hsm().suspend_non_retentive(NextStage {
+                start_addr: resume_addr,
+                opaque,
+                next_mode: MPP::Supervisor,
+            });
+        }
+        match (platform_specific, retentive) {
+            (true, true) => self.power.suspend(suspend_type),
+            (true, false) => power_off_with(|| self.power.suspend(suspend_type)),
+            (false, _) => riscv::asm::wfi(),
+        }
         crate::sbi::trap::handler::msoft_ipi_handler();
 
-        match suspend_type {
-            RETENTIVE => {
-                local_hsm().resume();
-                return SbiRet::success(0);
-            }
-            NON_RETENTIVE => return self.hart_resume(current_hartid(), resume_addr, opaque),
-            _ => return SbiRet::invalid_param(),
+        if retentive {
+            local_hsm().resume();
+            return SbiRet::success(0);
         }
+        local_hsm().wake_non_retentive();
+        // reset the hart local context to prevent the hart context from being polluted
+        hart_context_mut(current_hartid()).reset();
+        // boot resume hart from resume addr
+        unsafe { boot() }
     }
 }
 
 impl SbiHsm {
+    pub fn new(power: Box<dyn HartPowerDevice>) -> Self {
+        Self { power }
+    }
+
     // non retentive resume
-    fn hart_resume(&self, hartid: usize, resume_addr: usize, opaque: usize) -> SbiRet {
+    pub(crate) fn hart_resume(&self, hartid: usize, resume_addr: usize, opaque: usize) -> SbiRet {
         match remote_hsm(hartid) {
             Some(remote) => {
                 if remote.resume(NextStage {
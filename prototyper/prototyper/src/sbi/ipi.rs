@@ -1,4 +1,4 @@
-use super::pmu::pmu_firmware_counter_increment;
+use super::pmu::{pmu_firmware_counter_increment, pmu_remote_ipi_received};
 use crate::platform::PLATFORM;
 use crate::riscv::csr::stimecmp;
 use crate::riscv::current_hartid;
@@ -16,6 +16,8 @@ use spin::Mutex;
 pub(crate) const IPI_TYPE_SSOFT: u8 = 1 << 0;
 /// IPI type for memory fence operations.
 pub(crate) const IPI_TYPE_FENCE: u8 = 1 << 1;
+/// IPI type for supervisor software event delivery.
+pub(crate) const IPI_TYPE_SSE: u8 = 1 << 2;
 
 /// Trait defining interface for inter-processor interrupt device
 #[allow(unused)]
@@ -28,12 +30,30 @@ pub trait IpiDevice {
     fn read_mtimecmp(&self, hart_idx: usize) -> u64;
     /// Write machine timer compare value for given hart.
     fn write_mtimecmp(&self, hart_idx: usize, val: u64);
+    /// Whether the device has a machine timer for given hart.
+    fn has_mtimer(&self, _hart_idx: usize) -> bool {
+        true
+    }
     /// Read machine software interrupt pending bit for given hart.
     fn read_msip(&self, hart_idx: usize) -> bool;
     /// Set machine software interrupt pending bit for given hart.
     fn set_msip(&self, hart_idx: usize);
     /// Clear machine software interrupt pending bit for given hart.
     fn clear_msip(&self, hart_idx: usize);
+    /// Set supervisor software interrupt pending bit for given hart.
+    ///
+    /// Returns `false` if the device can't raise supervisor software interrupts.
+    fn set_ssip(&self, _hart_idx: usize) -> bool {
+        false
+    }
+    /// Prepare the device for receiving IPIs on the current hart.
+    fn init_local(&self) {}
+    /// Claim the pending machine external interrupt on the current hart.
+    ///
+    /// Returns `true` if it is an IPI, for devices that deliver IPIs as external interrupts.
+    fn claim_ipi(&self) -> bool {
+        false
+    }
 }
 
 /// SBI IPI implementation.
@@ -44,7 +64,10 @@ pub struct SbiIpi {
     pub max_hart_id: usize,
 }
 
-impl rustsbi::Timer for SbiIpi {
+/// SBI Timer implementation, on Sstc or machine timers of the IPI device.
+pub struct SbiTimer;
+
+impl rustsbi::Timer for SbiTimer {
     /// Set timer value for current hart.
     #[inline]
     fn set_timer(&self, stime_value: u64) {
@@ -55,8 +78,8 @@ impl rustsbi::Timer for SbiIpi {
         // Set timer value based on extension support.
         if uses_sstc {
             stimecmp::set(stime_value);
-        } else {
-            self.write_mtimecmp(hart_id, stime_value);
+        } else if let Some(ipi) = unsafe { PLATFORM.sbi.ipi.as_ref() } {
+            ipi.write_mtimecmp(hart_id, stime_value);
             unsafe {
                 riscv::register::mip::clear_stimer();
             }
@@ -107,6 +130,11 @@ impl rustsbi::Ipi for SbiIpi {
                 continue;
             }
 
+            // Raise the interrupt directly if the device can, otherwise through machine mode.
+            if self.set_ssip(hart_id) {
+                pmu_remote_ipi_received(hart_id);
+                continue;
+            }
             if set_ipi_type(hart_id, IPI_TYPE_SSOFT) == 0 {
                 self.set_msip(hart_id);
             }
@@ -209,12 +237,24 @@ impl SbiIpi {
         self.ipi_dev.lock().set_msip(hart_idx);
     }
 
+    /// Set supervisor software interrupt pending for hart, if the device supports it.
+    #[inline]
+    pub fn set_ssip(&self, hart_idx: usize) -> bool {
+        self.ipi_dev.lock().set_ssip(hart_idx)
+    }
+
     /// Clear machine software interrupt pending for hart.
     #[inline]
     pub fn clear_msip(&self, hart_idx: usize) {
         self.ipi_dev.lock().clear_msip(hart_idx);
     }
 
+    /// Checks whether the hart has a machine timer, or Sstc to do without one.
+    #[inline]
+    pub fn timer_available(&self, hart_idx: usize) -> bool {
+        hart_extension_probe(hart_idx, Extension::Sstc) || self.ipi_dev.lock().has_mtimer(hart_idx)
+    }
+
     /// Write machine timer compare value for hart.
     #[inline]
     pub fn write_mtimecmp(&self, hart_idx: usize, val: u64) {
@@ -242,6 +282,23 @@ pub fn get_and_reset_ipi_type() -> u8 {
     hart_context(current_hartid()).ipi_type.swap(0, Relaxed)
 }
 
+/// Prepare the IPI device on current hart.
+#[inline]
+pub fn init_local() {
+    if let Some(ipi) = unsafe { PLATFORM.sbi.ipi.as_ref() } {
+        ipi.ipi_dev.lock().init_local();
+    }
+}
+
+/// Claim the pending machine external interrupt on current hart, returns whether it is an IPI.
+#[inline]
+pub fn claim_external_ipi() -> bool {
+    match unsafe { PLATFORM.sbi.ipi.as_ref() } {
+        Some(ipi) => ipi.ipi_dev.lock().claim_ipi(),
+        None => false,
+    }
+}
+
 /// Clear machine software interrupt pending for current hart.
 #[inline]
 pub fn clear_msip() {
@@ -1,16 +1,18 @@
 use rustsbi::RustSBI;
 
 pub mod console;
+pub mod dbtr;
+pub mod fwft;
 pub mod hsm;
 pub mod ipi;
 pub mod pmu;
 pub mod reset;
 pub mod rfence;
+pub mod sse;
 pub mod suspend;
 
 pub mod early_trap;
 pub mod features;
-pub mod fifo;
 pub mod hart_context;
 pub mod heap;
 pub mod logger;
@@ -18,11 +20,14 @@ pub mod trap;
 pub mod trap_stack;
 
 use console::SbiConsole;
+use dbtr::SbiDbtr;
+use fwft::SbiFwft;
 use hsm::SbiHsm;
-use ipi::SbiIpi;
+use ipi::{SbiIpi, SbiTimer};
 use pmu::SbiPmu;
 use reset::SbiReset;
 use rfence::SbiRFence;
+use sse::SbiSse;
 use suspend::SbiSuspend;
 
 #[derive(RustSBI, Default)]
@@ -31,8 +36,10 @@ use suspend::SbiSuspend;
 pub struct SBI {
     #[rustsbi(console)]
     pub console: Option<SbiConsole>,
-    #[rustsbi(ipi, timer)]
+    #[rustsbi(ipi)]
     pub ipi: Option<SbiIpi>,
+    #[rustsbi(timer)]
+    pub timer: Option<SbiTimer>,
     #[rustsbi(hsm)]
     pub hsm: Option<SbiHsm>,
     #[rustsbi(reset)]
@@ -43,6 +50,12 @@ pub struct SBI {
     pub pmu: Option<SbiPmu>,
     #[rustsbi(susp)]
     pub susp: Option<SbiSuspend>,
+    #[rustsbi(sse)]
+    pub sse: Option<SbiSse>,
+    #[rustsbi(fwft)]
+    pub fwft: Option<SbiFwft>,
+    #[rustsbi(dbtr)]
+    pub dbtr: Option<SbiDbtr>,
 }
 
 impl SBI {
@@ -50,11 +63,15 @@ impl SBI {
         SBI {
             console: None,
             ipi: None,
+            timer: None,
             hsm: None,
             reset: None,
             rfence: None,
             pmu: None,
             susp: None,
+            sse: None,
+            fwft: None,
+            dbtr: None,
         }
     }
 }
@@ -1,15 +1,20 @@
 use alloc::collections::BTreeMap;
 use alloc::vec::Vec;
+use core::sync::atomic::{AtomicUsize, Ordering};
 use riscv::register::*;
 use rustsbi::{Pmu, SbiRet};
 use sbi_spec::binary::SharedPtr;
 use sbi_spec::pmu::shmem_size::SIZE;
 use sbi_spec::pmu::*;
+use spin::Mutex;
 
+use crate::platform::PLATFORM;
 use crate::riscv::csr::*;
 use crate::{riscv::current_hartid, sbi::features::hart_mhpm_mask};
 
-use super::features::{PrivilegedVersion, hart_privileged_version};
+use super::features::{
+    Extension, PrivilegedVersion, hart_extension_probe, hart_privileged_version,
+};
 use super::trap_stack::{hart_context, hart_context_mut};
 
 /// Maximum number of hardware performance counters supported.
@@ -18,6 +23,59 @@ const PMU_HARDWARE_COUNTER_MAX: usize = 32;
 const PMU_FIRMWARE_COUNTER_MAX: usize = 16;
 /// Marker value for inactive/invalid event indices.
 const PMU_EVENT_IDX_INVALID: usize = usize::MAX;
+/// Overflow and mode inhibit bits of `mhpmevent` defined by Sscofpmf.
+const MHPMEVENT_OF: u64 = 1 << 63;
+const MHPMEVENT_MINH: u64 = 1 << 62;
+const MHPMEVENT_SINH: u64 = 1 << 61;
+const MHPMEVENT_UINH: u64 = 1 << 60;
+const MHPMEVENT_VSINH: u64 = 1 << 59;
+const MHPMEVENT_VUINH: u64 = 1 << 58;
+const MHPMEVENT_SSCOF_MASK: u64 = MHPMEVENT_OF
+    | MHPMEVENT_MINH
+    | MHPMEVENT_SINH
+    | MHPMEVENT_UINH
+    | MHPMEVENT_VSINH
+    | MHPMEVENT_VUINH;
+
+/// Layout of the PMU snapshot shared memory.
+#[repr(C)]
+struct SnapshotData {
+    /// Overflown counters relative to the `counter_idx_base` of the last stop request.
+    counter_overflow_bitmap: u64,
+    /// Counter values relative to the `counter_idx_base` of the last start or stop request.
+    counter_values: [u64; 64],
+}
+
+/// Platform specific firmware events defined by the prototyper, identified by `event_data`.
+pub mod platform_event {
+    /// Remote fence requests were promoted to a full flush, as their merged ranges exceeded
+    /// the TLB flush limit.
+    pub const RFENCE_FULL_FLUSH: u64 = 0x1;
+}
+
+/// Platform specific firmware events registered by platform code and drivers.
+static PLATFORM_EVENTS: Mutex<Vec<u64>> = Mutex::new(Vec::new());
+
+/// Registers a platform specific firmware event identified by `event_data`.
+///
+/// Supervisor software can then count the event by configuring a firmware counter
+/// with the `PLATFORM` event code and this `event_data`.
+pub fn register_platform_event(event_data: u64) {
+    let mut events = PLATFORM_EVENTS.lock();
+    if events.contains(&event_data) {
+        error!(
+            "Platform firmware event {:#x} is already registered",
+            event_data
+        );
+    } else {
+        events.push(event_data);
+    }
+}
+
+/// Checks whether a platform specific firmware event has been registered.
+fn platform_event_registered(event_data: u64) -> bool {
+    PLATFORM_EVENTS.lock().contains(&event_data)
+}
 
 /// PMU state tracking hardware and firmware performance counters
 #[repr(C)]
@@ -27,8 +85,14 @@ pub struct PmuState {
     fw_counter_state: usize,
     /// Values for firmware-managed counters
     fw_counter: [u64; PMU_FIRMWARE_COUNTER_MAX],
+    /// Event data of platform specific events monitored by firmware counters
+    fw_event_data: [u64; PMU_FIRMWARE_COUNTER_MAX],
     hw_counters_num: usize,
     total_counters_num: usize,
+    /// Physical address of the snapshot shared memory, if set.
+    snapshot: Option<usize>,
+    /// IPIs raised directly in supervisor mode by other harts, not yet added to counters.
+    ipi_received: AtomicUsize,
 }
 
 impl PmuState {
@@ -47,11 +111,46 @@ impl PmuState {
             active_event,
             fw_counter_state: 0,
             fw_counter: [0; PMU_FIRMWARE_COUNTER_MAX],
+            fw_event_data: [0; PMU_FIRMWARE_COUNTER_MAX],
             hw_counters_num,
             total_counters_num,
+            snapshot: None,
+            ipi_received: AtomicUsize::new(0),
+        }
+    }
+
+    /// Adds `count` occurrences of a firmware event to the started counters monitoring it.
+    fn add_fw_event(&mut self, firmware_event: usize, event_data: u64, count: u64) {
+        let counter_idx_start = self.hw_counters_num;
+        for counter_idx in counter_idx_start..counter_idx_start + PMU_FIRMWARE_COUNTER_MAX {
+            let fw_idx = counter_idx - counter_idx_start;
+            if self.active_event[counter_idx] == EventIdx::from_firmwarw_event(firmware_event).raw()
+                && (firmware_event != firmware_event::PLATFORM
+                    || self.fw_event_data[fw_idx] == event_data)
+                && self.is_firmware_event_start(counter_idx)
+            {
+                self.fw_counter[fw_idx] += count;
+            }
         }
     }
 
+    /// Adds IPIs received without trapping into the firmware to the counters.
+    ///
+    /// They are counted when the hart next starts, stops or reads its counters.
+    fn collect_ipi_received(&mut self) {
+        let count = self.ipi_received.swap(0, Ordering::Relaxed);
+        if count != 0 {
+            self.add_fw_event(firmware_event::IPI_RECEIVED, 0, count as u64);
+        }
+    }
+
+    /// Returns the snapshot shared memory of this hart, if set.
+    #[inline]
+    fn snapshot(&self) -> Option<&'static mut SnapshotData> {
+        self.snapshot
+            .map(|addr| unsafe { &mut *(addr as *mut SnapshotData) })
+    }
+
     /// Returns the number of hardware counters available.
     #[inline(always)]
     pub fn get_hw_counter_num(&self) -> usize {
@@ -244,6 +343,7 @@ impl Pmu for SbiPmu {
                     counter_idx_base,
                     counter_idx_mask,
                     event_idx,
+                    event_data,
                     pmu_state,
                 );
             } else {
@@ -252,6 +352,7 @@ impl Pmu for SbiPmu {
                     counter_idx_mask,
                     event_idx,
                     event_data,
+                    flags,
                     pmu_state,
                 );
             }
@@ -264,6 +365,10 @@ impl Pmu for SbiPmu {
                 }
             }
             pmu_state.active_event[counter_idx] = event_idx;
+            if is_firmware_event {
+                let fw_idx = counter_idx - pmu_state.hw_counters_num;
+                pmu_state.fw_event_data[fw_idx] = event_data;
+            }
         }
 
         if configure_counter(pmu_state, counter_idx, event, flags) {
@@ -289,6 +394,7 @@ impl Pmu for SbiPmu {
         };
 
         let pmu_state = &mut hart_context_mut(current_hartid()).pmu_state;
+        pmu_state.collect_ipi_received();
         let is_update_value = flags.contains(flags::CounterStartFlags::INIT_VALUE);
 
         if counter_idx_base >= pmu_state.total_counters_num
@@ -297,15 +403,28 @@ impl Pmu for SbiPmu {
             return SbiRet::invalid_param();
         }
 
-        if flags.contains(flags::CounterStartFlags::INIT_SNAPSHOT) {
-            return SbiRet::no_shmem();
-        }
+        let snapshot = if flags.contains(flags::CounterStartFlags::INIT_SNAPSHOT) {
+            match pmu_state.snapshot() {
+                Some(snapshot) => Some(snapshot),
+                None => return SbiRet::no_shmem(),
+            }
+        } else {
+            None
+        };
 
         for counter_idx in CounterMask::new(counter_idx_base, counter_idx_mask) {
             if counter_idx >= pmu_state.total_counters_num {
                 return SbiRet::invalid_param();
             }
 
+            // Initial values from the snapshot take precedence over `initial_value`.
+            let (initial_value, is_update_value) = match &snapshot {
+                Some(snapshot) => (
+                    snapshot.counter_values[counter_idx - counter_idx_base],
+                    true,
+                ),
+                None => (initial_value, is_update_value),
+            };
             let start_result = if counter_idx >= pmu_state.get_hw_counter_num() {
                 pmu_state.start_fw_counter(counter_idx, initial_value, is_update_value)
             } else {
@@ -339,6 +458,7 @@ impl Pmu for SbiPmu {
         };
 
         let pmu_state = &mut hart_context_mut(current_hartid()).pmu_state;
+        pmu_state.collect_ipi_received();
         let is_reset = flags.contains(flags::CounterStopFlags::RESET);
 
         if counter_idx_base >= pmu_state.total_counters_num
@@ -347,24 +467,44 @@ impl Pmu for SbiPmu {
             return SbiRet::invalid_param();
         }
 
-        if flags.contains(flags::CounterStopFlags::TAKE_SNAPSHOT) {
-            return SbiRet::no_shmem();
-        }
+        let mut snapshot = if flags.contains(flags::CounterStopFlags::TAKE_SNAPSHOT) {
+            match pmu_state.snapshot() {
+                Some(snapshot) => Some(snapshot),
+                None => return SbiRet::no_shmem(),
+            }
+        } else {
+            None
+        };
+        let mut overflow_bitmap = 0;
 
         for counter_idx in CounterMask::new(counter_idx_base, counter_idx_mask) {
             if counter_idx >= pmu_state.total_counters_num {
                 return SbiRet::invalid_param();
             }
 
+            let snapshot_idx = counter_idx - counter_idx_base;
             let stop_result = if counter_idx >= pmu_state.get_hw_counter_num() {
-                pmu_state.stop_fw_counter(counter_idx, is_reset)
+                let result = pmu_state.stop_fw_counter(counter_idx, is_reset);
+                if let Some(snapshot) = &mut snapshot {
+                    snapshot.counter_values[snapshot_idx] =
+                        pmu_state.get_fw_counter(counter_idx).unwrap_or(0);
+                }
+                result
             } else {
                 // If RESET flag is set, mark the counter as inactive
                 if is_reset {
                     pmu_state.active_event[counter_idx] = PMU_EVENT_IDX_INVALID;
                 }
                 let mhpm_offset = get_mhpm_csr_offset(counter_idx).unwrap();
-                stop_hardware_counter(mhpm_offset, is_reset)
+                // Resetting the counter clears its event, so check overflow beforehand.
+                if hardware_counter_overflowed(mhpm_offset) {
+                    overflow_bitmap |= 1 << snapshot_idx;
+                }
+                let result = stop_hardware_counter(mhpm_offset, is_reset);
+                if let Some(snapshot) = &mut snapshot {
+                    snapshot.counter_values[snapshot_idx] = read_mhpmcounter(mhpm_offset);
+                }
+                result
             };
             match stop_result {
                 Ok(_) => {}
@@ -372,6 +512,9 @@ impl Pmu for SbiPmu {
                 Err(StopCounterErr::AlreadyStop) => return SbiRet::already_stopped(),
             }
         }
+        if let Some(snapshot) = snapshot {
+            snapshot.counter_overflow_bitmap = overflow_bitmap;
+        }
         SbiRet::success(0)
     }
 
@@ -379,36 +522,46 @@ impl Pmu for SbiPmu {
     /// Function: Read a firmware counter (FID #5).
     #[inline]
     fn counter_fw_read(&self, counter_idx: usize) -> SbiRet {
-        let pmu_state = &hart_context(current_hartid()).pmu_state;
-        match pmu_state.get_event_idx(counter_idx, true) {
-            Some(event_id) if event_id.firmware_event_valid() => {
-                if event_id.event_code() == firmware_event::PLATFORM {
-                    // TODO: Handle platform-specific PMU events
-                    return SbiRet::invalid_param();
-                }
-                match pmu_state.get_fw_counter(counter_idx) {
-                    Some(value) => SbiRet::success(value as usize),
-                    None => SbiRet::invalid_param(),
-                }
-            }
-            _ => SbiRet::invalid_param(),
+        match read_fw_counter(counter_idx) {
+            Some(value) => SbiRet::success(value as usize),
+            None => SbiRet::invalid_param(),
         }
     }
 
     /// Function: Read a firmware counter high bits (FID #6).
     #[inline]
-    fn counter_fw_read_hi(&self, _counter_idx: usize) -> SbiRet {
+    fn counter_fw_read_hi(&self, counter_idx: usize) -> SbiRet {
         // The Specification states the this function always return zero in sbiret.value for RV64 (or higher) systems.
-        // Currently RustSBI Prototyper only supports RV64 systems
-        SbiRet::success(0)
+        if cfg!(target_pointer_width = "64") {
+            return SbiRet::success(0);
+        }
+        match read_fw_counter(counter_idx) {
+            Some(value) => SbiRet::success((value >> 32) as usize),
+            None => SbiRet::invalid_param(),
+        }
     }
 
     /// Function: Set PMU snapshot shared memory (FID #7).
     #[inline]
     fn snapshot_set_shmem(&self, shmem: SharedPtr<[u8; SIZE]>, flags: usize) -> SbiRet {
-        // Optional function, `not_supported` is returned if not implemented.
-        let _ = (shmem, flags);
-        SbiRet::not_supported()
+        if flags != 0 {
+            return SbiRet::invalid_param();
+        }
+        let pmu_state = &mut hart_context_mut(current_hartid()).pmu_state;
+        let (lo, hi) = (shmem.phys_addr_lo(), shmem.phys_addr_hi());
+        if lo == usize::MAX && hi == usize::MAX {
+            pmu_state.snapshot = None;
+            return SbiRet::success(0);
+        }
+        if !lo.is_multiple_of(SIZE) {
+            return SbiRet::invalid_param();
+        }
+        if hi != 0 || !unsafe { PLATFORM.shmem_accessible(lo, SIZE) } {
+            return SbiRet::invalid_address();
+        }
+        unsafe { (lo as *mut u8).write_bytes(0, SIZE) };
+        pmu_state.snapshot = Some(lo);
+        SbiRet::success(0)
     }
 }
 
@@ -428,13 +581,17 @@ impl SbiPmu {
         counter_idx_base: usize,
         counter_idx_mask: usize,
         event_idx: usize,
+        event_data: u64,
         pmu_state: &PmuState,
     ) -> Result<usize, SbiRet> {
-        // TODO: support `PLATFORM` event
         let event = EventIdx::new(event_idx);
         if !event.firmware_event_valid() {
             return Err(SbiRet::not_supported());
         }
+        if event.event_code() == firmware_event::PLATFORM && !platform_event_registered(event_data)
+        {
+            return Err(SbiRet::not_supported());
+        }
 
         //  TODO: If all firmware events are implemented,
         // this condition should be removed.
@@ -466,6 +623,7 @@ impl SbiPmu {
         counter_idx_mask: usize,
         event_idx: usize,
         event_data: u64,
+        flags: flags::CounterCfgFlags,
         pmu_state: &PmuState,
     ) -> Result<usize, SbiRet> {
         let event = EventIdx::new(event_idx);
@@ -528,7 +686,7 @@ impl SbiPmu {
             }
 
             // Found a counter that meets the conditions - write the event value to the corresponding mhpmevent
-            self.pmu_update_hardware_mhpmevent(mhpm_offset, event_idx, event_data)?;
+            self.pmu_update_hardware_mhpmevent(mhpm_offset, event_idx, event_data, flags)?;
             return Ok(counter_idx);
         }
         Err(SbiRet::not_supported())
@@ -539,6 +697,7 @@ impl SbiPmu {
         mhpm_offset: u16,
         event_idx: usize,
         event_data: u64,
+        flags: flags::CounterCfgFlags,
     ) -> Result<(), SbiRet> {
         // If the event is SBI_PMU_HW_CPU_CYCLES and mcycle is selected,
         // or the event is SBI_PMU_HW_INSTRUCTIONS and minstret is selected, return directly
@@ -571,6 +730,12 @@ impl SbiPmu {
             return Err(SbiRet::not_supported());
         };
 
+        let mhpmevent_val = if hart_extension_probe(current_hartid(), Extension::Sscofpmf) {
+            // Overflow interrupts stay disabled until the counter is started.
+            (mhpmevent_val & !MHPMEVENT_SSCOF_MASK) | MHPMEVENT_OF | mhpmevent_inhibit(flags)
+        } else {
+            mhpmevent_val
+        };
         write_mhpmevent(mhpm_offset, mhpmevent_val);
         Ok(())
     }
@@ -650,6 +815,20 @@ fn configure_counter(
     true
 }
 
+/// Converts the mode filters of counter configuration flags to `mhpmevent` inhibit bits.
+fn mhpmevent_inhibit(flags: flags::CounterCfgFlags) -> u64 {
+    [
+        (flags::CounterCfgFlags::SET_MINH, MHPMEVENT_MINH),
+        (flags::CounterCfgFlags::SET_SINH, MHPMEVENT_SINH),
+        (flags::CounterCfgFlags::SET_UINH, MHPMEVENT_UINH),
+        (flags::CounterCfgFlags::SET_VSINH, MHPMEVENT_VSINH),
+        (flags::CounterCfgFlags::SET_VUINH, MHPMEVENT_VUINH),
+    ]
+    .into_iter()
+    .filter(|(flag, _)| flags.contains(*flag))
+    .fold(0, |bits, (_, bit)| bits | bit)
+}
+
 /// Get the offset of the mhpmcounter CSR corresponding to counter_idx relative to mcycle
 fn get_mhpm_csr_offset(counter_idx: usize) -> Option<u16> {
     let mhpm_mask = hart_mhpm_mask(current_hartid());
@@ -698,6 +877,11 @@ fn start_hardware_counter(
         write_mhpmcounter(mhpm_offset, new_value);
     }
 
+    // Clear the overflow bit so that the counter raises an interrupt when it overflows.
+    if mhpm_offset >= 3 && hart_extension_probe(current_hartid(), Extension::Sscofpmf) {
+        write_mhpmevent(mhpm_offset, read_mhpmevent(mhpm_offset) & !MHPMEVENT_OF);
+    }
+
     unsafe {
         match mhpm_offset {
             0 => mcountinhibit::clear_cy(),
@@ -790,6 +974,46 @@ fn write_mhpmcounter(mhpm_offset: u16, mhpmcounter_val: u64) {
     }
 }
 
+/// Reads the hardware counter specified by the offset.
+fn read_mhpmcounter(mhpm_offset: u16) -> u64 {
+    match mhpm_offset {
+        0 => riscv::register::mcycle::read64(),
+        2 => riscv::register::minstret::read64(),
+        _ => {
+            seq_macro::seq!(N in 3..=31 {
+                match mhpm_offset {
+                    #(
+                        N => pastey::paste!{ [<mhpmcounter ~N>]::read() as u64 },
+                    )*
+                    _ => 0,
+                }
+            })
+        }
+    }
+}
+
+/// Checks whether the hardware counter specified by the offset has overflown.
+///
+/// Overflow is only tracked in `mhpmevent` when the hart implements Sscofpmf.
+fn hardware_counter_overflowed(mhpm_offset: u16) -> bool {
+    if mhpm_offset < 3 || !hart_extension_probe(current_hartid(), Extension::Sscofpmf) {
+        return false;
+    }
+    read_mhpmevent(mhpm_offset) & MHPMEVENT_OF != 0
+}
+
+/// Reads the event selector of the hardware counter specified by the offset.
+fn read_mhpmevent(mhpm_offset: u16) -> u64 {
+    seq_macro::seq!(N in 3..=31 {
+        match mhpm_offset {
+            #(
+                N => pastey::paste!{ [<mhpmevent ~N>]::read() as u64 },
+            )*
+            _ => 0,
+        }
+    })
+}
+
 /// Wrap for counter info
 struct CounterInfo {
     /// Packed representation of counter information:
@@ -957,14 +1181,8 @@ impl EventIdx {
         if event_type != event_type::FIRMWARE {
             return false;
         }
-        if (event_code > firmware_event::HFENCE_VVMA_ASID_RECEIVED
-            && event_code < firmware_event::PLATFORM)
-            || event_code >= firmware_event::PLATFORM
-        {
-            // TODO:Currently RustSBI Prototyper does not support PLATFORM practice
-            return false;
-        }
-        true
+        event_code <= firmware_event::HFENCE_VVMA_ASID_RECEIVED
+            || event_code == firmware_event::PLATFORM
     }
 }
 
@@ -1126,16 +1344,39 @@ cfg_if::cfg_if! {
     }
 }
 
-pub fn pmu_firmware_counter_increment(firmware_event: usize) {
+/// Reads the value of a firmware counter monitoring a valid firmware event.
+fn read_fw_counter(counter_idx: usize) -> Option<u64> {
     let pmu_state = &mut hart_context_mut(current_hartid()).pmu_state;
-    let counter_idx_start = pmu_state.hw_counters_num;
-    for counter_idx in counter_idx_start..counter_idx_start + PMU_FIRMWARE_COUNTER_MAX {
-        let fw_idx = counter_idx - counter_idx_start;
-        if pmu_state.active_event[counter_idx]
-            == EventIdx::from_firmwarw_event(firmware_event).raw()
-            && pmu_state.is_firmware_event_start(counter_idx)
-        {
-            pmu_state.fw_counter[fw_idx] += 1;
-        }
+    pmu_state.collect_ipi_received();
+    match pmu_state.get_event_idx(counter_idx, true) {
+        Some(event_id) if event_id.firmware_event_valid() => pmu_state.get_fw_counter(counter_idx),
+        _ => None,
     }
 }
+
+/// Increments the firmware counters monitoring a standard firmware event.
+pub fn pmu_firmware_counter_increment(firmware_event: usize) {
+    firmware_counter_increment(firmware_event, 0);
+}
+
+/// Increments the firmware counters monitoring a registered platform specific firmware event.
+pub fn pmu_platform_counter_increment(event_data: u64) {
+    firmware_counter_increment(firmware_event::PLATFORM, event_data);
+}
+
+/// Counts an IPI received by `hart_id` without trapping into the firmware.
+///
+/// Devices such as ACLINT SSWI raise supervisor software interrupts directly, so the
+/// sender records the IPI for the target hart to add to its own counters.
+pub fn pmu_remote_ipi_received(hart_id: usize) {
+    hart_context(hart_id)
+        .pmu_state
+        .ipi_received
+        .fetch_add(1, Ordering::Relaxed);
+}
+
+fn firmware_counter_increment(firmware_event: usize, event_data: u64) {
+    hart_context_mut(current_hartid())
+        .pmu_state
+        .add_fw_event(firmware_event, event_data, 1);
+}
@@ -10,19 +10,35 @@ pub trait ResetDevice {
     fn reset(&self) -> !;
 }
 
+/// System reset, with separate devices for rebooting and powering off.
+///
+/// Warm and cold reboot share the reboot device, as the `syscon-reboot`, `gpio-restart`
+/// and `sifive,test` bindings only describe one way to restart the system.
 pub struct SbiReset {
-    pub reset_dev: Mutex<Box<dyn ResetDevice>>,
+    pub reboot_dev: Option<Mutex<Box<dyn ResetDevice>>>,
+    pub poweroff_dev: Option<Mutex<Box<dyn ResetDevice>>>,
 }
 
 impl SbiReset {
-    pub fn new(reset_dev: Mutex<Box<dyn ResetDevice>>) -> Self {
-        Self { reset_dev }
+    pub fn new(
+        reboot_dev: Option<Box<dyn ResetDevice>>,
+        poweroff_dev: Option<Box<dyn ResetDevice>>,
+    ) -> Self {
+        Self {
+            reboot_dev: reboot_dev.map(Mutex::new),
+            poweroff_dev: poweroff_dev.map(Mutex::new),
+        }
     }
 
     #[allow(unused)]
     pub fn fail(&self) -> ! {
         trace!("Test fail, invoke process exit procedure on Reset device");
-        self.reset_dev.lock().fail(0);
+        match (&self.poweroff_dev, &self.reboot_dev) {
+            (Some(dev), _) | (None, Some(dev)) => dev.lock().fail(0),
+            (None, None) => loop {
+                core::hint::spin_loop()
+            },
+        }
     }
 }
 
@@ -34,12 +50,20 @@ impl rustsbi::Reset for SbiReset {
             RESET_TYPE_SHUTDOWN, RESET_TYPE_WARM_REBOOT,
         };
         match reset_type {
-            RESET_TYPE_SHUTDOWN => match reset_reason {
-                RESET_REASON_NO_REASON => self.reset_dev.lock().pass(),
-                RESET_REASON_SYSTEM_FAILURE => self.reset_dev.lock().fail(u16::MAX),
-                value => self.reset_dev.lock().fail(value as _),
+            RESET_TYPE_SHUTDOWN => {
+                let Some(dev) = &self.poweroff_dev else {
+                    return SbiRet::not_supported();
+                };
+                match reset_reason {
+                    RESET_REASON_NO_REASON => dev.lock().pass(),
+                    RESET_REASON_SYSTEM_FAILURE => dev.lock().fail(u16::MAX),
+                    value => dev.lock().fail(value as _),
+                }
+            }
+            RESET_TYPE_COLD_REBOOT | RESET_TYPE_WARM_REBOOT => match &self.reboot_dev {
+                Some(dev) => dev.lock().reset(),
+                None => SbiRet::not_supported(),
             },
-            RESET_TYPE_COLD_REBOOT | RESET_TYPE_WARM_REBOOT => self.reset_dev.lock().reset(),
 
             _ => SbiRet::invalid_param(),
         }
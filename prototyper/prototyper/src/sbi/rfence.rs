@@ -1,26 +1,132 @@
 use rustsbi::{HartMask, SbiRet};
 use sbi_spec::pmu::firmware_event;
-use spin::Mutex;
 
-use crate::cfg::{PAGE_SIZE, TLB_FLUSH_LIMIT};
+use crate::cfg::{NUM_HART_MAX, PAGE_SIZE, TLB_FLUSH_LIMIT};
 use crate::platform::PLATFORM;
 use crate::riscv::current_hartid;
-use crate::sbi::fifo::{Fifo, FifoError};
 use crate::sbi::trap_stack::ROOT_STACK;
 use core::arch::asm;
+use core::cell::UnsafeCell;
 
-use core::sync::atomic::{AtomicU32, Ordering};
+use core::sync::atomic::{AtomicBool, AtomicU32, Ordering};
 
-use super::pmu::pmu_firmware_counter_increment;
+use super::pmu::{platform_event, pmu_firmware_counter_increment, pmu_platform_counter_increment};
+
+/// Words of a set of harts, one bit per hart.
+const HART_SET_WORDS: usize = NUM_HART_MAX.div_ceil(usize::BITS as usize);
 
 /// Cell for managing remote fence operations between harts.
 pub(crate) struct RFenceCell {
-    // Queue of fence operations with source hart ID
-    queue: Mutex<Fifo<(RFenceContext, usize)>>,
+    // Lock-free mailbox of fence operations, one slot for each sending hart
+    mailbox: RFenceMailbox,
     // Counter for tracking pending synchronization operations
     wait_sync_count: AtomicU32,
 }
 
+/// Set of harts waiting for a fence operation.
+#[derive(Clone, Copy)]
+struct HartSet([usize; HART_SET_WORDS]);
+
+impl HartSet {
+    const WORD_BITS: usize = usize::BITS as usize;
+
+    /// Creates a set of the single hart `hart_id`.
+    fn single(hart_id: usize) -> Self {
+        let mut words = [0; HART_SET_WORDS];
+        words[hart_id / Self::WORD_BITS] = 1 << (hart_id % Self::WORD_BITS);
+        Self(words)
+    }
+
+    /// Adds all harts of `other` to this set.
+    fn merge(&mut self, other: &HartSet) {
+        for (word, other) in self.0.iter_mut().zip(other.0) {
+            *word |= other;
+        }
+    }
+
+    /// Iterates over the harts in this set.
+    fn iter(&self) -> impl Iterator<Item = usize> + '_ {
+        (0..HART_SET_WORDS * Self::WORD_BITS).filter(|hart_id| {
+            self.0[hart_id / Self::WORD_BITS] & (1 << (hart_id % Self::WORD_BITS)) != 0
+        })
+    }
+}
+
+/// Slot of the mailbox holding the fence operation sent by one hart.
+struct RFenceSlot {
+    pending: AtomicBool,
+    ctx: UnsafeCell<RFenceContext>,
+}
+
+/// Lock-free mailbox of fence operations, with a slot for each sending hart.
+///
+/// Each sender waits for its operation to complete before sending another, so it has at most
+/// one operation pending in each mailbox and its slot never overflows. The sender writes its
+/// slot before setting `pending`, and only the receiving hart reads and clears it.
+struct RFenceMailbox {
+    slots: [RFenceSlot; NUM_HART_MAX],
+}
+
+impl RFenceMailbox {
+    fn new() -> Self {
+        Self {
+            slots: [const {
+                RFenceSlot {
+                    pending: AtomicBool::new(false),
+                    ctx: UnsafeCell::new(RFenceContext {
+                        start_addr: 0,
+                        size: 0,
+                        asid: 0,
+                        vmid: 0,
+                        op: RFenceType::FenceI,
+                    }),
+                }
+            }; NUM_HART_MAX],
+        }
+    }
+
+    /// Posts `ctx` from the current hart.
+    fn post(&self, ctx: RFenceContext) {
+        let slot = &self.slots[current_hartid()];
+        // The previous operation of this hart has completed, so its slot is not pending.
+        unsafe { *slot.ctx.get() = ctx };
+        slot.pending.store(true, Ordering::Release);
+    }
+
+    /// Takes the first pending operation, merged with the later ones it covers or adjoins.
+    fn take(&self) -> Option<(RFenceContext, HartSet)> {
+        let (first, slot) = self
+            .slots
+            .iter()
+            .enumerate()
+            .find(|(_, slot)| slot.pending.load(Ordering::Acquire))?;
+        let mut ctx = unsafe { *slot.ctx.get() };
+        slot.pending.store(false, Ordering::Relaxed);
+        let mut sources = HartSet::single(first);
+        for (hart_id, slot) in self.slots.iter().enumerate().skip(first + 1) {
+            if !slot.pending.load(Ordering::Acquire) {
+                continue;
+            }
+            let other = unsafe { &*slot.ctx.get() };
+            let was_ranged = !ctx.is_full_flush() && !other.is_full_flush();
+            if ctx.merge(other) {
+                slot.pending.store(false, Ordering::Relaxed);
+                sources.merge(&HartSet::single(hart_id));
+                if was_ranged && ctx.is_full_flush() {
+                    pmu_platform_counter_increment(platform_event::RFENCE_FULL_FLUSH);
+                }
+            }
+        }
+        Some((ctx, sources))
+    }
+
+    fn is_empty(&self) -> bool {
+        self.slots
+            .iter()
+            .all(|slot| !slot.pending.load(Ordering::Relaxed))
+    }
+}
+
 /// Context information for a remote fence operation.
 #[repr(C)]
 #[derive(Clone, Copy, Debug)]
@@ -61,11 +167,68 @@ pub enum RFenceType {
     HFenceVvma,
 }
 
+impl RFenceContext {
+    /// Checks if the operation flushes the whole address space rather than a range.
+    fn is_full_flush(&self) -> bool {
+        (self.start_addr == 0 && self.size == 0)
+            || self.size == usize::MAX
+            || self.size > TLB_FLUSH_LIMIT
+    }
+
+    /// Checks if this operation flushes everything `other` does, for all ASIDs and VMIDs.
+    fn covers(&self, other: &RFenceContext) -> bool {
+        self.is_full_flush() && self.op == other.op.global_op()
+    }
+
+    /// Merges `other` into this operation if one covers the other, or if both have the same
+    /// type, ASID and VMID, and their ranges overlap or adjoin.
+    fn merge(&mut self, other: &RFenceContext) -> bool {
+        if self.covers(other) {
+            return true;
+        }
+        if other.covers(self) {
+            *self = *other;
+            return true;
+        }
+        if self.op != other.op || self.asid != other.asid || self.vmid != other.vmid {
+            return false;
+        }
+        if self.is_full_flush() {
+            return true;
+        }
+        if other.is_full_flush() {
+            *self = *other;
+            return true;
+        }
+        let (end, other_end) = (self.start_addr + self.size, other.start_addr + other.size);
+        if other.start_addr > end || self.start_addr > other_end {
+            return false;
+        }
+        self.start_addr = self.start_addr.min(other.start_addr);
+        self.size = end.max(other_end) - self.start_addr;
+        true
+    }
+}
+
+impl RFenceType {
+    /// Operation covering this one for all ASIDs and VMIDs.
+    fn global_op(self) -> Self {
+        match self {
+            Self::FenceI => Self::FenceI,
+            Self::SFenceVma | Self::SFenceVmaAsid => Self::SFenceVma,
+            #[cfg(feature = "hypervisor")]
+            Self::HFenceGvmaVmid | Self::HFenceGvma => Self::HFenceGvma,
+            #[cfg(feature = "hypervisor")]
+            Self::HFenceVvmaAsid | Self::HFenceVvma => Self::HFenceVvma,
+        }
+    }
+}
+
 impl RFenceCell {
-    /// Creates a new RFenceCell with empty queue and zero sync count.
+    /// Creates a new RFenceCell with empty mailbox and zero sync count.
     pub fn new() -> Self {
         Self {
-            queue: Mutex::new(Fifo::new()),
+            mailbox: RFenceMailbox::new(),
             wait_sync_count: AtomicU32::new(0),
         }
     }
@@ -115,7 +278,8 @@ pub(crate) fn remote_rfence(hart_id: usize) -> Option<RemoteRFenceCell<'static>>
 impl LocalRFenceCell<'_> {
     /// Checks if all synchronization operations are complete.
     pub fn is_sync(&self) -> bool {
-        self.0.wait_sync_count.load(Ordering::Relaxed) == 0
+        // Pairs with `sub`, so the operations taken from the slots of this hart are read.
+        self.0.wait_sync_count.load(Ordering::Acquire) == 0
     }
 
     /// Increments the synchronization counter.
@@ -123,54 +287,32 @@ impl LocalRFenceCell<'_> {
         self.0.wait_sync_count.fetch_add(1, Ordering::Relaxed);
     }
 
-    /// Checks if the operation queue is empty.
+    /// Checks if no fence operation is pending.
     pub fn is_empty(&self) -> bool {
-        self.0.queue.lock().is_empty()
+        self.0.mailbox.is_empty()
     }
 
-    /// Gets the next fence operation from the queue.
-    pub fn get(&self) -> Option<(RFenceContext, usize)> {
-        self.0.queue.lock().pop().ok()
+    /// Gets the next fence operation with the harts waiting for it, merging pending ones.
+    fn get(&self) -> Option<(RFenceContext, HartSet)> {
+        self.0.mailbox.take()
     }
 
-    /// Adds a fence operation to the queue, retrying if full.
+    /// Adds a fence operation from the current hart.
     pub fn set(&self, ctx: RFenceContext) {
-        let hart_id = current_hartid();
-        loop {
-            let mut queue = self.0.queue.lock();
-            match queue.push((ctx, hart_id)) {
-                Ok(_) => break,
-                Err(FifoError::Full) => {
-                    drop(queue);
-                    rfence_single_handler();
-                }
-                Err(_) => panic!("Unable to push fence ops to fifo"),
-            }
-        }
+        self.0.mailbox.post(ctx);
     }
 }
 
 #[allow(unused)]
 impl RemoteRFenceCell<'_> {
-    /// Adds a fence operation to the queue from a remote hart.
+    /// Adds a fence operation from the current hart to a remote hart.
     pub fn set(&self, ctx: RFenceContext) {
-        let hart_id = current_hartid();
-        loop {
-            let mut queue = self.0.queue.lock();
-            match queue.push((ctx, hart_id)) {
-                Ok(_) => return,
-                Err(FifoError::Full) => {
-                    drop(queue);
-                    rfence_single_handler();
-                }
-                Err(_) => panic!("Unable to push fence ops to fifo"),
-            }
-        }
+        self.0.mailbox.post(ctx);
     }
 
     /// Decrements the synchronization counter.
     pub fn sub(&self) {
-        self.0.wait_sync_count.fetch_sub(1, Ordering::Relaxed);
+        self.0.wait_sync_count.fetch_sub(1, Ordering::Release);
     }
 }
 
@@ -180,10 +322,9 @@ pub(crate) struct SbiRFence;
 /// Validates address range for fence operations
 #[inline(always)]
 fn validate_address_range(start_addr: usize, size: usize) -> Result<usize, SbiRet> {
-    if !((start_addr == 0 && size == 0) || size == usize::MAX) {
-        if start_addr & (PAGE_SIZE - 1) != 0 {
-            return Err(SbiRet::invalid_address());
-        }
+    if !((start_addr == 0 && size == 0) || size == usize::MAX) && start_addr & (PAGE_SIZE - 1) != 0
+    {
+        return Err(SbiRet::invalid_address());
     }
 
     if start_addr > usize::MAX - size {
@@ -195,11 +336,9 @@ fn validate_address_range(start_addr: usize, size: usize) -> Result<usize, SbiRe
 
 /// Processes a remote fence operation by sending IPI to target harts.
 fn remote_fence_process(rfence_ctx: RFenceContext, hart_mask: HartMask) -> SbiRet {
-    let sbi_ret = unsafe { PLATFORM.sbi.ipi.as_ref() }
+    unsafe { PLATFORM.sbi.ipi.as_ref() }
         .unwrap()
-        .send_ipi_by_fence(hart_mask, rfence_ctx);
-
-    sbi_ret
+        .send_ipi_by_fence(hart_mask, rfence_ctx)
 }
 
 #[cfg(feature = "hypervisor")]
@@ -387,19 +526,15 @@ pub fn rfence_single_handler() {
         None => panic!("rfence_single_handler called with no local rfence context"),
     };
 
-    if let Some((ctx, source_hart_id)) = local_rf.get() {
-        let full_flush = (ctx.start_addr == 0 && ctx.size == 0)
-            || (ctx.size == usize::MAX)
-            || (ctx.size > TLB_FLUSH_LIMIT && ctx.size != usize::MAX);
+    if let Some((ctx, sources)) = local_rf.get() {
+        let full_flush = ctx.is_full_flush();
 
-        match ctx.op {
+        let received_event = match ctx.op {
             RFenceType::FenceI => {
-                pmu_firmware_counter_increment(firmware_event::FENCE_I_RECEIVED);
                 unsafe { asm!("fence.i") };
-                remote_rfence(source_hart_id).unwrap().sub();
+                firmware_event::FENCE_I_RECEIVED
             }
             RFenceType::SFenceVma => {
-                pmu_firmware_counter_increment(firmware_event::SFENCE_VMA_RECEIVED);
                 if full_flush {
                     unsafe { asm!("sfence.vma") };
                 } else {
@@ -408,12 +543,9 @@ pub fn rfence_single_handler() {
                         unsafe { asm!("sfence.vma {}", in(reg) addr) };
                     }
                 }
-                if let Some(remote_cell) = remote_rfence(source_hart_id) {
-                    remote_cell.sub();
-                }
+                firmware_event::SFENCE_VMA_RECEIVED
             }
             RFenceType::SFenceVmaAsid => {
-                pmu_firmware_counter_increment(firmware_event::SFENCE_VMA_ASID_RECEIVED);
                 let asid = ctx.asid;
                 if full_flush {
                     unsafe { asm!("sfence.vma x0, {}", in(reg) asid) };
@@ -423,13 +555,10 @@ pub fn rfence_single_handler() {
                         unsafe { asm!("sfence.vma {}, {}", in(reg) addr, in(reg) asid) };
                     }
                 }
-                if let Some(remote_cell) = remote_rfence(source_hart_id) {
-                    remote_cell.sub();
-                }
+                firmware_event::SFENCE_VMA_ASID_RECEIVED
             }
             #[cfg(feature = "hypervisor")]
             RFenceType::HFenceGvmaVmid => {
-                pmu_firmware_counter_increment(firmware_event::HFENCE_GVMA_VMID_RECEIVED);
                 let vmid = ctx.vmid;
                 if full_flush {
                     unsafe { asm!("hfence.gvma x0, {}", in(reg) vmid) };
@@ -439,13 +568,10 @@ pub fn rfence_single_handler() {
                         unsafe { asm!("hfence.gvma {}, {}", in(reg) addr, in(reg) vmid) };
                     }
                 }
-                if let Some(remote_cell) = remote_rfence(source_hart_id) {
-                    remote_cell.sub();
-                }
+                firmware_event::HFENCE_GVMA_VMID_RECEIVED
             }
             #[cfg(feature = "hypervisor")]
             RFenceType::HFenceGvma => {
-                pmu_firmware_counter_increment(firmware_event::HFENCE_GVMA_RECEIVED);
                 if full_flush {
                     unsafe { asm!("hfence.gvma x0, x0") };
                 } else {
@@ -454,13 +580,10 @@ pub fn rfence_single_handler() {
                         unsafe { asm!("hfence.gvma {}, x0", in(reg) addr) };
                     }
                 }
-                if let Some(remote_cell) = remote_rfence(source_hart_id) {
-                    remote_cell.sub();
-                }
+                firmware_event::HFENCE_GVMA_RECEIVED
             }
             #[cfg(feature = "hypervisor")]
             RFenceType::HFenceVvmaAsid => {
-                pmu_firmware_counter_increment(firmware_event::HFENCE_VVMA_ASID_RECEIVED);
                 let asid = ctx.asid;
                 if full_flush {
                     unsafe { asm!("hfence.vvma x0, {}", in(reg) asid) };
@@ -470,13 +593,10 @@ pub fn rfence_single_handler() {
                         unsafe { asm!("hfence.vvma {}, {}", in(reg) addr, in(reg) asid) };
                     }
                 }
-                if let Some(remote_cell) = remote_rfence(source_hart_id) {
-                    remote_cell.sub();
-                }
+                firmware_event::HFENCE_VVMA_ASID_RECEIVED
             }
             #[cfg(feature = "hypervisor")]
             RFenceType::HFenceVvma => {
-                pmu_firmware_counter_increment(firmware_event::HFENCE_VVMA_RECEIVED);
                 if full_flush {
                     unsafe { asm!("hfence.vvma x0, x0") };
                 } else {
@@ -485,9 +605,15 @@ pub fn rfence_single_handler() {
                         unsafe { asm!("hfence.vvma {}, x0", in(reg) addr) };
                     }
                 }
-                if let Some(remote_cell) = remote_rfence(source_hart_id) {
-                    remote_cell.sub();
-                }
+                firmware_event::HFENCE_VVMA_RECEIVED
+            }
+        };
+
+        // Merged requests are received once for each waiting hart.
+        for source_hart_id in sources.iter() {
+            pmu_firmware_counter_increment(received_event);
+            if let Some(remote_cell) = remote_rfence(source_hart_id) {
+                remote_cell.sub();
             }
         }
     }
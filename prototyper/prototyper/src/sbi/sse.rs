@@ -0,0 +1,654 @@
+use core::arch::asm;
+use core::ops::Range;
+use core::sync::atomic::{AtomicU32, Ordering};
+use fast_trap::FastContext;
+use riscv::register::{mepc, mstatus, sepc, sstatus};
+use rustsbi::{SbiRet, SharedPtr};
+use sbi_spec::sse::{attr_id, config, event_id, event_state, interrupted_flags, status};
+use spin::Mutex;
+
+use crate::platform::PLATFORM;
+use crate::riscv::current_hartid;
+use crate::sbi::features::{Extension, hart_extension_probe};
+use crate::sbi::hsm::remote_hsm;
+use crate::sbi::ipi::{IPI_TYPE_SSE, set_ipi_type};
+use crate::sbi::trap_stack::{hart_context, hart_context_mut};
+
+/// Local counter overflow interrupt number.
+pub(crate) const LCOFI: usize = 13;
+
+/// Number of event attributes defined by the specification.
+const ATTR_COUNT: u32 = attr_id::INTERRUPTED_A7 + 1;
+
+/// Standard event IDs defined by the specification.
+const STANDARD_EVENTS: [u32; 8] = [
+    event_id::LOCAL_HIGH_PRIO_RAS,
+    event_id::LOCAL_DOUBLE_TRAP,
+    event_id::GLOBAL_HIGH_PRIO_RAS,
+    event_id::LOCAL_PMU_OVERFLOW,
+    event_id::LOCAL_LOW_PRIO_RAS,
+    event_id::GLOBAL_LOW_PRIO_RAS,
+    event_id::LOCAL_SOFTWARE_INJECTED,
+    event_id::GLOBAL_SOFTWARE_INJECTED,
+];
+
+/// Local events supported by the prototyper, in the order stored in `SseState`.
+const LOCAL_EVENTS: [u32; 2] = [
+    event_id::LOCAL_PMU_OVERFLOW,
+    event_id::LOCAL_SOFTWARE_INJECTED,
+];
+
+/// Global events supported by the prototyper.
+const GLOBAL_EVENTS: [u32; 1] = [event_id::GLOBAL_SOFTWARE_INJECTED];
+
+/// `INTERRUPTED_FLAGS` bits saved and restored by the prototyper.
+const SUPPORTED_FLAGS: usize = interrupted_flags::SSTATUS_SPP | interrupted_flags::SSTATUS_SPIE;
+
+const SSTATUS_SIE: usize = 1 << 1;
+const SSTATUS_SPIE: usize = 1 << 5;
+const SSTATUS_SPP: usize = 1 << 8;
+
+/// Global events, shared by all harts.
+static GLOBAL: Mutex<[Event; GLOBAL_EVENTS.len()]> =
+    Mutex::new([Event::new(event_id::GLOBAL_SOFTWARE_INJECTED)]);
+
+/// Attributes and state of a software event.
+#[derive(Clone, Copy)]
+struct Event {
+    id: u32,
+    state: usize,
+    pending: bool,
+    priority: u32,
+    config: usize,
+    /// Hart on which a global event is delivered.
+    preferred_hart: usize,
+    entry_pc: usize,
+    entry_arg: usize,
+    /// Interrupted `sepc`, flags, `a6` and `a7`, saved when the event starts running.
+    interrupted: [usize; 4],
+    /// Hart on which the event is running.
+    hart_id: usize,
+}
+
+impl Event {
+    const fn new(id: u32) -> Self {
+        Self {
+            id,
+            state: event_state::UNUSED,
+            pending: false,
+            priority: 0,
+            config: 0,
+            preferred_hart: 0,
+            entry_pc: 0,
+            entry_arg: 0,
+            interrupted: [0; 4],
+            hart_id: 0,
+        }
+    }
+
+    #[inline]
+    const fn is_global(&self) -> bool {
+        self.id & event_id::GLOBAL_BIT != 0
+    }
+
+    #[inline]
+    const fn is_injectable(&self) -> bool {
+        matches!(
+            self.id,
+            event_id::LOCAL_SOFTWARE_INJECTED | event_id::GLOBAL_SOFTWARE_INJECTED
+        )
+    }
+
+    /// Lower value means higher priority; event ID breaks ties.
+    #[inline]
+    const fn priority_key(&self) -> (u32, u32) {
+        (self.priority, self.id)
+    }
+
+    /// Whether the event is running on the current hart.
+    #[inline]
+    fn is_running_here(&self, hart_id: usize) -> bool {
+        self.state == event_state::RUNNING && (!self.is_global() || self.hart_id == hart_id)
+    }
+
+    /// Whether the event is ready to be delivered on the current hart.
+    #[inline]
+    fn is_ready_here(&self, hart_id: usize) -> bool {
+        self.state == event_state::ENABLED
+            && self.pending
+            && (!self.is_global() || self.preferred_hart == hart_id)
+    }
+
+    fn read_attr(&self, attr: u32) -> usize {
+        match attr {
+            attr_id::STATUS => {
+                let mut value = self.state;
+                if self.pending {
+                    value |= status::PENDING;
+                }
+                if self.is_injectable() {
+                    value |= status::INJECTABLE;
+                }
+                value
+            }
+            attr_id::PRIORITY => self.priority as usize,
+            attr_id::CONFIG => self.config,
+            attr_id::PREFERRED_HART if self.is_global() => self.preferred_hart,
+            attr_id::PREFERRED_HART => current_hartid(),
+            attr_id::ENTRY_PC => self.entry_pc,
+            attr_id::ENTRY_ARG => self.entry_arg,
+            attr_id::INTERRUPTED_SEPC => self.interrupted[0],
+            attr_id::INTERRUPTED_FLAGS => self.interrupted[1],
+            attr_id::INTERRUPTED_A6 => self.interrupted[2],
+            attr_id::INTERRUPTED_A7 => self.interrupted[3],
+            _ => unreachable!(),
+        }
+    }
+
+    /// Checks whether `value` can be written to attribute `attr`.
+    fn check_write(&self, attr: u32, value: usize) -> Result<(), SbiRet> {
+        let configurable = matches!(self.state, event_state::UNUSED | event_state::REGISTERED);
+        match attr {
+            attr_id::PRIORITY if u32::try_from(value).is_err() => Err(SbiRet::invalid_param()),
+            attr_id::CONFIG if value & !config::ONESHOT != 0 => Err(SbiRet::invalid_param()),
+            attr_id::PREFERRED_HART if !self.is_global() => Err(SbiRet::bad_range()),
+            attr_id::PREFERRED_HART if !hart_is_valid(value) => Err(SbiRet::invalid_param()),
+            attr_id::PRIORITY | attr_id::CONFIG | attr_id::PREFERRED_HART if !configurable => {
+                Err(SbiRet::invalid_state())
+            }
+            attr_id::PRIORITY | attr_id::CONFIG | attr_id::PREFERRED_HART => Ok(()),
+            attr_id::INTERRUPTED_FLAGS if value & !SUPPORTED_FLAGS != 0 => {
+                Err(SbiRet::invalid_param())
+            }
+            attr_id::INTERRUPTED_SEPC
+            | attr_id::INTERRUPTED_FLAGS
+            | attr_id::INTERRUPTED_A6
+            | attr_id::INTERRUPTED_A7 => {
+                if self.is_running_here(current_hartid()) {
+                    Ok(())
+                } else {
+                    Err(SbiRet::invalid_state())
+                }
+            }
+            // STATUS, ENTRY_PC and ENTRY_ARG are read-only.
+            _ => Err(SbiRet::bad_range()),
+        }
+    }
+
+    fn write_attr(&mut self, attr: u32, value: usize) {
+        match attr {
+            attr_id::PRIORITY => self.priority = value as u32,
+            attr_id::CONFIG => self.config = value,
+            attr_id::PREFERRED_HART => self.preferred_hart = value,
+            attr_id::INTERRUPTED_SEPC => self.interrupted[0] = value,
+            attr_id::INTERRUPTED_FLAGS => self.interrupted[1] = value,
+            attr_id::INTERRUPTED_A6 => self.interrupted[2] = value,
+            attr_id::INTERRUPTED_A7 => self.interrupted[3] = value,
+            _ => unreachable!(),
+        }
+    }
+}
+
+/// Per-hart state of Supervisor Software Events.
+pub(crate) struct SseState {
+    /// Whether software events are masked on this hart.
+    masked: bool,
+    /// Local events of this hart, in the order of `LOCAL_EVENTS`.
+    events: [Event; LOCAL_EVENTS.len()],
+    /// Local events injected by any hart but not yet marked pending.
+    injected: AtomicU32,
+    /// Interrupted context of the event just completed, to be resumed.
+    completed: Option<[usize; 4]>,
+}
+
+impl SseState {
+    pub const fn new() -> Self {
+        Self {
+            masked: true,
+            events: [Event::new(LOCAL_EVENTS[0]), Event::new(LOCAL_EVENTS[1])],
+            injected: AtomicU32::new(0),
+            completed: None,
+        }
+    }
+
+    /// Marks events injected by other harts as pending.
+    #[inline]
+    fn collect_injected(&mut self) {
+        let injected = self.injected.swap(0, Ordering::Acquire);
+        for (idx, event) in self.events.iter_mut().enumerate() {
+            if injected & (1 << idx) != 0 {
+                event.pending = true;
+            }
+        }
+    }
+}
+
+/// Gets the SSE state of the current hart.
+#[inline]
+fn local_sse() -> &'static mut SseState {
+    &mut hart_context_mut(current_hartid()).sse
+}
+
+/// Implementation of SBI Supervisor Software Events extension.
+pub(crate) struct SbiSse;
+
+impl SbiSse {
+    /// Creates the SSE extension, with global events preferring the boot hart.
+    pub fn new() -> Self {
+        for event in GLOBAL.lock().iter_mut() {
+            event.preferred_hart = current_hartid();
+        }
+        Self
+    }
+}
+
+impl rustsbi::Sse for SbiSse {
+    fn read_attrs(
+        &self,
+        event_id: u32,
+        base_attr_id: u32,
+        attr_count: u32,
+        output: SharedPtr<u8>,
+    ) -> SbiRet {
+        with_event(event_id, |event| {
+            let attrs = match attr_range(base_attr_id, attr_count) {
+                Ok(attrs) => attrs,
+                Err(err) => return err,
+            };
+            let output = match shmem_ptr(output, attr_count) {
+                Ok(ptr) => ptr,
+                Err(err) => return err,
+            };
+            for (i, attr) in attrs.enumerate() {
+                unsafe { output.add(i).write_volatile(event.read_attr(attr)) };
+            }
+            SbiRet::success(0)
+        })
+    }
+
+    fn write_attrs(
+        &self,
+        event_id: u32,
+        base_attr_id: u32,
+        attr_count: u32,
+        input: SharedPtr<u8>,
+    ) -> SbiRet {
+        with_event(event_id, |event| {
+            let attrs = match attr_range(base_attr_id, attr_count) {
+                Ok(attrs) => attrs,
+                Err(err) => return err,
+            };
+            let input = match shmem_ptr(input, attr_count) {
+                Ok(ptr) => ptr,
+                Err(err) => return err,
+            };
+            let read = |i: usize| unsafe { input.add(i).read_volatile() };
+            // Check all values before writing any of them.
+            for (i, attr) in attrs.clone().enumerate() {
+                if let Err(err) = event.check_write(attr, read(i)) {
+                    return err;
+                }
+            }
+            for (i, attr) in attrs.enumerate() {
+                event.write_attr(attr, read(i));
+            }
+            SbiRet::success(0)
+        })
+    }
+
+    fn register(&self, event_id: u32, handler_entry_pc: usize, handler_entry_arg: usize) -> SbiRet {
+        with_event(event_id, |event| {
+            if !handler_entry_pc.is_multiple_of(2) {
+                return SbiRet::invalid_param();
+            }
+            if event.state != event_state::UNUSED {
+                return SbiRet::invalid_state();
+            }
+            event.entry_pc = handler_entry_pc;
+            event.entry_arg = handler_entry_arg;
+            event.state = event_state::REGISTERED;
+            SbiRet::success(0)
+        })
+    }
+
+    fn unregister(&self, event_id: u32) -> SbiRet {
+        with_event(event_id, |event| {
+            if event.state != event_state::REGISTERED {
+                return SbiRet::invalid_state();
+            }
+            event.state = event_state::UNUSED;
+            event.pending = false;
+            if event.id == event_id::LOCAL_PMU_OVERFLOW {
+                lcofi_release();
+            }
+            SbiRet::success(0)
+        })
+    }
+
+    fn enable(&self, event_id: u32) -> SbiRet {
+        with_event(event_id, |event| {
+            if event.state != event_state::REGISTERED {
+                return SbiRet::invalid_state();
+            }
+            event.state = event_state::ENABLED;
+            if event.id == event_id::LOCAL_PMU_OVERFLOW {
+                lcofi_claim();
+            }
+            SbiRet::success(0)
+        })
+    }
+
+    fn disable(&self, event_id: u32) -> SbiRet {
+        with_event(event_id, |event| {
+            if event.state != event_state::ENABLED {
+                return SbiRet::invalid_state();
+            }
+            event.state = event_state::REGISTERED;
+            if event.id == event_id::LOCAL_PMU_OVERFLOW {
+                lcofi_release();
+            }
+            SbiRet::success(0)
+        })
+    }
+
+    fn complete(&self) -> SbiRet {
+        let hart_id = current_hartid();
+        let sse = local_sse();
+        let mut global = GLOBAL.lock();
+        // The running event with the highest priority is the latest one delivered.
+        let Some(event) = sse
+            .events
+            .iter_mut()
+            .chain(global.iter_mut())
+            .filter(|event| event.is_running_here(hart_id))
+            .min_by_key(|event| event.priority_key())
+        else {
+            return SbiRet::success(0);
+        };
+        if event.config & config::ONESHOT != 0 {
+            event.state = event_state::REGISTERED;
+        } else {
+            event.state = event_state::ENABLED;
+            if event.id == event_id::LOCAL_PMU_OVERFLOW {
+                unsafe { asm!("csrs mie, {}", in(reg) 1usize << LCOFI) };
+            }
+        }
+        sse.completed = Some(event.interrupted);
+        SbiRet::success(0)
+    }
+
+    fn inject(&self, event_id: u32, hart_id: usize) -> SbiRet {
+        if let Some(idx) = LOCAL_EVENTS.iter().position(|id| *id == event_id) {
+            if !hart_is_valid(hart_id) {
+                return SbiRet::invalid_param();
+            }
+            // Harts may differ in extensions, so ask the hart which takes the event.
+            if !local_event_supported(event_id, hart_id) {
+                return SbiRet::not_supported();
+            }
+            if !Event::new(event_id).is_injectable() {
+                return SbiRet::invalid_param();
+            }
+            hart_context(hart_id)
+                .sse
+                .injected
+                .fetch_or(1 << idx, Ordering::Release);
+            if hart_id != current_hartid() {
+                send_sse_ipi(hart_id);
+            }
+            SbiRet::success(0)
+        } else if let Some(idx) = GLOBAL_EVENTS.iter().position(|id| *id == event_id) {
+            let target = {
+                let mut global = GLOBAL.lock();
+                global[idx].pending = true;
+                global[idx].preferred_hart
+            };
+            if target != current_hartid() {
+                send_sse_ipi(target);
+            }
+            SbiRet::success(0)
+        } else if is_valid_event(event_id) {
+            SbiRet::not_supported()
+        } else {
+            SbiRet::invalid_param()
+        }
+    }
+
+    fn hart_unmask(&self) -> SbiRet {
+        let sse = local_sse();
+        if !sse.masked {
+            return SbiRet::already_started();
+        }
+        sse.masked = false;
+        SbiRet::success(0)
+    }
+
+    fn hart_mask(&self) -> SbiRet {
+        let sse = local_sse();
+        if sse.masked {
+            return SbiRet::already_stopped();
+        }
+        sse.masked = true;
+        SbiRet::success(0)
+    }
+}
+
+/// Runs `f` on a supported event, or returns the error for an unsupported or invalid one.
+fn with_event(event_id: u32, f: impl FnOnce(&mut Event) -> SbiRet) -> SbiRet {
+    if let Some(idx) = LOCAL_EVENTS.iter().position(|id| *id == event_id) {
+        if !local_event_supported(event_id, current_hartid()) {
+            return SbiRet::not_supported();
+        }
+        let sse = local_sse();
+        sse.collect_injected();
+        f(&mut sse.events[idx])
+    } else if let Some(idx) = GLOBAL_EVENTS.iter().position(|id| *id == event_id) {
+        f(&mut GLOBAL.lock()[idx])
+    } else if is_valid_event(event_id) {
+        SbiRet::not_supported()
+    } else {
+        SbiRet::invalid_param()
+    }
+}
+
+/// Checks whether the event ID is a standard or platform-specific one, rather than reserved.
+#[inline]
+fn is_valid_event(event_id: u32) -> bool {
+    matches!(event_id >> 16, 0x0000 | 0x0001 | 0x0010 | 0xFFFF)
+        && (event_id & event_id::PLATFORM_BIT != 0 || STANDARD_EVENTS.contains(&event_id))
+}
+
+/// Checks whether the local event is supported on the given hart.
+#[inline]
+fn local_event_supported(event_id: u32, hart_id: usize) -> bool {
+    match event_id {
+        event_id::LOCAL_PMU_OVERFLOW => hart_extension_probe(hart_id, Extension::Sscofpmf),
+        _ => true,
+    }
+}
+
+/// Checks whether the hart exists and is enabled on this platform.
+#[inline]
+fn hart_is_valid(hart_id: usize) -> bool {
+    remote_hsm(hart_id).is_some()
+        && unsafe { PLATFORM.info.cpu_enabled }
+            .is_some_and(|list| list.get(hart_id).is_some_and(|enabled| *enabled))
+}
+
+/// Gets the range of attribute IDs to access.
+#[inline]
+fn attr_range(base_attr_id: u32, attr_count: u32) -> Result<Range<u32>, SbiRet> {
+    if attr_count == 0 {
+        return Err(SbiRet::invalid_param());
+    }
+    match base_attr_id.checked_add(attr_count) {
+        Some(end) if end <= ATTR_COUNT => Ok(base_attr_id..end),
+        _ => Err(SbiRet::bad_range()),
+    }
+}
+
+/// Checks the shared memory holding `attr_count` attribute values.
+fn shmem_ptr(shmem: SharedPtr<u8>, attr_count: u32) -> Result<*mut usize, SbiRet> {
+    let start = shmem.phys_addr_lo();
+    let size = attr_count as usize * size_of::<usize>();
+    let in_memory = unsafe { PLATFORM.shmem_accessible(start, size) };
+    if shmem.phys_addr_hi() != 0 || !start.is_multiple_of(size_of::<usize>()) || !in_memory {
+        return Err(SbiRet::invalid_address());
+    }
+    Ok(start as *mut usize)
+}
+
+/// Sends an IPI to let the hart deliver its pending events.
+fn send_sse_ipi(hart_id: usize) {
+    if !remote_hsm(hart_id).is_some_and(|hsm| hsm.allow_ipi()) {
+        return;
+    }
+    if set_ipi_type(hart_id, IPI_TYPE_SSE) == 0
+        && let Some(ipi) = unsafe { PLATFORM.sbi.ipi.as_ref() }
+    {
+        ipi.set_msip(hart_id);
+    }
+}
+
+/// Routes local counter overflow interrupts to machine mode to deliver them as events.
+#[inline]
+fn lcofi_claim() {
+    unsafe {
+        asm!("csrc mideleg, {}", in(reg) 1usize << LCOFI);
+        asm!("csrc mip, {}", in(reg) 1usize << LCOFI);
+        asm!("csrs mie, {}", in(reg) 1usize << LCOFI);
+    }
+}
+
+/// Returns local counter overflow interrupts to supervisor mode.
+#[inline]
+fn lcofi_release() {
+    unsafe {
+        asm!("csrc mie, {}", in(reg) 1usize << LCOFI);
+        asm!("csrc mip, {}", in(reg) 1usize << LCOFI);
+        asm!("csrs mideleg, {}", in(reg) 1usize << LCOFI);
+    }
+}
+
+/// Handle local counter overflow interrupts claimed by the local PMU overflow event.
+pub fn lcofi_handler(ctx: &mut FastContext) {
+    // Keep the interrupt disabled until the event is completed.
+    unsafe {
+        asm!("csrc mie, {}", in(reg) 1usize << LCOFI);
+        asm!("csrc mip, {}", in(reg) 1usize << LCOFI);
+    }
+    let sse = local_sse();
+    if let Some(event) = sse
+        .events
+        .iter_mut()
+        .find(|event| event.id == event_id::LOCAL_PMU_OVERFLOW)
+    {
+        event.pending = true;
+    }
+    deliver_pending(ctx);
+}
+
+/// Delivers the highest priority pending event on the current hart, if any.
+///
+/// Must be called right before returning to supervisor or user mode, after `mepc` and
+/// the argument registers in `ctx` are set to the context to be interrupted.
+pub fn deliver_pending(ctx: &mut FastContext) {
+    if unsafe { PLATFORM.sbi.sse.is_none() } || mstatus::read().mpp() == mstatus::MPP::Machine {
+        return;
+    }
+    let hart_id = current_hartid();
+    let sse = local_sse();
+    if sse.masked {
+        return;
+    }
+    sse.collect_injected();
+    let mut global = GLOBAL.lock();
+    let running = sse
+        .events
+        .iter()
+        .chain(global.iter())
+        .filter(|event| event.is_running_here(hart_id))
+        .map(|event| event.priority_key())
+        .min();
+    let Some(event) = sse
+        .events
+        .iter_mut()
+        .chain(global.iter_mut())
+        .filter(|event| event.is_ready_here(hart_id))
+        .min_by_key(|event| event.priority_key())
+    else {
+        return;
+    };
+    // Only an event of higher priority preempts a running one.
+    if running.is_some_and(|key| key <= event.priority_key()) {
+        return;
+    }
+
+    let a = &mut ctx.regs().a;
+    let sstatus_bits = sstatus::read().bits();
+    let mut flags = 0;
+    if sstatus_bits & SSTATUS_SPP != 0 {
+        flags |= interrupted_flags::SSTATUS_SPP;
+    }
+    if sstatus_bits & SSTATUS_SPIE != 0 {
+        flags |= interrupted_flags::SSTATUS_SPIE;
+    }
+    event.interrupted = [sepc::read(), flags, a[6], a[7]];
+    event.state = event_state::RUNNING;
+    event.pending = false;
+    event.hart_id = hart_id;
+
+    // Enter the handler as if trapped from the interrupted context.
+    let mut new_sstatus = sstatus_bits & !(SSTATUS_SIE | SSTATUS_SPIE | SSTATUS_SPP);
+    if mstatus::read().mpp() == mstatus::MPP::Supervisor {
+        new_sstatus |= SSTATUS_SPP;
+    }
+    if sstatus_bits & SSTATUS_SIE != 0 {
+        new_sstatus |= SSTATUS_SPIE;
+    }
+    a[6] = hart_id;
+    a[7] = event.entry_arg;
+    unsafe {
+        sepc::write(mepc::read());
+        asm!("csrw sstatus, {}", in(reg) new_sstatus);
+        mstatus::set_mpp(mstatus::MPP::Supervisor);
+    }
+    mepc::write(event.entry_pc);
+}
+
+/// Resumes the context interrupted by the event just completed.
+///
+/// `args` are `a0` to `a5` of the `complete` call, which the event handler has restored
+/// to the interrupted values. Returns `false` if no event was completed.
+pub fn resume_interrupted(ctx: &mut FastContext, args: [usize; 6]) -> bool {
+    let Some([interrupted_sepc, flags, a6, a7]) = local_sse().completed.take() else {
+        return false;
+    };
+    // Return from the handler as `sret` would ...
+    let sstatus_bits = sstatus::read().bits();
+    let mpp = if sstatus_bits & SSTATUS_SPP != 0 {
+        mstatus::MPP::Supervisor
+    } else {
+        mstatus::MPP::User
+    };
+    let mut new_sstatus = sstatus_bits & !(SSTATUS_SIE | SSTATUS_SPIE | SSTATUS_SPP);
+    if sstatus_bits & SSTATUS_SPIE != 0 {
+        new_sstatus |= SSTATUS_SIE;
+    }
+    // ... then restore the interrupted state saved on delivery.
+    if flags & interrupted_flags::SSTATUS_SPP != 0 {
+        new_sstatus |= SSTATUS_SPP;
+    }
+    if flags & interrupted_flags::SSTATUS_SPIE != 0 {
+        new_sstatus |= SSTATUS_SPIE;
+    }
+    mepc::write(sepc::read());
+    unsafe {
+        sepc::write(interrupted_sepc);
+        asm!("csrw sstatus, {}", in(reg) new_sstatus);
+        mstatus::set_mpp(mpp);
+    }
+    let [a0, a1, a2, a3, a4, a5] = args;
+    ctx.regs().a = [a0, a1, a2, a3, a4, a5, a6, a7];
+    true
+}
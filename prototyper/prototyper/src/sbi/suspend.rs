@@ -1,24 +1,49 @@
+use alloc::boxed::Box;
 use riscv::register::mstatus;
-use rustsbi::{Hsm, SbiRet};
-use sbi_spec::hsm::{hart_state::STOPPED, suspend_type::NON_RETENTIVE};
+use rustsbi::SbiRet;
+use sbi_spec::hsm::hart_state::STOPPED;
 
 use crate::{platform::PLATFORM, riscv::current_hartid};
 
-use super::hsm::remote_hsm;
+use super::hsm::{local_hsm, remote_hsm};
 
-const SUSPEND_TO_RAM: u32 = 0x0;
+pub(crate) const SUSPEND_TO_RAM: u32 = 0x0;
+
+/// Platform device entering system sleep states.
+pub trait SystemSuspendDevice {
+    /// Checks if the platform implements `sleep_type`.
+    fn supports(&self, sleep_type: u32) -> bool;
+
+    /// Puts the system to sleep with all other harts stopped, returning once a wake up
+    /// event arrives on the current hart.
+    ///
+    /// The device does not arm any wake up source; the supervisor must enable one, such as
+    /// a timer interrupt, before the request, or the system never wakes up.
+    ///
+    /// Returns `false` if the system could not enter the sleep state.
+    fn suspend(&self, sleep_type: u32) -> bool;
+}
 
 /// Implementation of SBI System Suspend Extension extension.
-pub(crate) struct SbiSuspend;
+pub(crate) struct SbiSuspend {
+    device: Box<dyn SystemSuspendDevice>,
+}
+
+impl SbiSuspend {
+    pub fn new(device: Box<dyn SystemSuspendDevice>) -> Self {
+        Self { device }
+    }
+}
 
 impl rustsbi::Susp for SbiSuspend {
     fn system_suspend(&self, sleep_type: u32, resume_addr: usize, opaque: usize) -> SbiRet {
-        if sleep_type != SUSPEND_TO_RAM {
+        // Reserved and unimplemented sleep types are both invalid.
+        if !self.device.supports(sleep_type) {
             return SbiRet::invalid_param();
         }
 
         let prev_mode = mstatus::read().mpp();
-        if prev_mode != mstatus::MPP::Supervisor || prev_mode != mstatus::MPP::User {
+        if prev_mode != mstatus::MPP::Supervisor && prev_mode != mstatus::MPP::User {
             return SbiRet::failed();
         }
 
@@ -41,15 +66,20 @@ impl rustsbi::Susp for SbiSuspend {
             }
         }
 
-        // TODO: The validity of `resume_addr` should be checked.
-        // If it is invalid, `SBI_ERR_INVALID_ADDRESS` should be returned.
+        // The supervisor resumes from memory it can execute.
+        if !unsafe { PLATFORM.shmem_accessible(resume_addr, 1) } {
+            return SbiRet::invalid_address();
+        }
 
-        if let Some(hsm) = unsafe { &PLATFORM.sbi.hsm } {
-            hsm.hart_suspend(NON_RETENTIVE, resume_addr, opaque);
-        } else {
+        let Some(hsm) = (unsafe { &PLATFORM.sbi.hsm }) else {
             return SbiRet::not_supported();
+        };
+        local_hsm().suspend();
+        if !self.device.suspend(sleep_type) {
+            local_hsm().resume();
+            return SbiRet::failed();
         }
-
-        unreachable!();
+        // Resume like a hart from non-retentive suspend.
+        hsm.hart_resume(current_hartid(), resume_addr, opaque)
     }
 }
@@ -1,19 +1,30 @@
+use core::arch::asm;
 use fast_trap::{EntireContext, EntireContextSeparated, EntireResult, FastContext, FastResult};
-use riscv::register::{mepc, mie, mstatus, mtval, satp, sstatus};
+use riscv::register::{mepc, mie, misa, mstatus, mtval, satp, sstatus};
 use riscv_decode::{Instruction, decode};
 use rustsbi::RustSBI;
 use sbi_spec::pmu::firmware_event;
 
 use crate::platform::PLATFORM;
-use crate::riscv::csr::{CSR_TIME, CSR_TIMEH};
+use crate::riscv::csr::{CSR_TIME, CSR_TIMEH, vector};
 use crate::riscv::current_hartid;
 use crate::sbi::console;
+use crate::sbi::early_trap::TrapInfo;
 use crate::sbi::hsm::local_hsm;
 use crate::sbi::ipi;
 use crate::sbi::pmu::pmu_firmware_counter_increment;
 use crate::sbi::rfence;
+use crate::sbi::sse;
 
 use super::helper::*;
+use super::misaligned::{self, Access, Register, Vector, Xlen};
+
+const SSTATUS_SPIE: usize = 1 << 5;
+
+#[cfg(target_pointer_width = "64")]
+const XLEN: Xlen = Xlen::Rv64;
+#[cfg(target_pointer_width = "32")]
+const XLEN: Xlen = Xlen::Rv32;
 
 #[inline]
 pub fn switch(mut ctx: FastContext, start_addr: usize, opaque: usize) -> FastResult {
@@ -48,7 +59,7 @@ pub fn msoft_ipi_handler() {
 }
 
 #[inline]
-pub fn msoft_handler(ctx: FastContext) -> FastResult {
+pub fn msoft_handler(mut ctx: FastContext) -> FastResult {
     match local_hsm().start() {
         // Handle HSM Start
         Ok(next_stage) => {
@@ -70,9 +81,10 @@ pub fn msoft_handler(ctx: FastContext) -> FastResult {
             riscv::asm::wfi();
             ctx.restore()
         }
-        // Handle IPI and RFence
+        // Handle IPI, RFence and SSE
         _ => {
             msoft_ipi_handler();
+            sse::deliver_pending(&mut ctx);
             ctx.restore()
         }
     }
@@ -90,7 +102,7 @@ pub fn sbi_call_handler(
     a6: usize,
     a7: usize,
 ) -> FastResult {
-    use sbi_spec::{base, hsm, legacy};
+    use sbi_spec::{base, hsm, legacy, sse as sse_spec};
     let mut ret = unsafe {
         PLATFORM
             .sbi
@@ -113,6 +125,13 @@ pub fn sbi_call_handler(
             {
                 ret.value = 1;
             }
+            // Resume the context interrupted by the completed event
+            (sse_spec::EID_SSE, sse_spec::COMPLETE)
+                if sse::resume_interrupted(&mut ctx, [ctx.a0(), a1, a2, a3, a4, a5]) =>
+            {
+                sse::deliver_pending(&mut ctx);
+                return ctx.restore();
+            }
             _ => {}
         }
     } else {
@@ -129,19 +148,33 @@ pub fn sbi_call_handler(
         }
     }
     ctx.regs().a = [ret.error, ret.value, a2, a3, a4, a5, a6, a7];
-    let epc = mepc::read();
-    mepc::write(epc + get_inst(epc).1);
+    // `ecall` has no compressed form.
+    mepc::write(mepc::read() + 4);
+    sse::deliver_pending(&mut ctx);
     ctx.restore()
 }
 
 /// Delegate trap handling to supervisor mode.
 #[inline]
 pub fn delegate(ctx: &mut EntireContextSeparated) {
-    use riscv::register::{mcause, scause, sepc, sstatus, stval, stvec};
+    use riscv::register::mcause;
+    delegate_with(ctx, mcause::read().bits(), mtval::read());
+}
+
+/// Delegate a trap with the given cause and value to supervisor mode.
+#[inline]
+pub fn delegate_with(ctx: &mut EntireContextSeparated, cause: usize, tval: usize) {
+    use riscv::register::{scause, sepc, sstatus, stval, stvec};
     unsafe {
         sepc::write(ctx.regs().pc);
-        scause::write(mcause::read().bits());
-        stval::write(mtval::read());
+        scause::write(cause);
+        stval::write(tval);
+        // Keep the interrupt enable bit as a trap taken by the supervisor would.
+        if sstatus::read().sie() {
+            asm!("csrs sstatus, {}", in(reg) SSTATUS_SPIE);
+        } else {
+            asm!("csrc sstatus, {}", in(reg) SSTATUS_SPIE);
+        }
         sstatus::clear_sie();
         if mstatus::read().mpp() == mstatus::MPP::Supervisor {
             sstatus::set_spp(sstatus::SPP::Supervisor);
@@ -153,6 +186,19 @@ pub fn delegate(ctx: &mut EntireContextSeparated) {
     }
 }
 
+/// Redirect traps from supervisor or user mode which the firmware doesn't handle.
+#[inline]
+pub extern "C" fn redirect_handler(ctx: EntireContext) -> EntireResult {
+    let mut ctx = ctx.split().0;
+    debug!(
+        "Redirect trap {:#x} at {:#x} to supervisor",
+        riscv::register::mcause::read().bits(),
+        ctx.regs().pc
+    );
+    delegate(&mut ctx);
+    ctx.restore()
+}
+
 /// Handle illegal instructions, particularly CSR access.
 #[inline]
 pub extern "C" fn illegal_instruction_handler(raw_ctx: EntireContext) -> EntireResult {
@@ -185,8 +231,9 @@ pub extern "C" fn illegal_instruction_handler(raw_ctx: EntireContext) -> EntireR
             return ctx.restore();
         }
     }
-    let epc = mepc::read();
-    mepc::write(epc + get_inst(epc).1);
+    // `csrrs` has no compressed form.
+    mepc::write(mepc::read() + 4);
+    sse::deliver_pending(&mut ctx);
     ctx.restore()
 }
 
@@ -196,57 +243,52 @@ pub extern "C" fn load_misaligned_handler(ctx: EntireContext) -> EntireResult {
     let current_pc = mepc::read();
     let current_addr = mtval::read();
 
-    let (current_inst, inst_len) = get_inst(current_pc);
+    let (current_inst, inst_len) = match get_inst(current_pc) {
+        Ok(inst) => inst,
+        Err(trap_info) => return forward_fault(ctx, trap_info),
+    };
     debug!(
         "Misaligned load: inst/{:x?}, load {:x?} in {:x?}",
         current_inst, current_addr, current_pc
     );
-    let decode_result = decode(current_inst as u32);
 
-    // TODO: INST FLD c.*sp
     // TODO: maybe can we reduce the time to update csr for read virtual-address.
-    let inst_type = match decode_result {
-        Ok(Instruction::Lb(data)) => (data.rd(), VarType::Signed, 1),
-        Ok(Instruction::Lbu(data)) => (data.rd(), VarType::UnSigned, 1),
-        Ok(Instruction::Lh(data)) => (data.rd(), VarType::Signed, 2),
-        Ok(Instruction::Lhu(data)) => (data.rd(), VarType::UnSigned, 2),
-        Ok(Instruction::Lw(data)) => (data.rd(), VarType::Signed, 4),
-        Ok(Instruction::Lwu(data)) => (data.rd(), VarType::UnSigned, 4),
-        Ok(Instruction::Ld(data)) => (data.rd(), VarType::Signed, 8),
-        Ok(Instruction::Flw(data)) => (data.rd(), VarType::Float, 4),
-        _ => panic!("Unsupported inst"),
+    let (target_reg, len, signed) = match misaligned::decode(current_inst as u32, XLEN) {
+        Some(Access::Load { rd, width, signed }) => (rd, width, signed),
+        Some(Access::Vector(vector)) if !vector.store && vector_emulated(&vector) => {
+            if let Err(trap_info) = vector_misaligned(&mut ctx, vector) {
+                return forward_fault(ctx, trap_info);
+            }
+            mepc::write(current_pc + inst_len);
+            return ctx.restore();
+        }
+        _ if mstatus::read().mpp() == mstatus::MPP::Machine => panic!("Unsupported inst"),
+        // Let the supervisor handle instructions that can't be emulated.
+        _ => {
+            delegate(&mut ctx);
+            return ctx.restore();
+        }
     };
-    let (target_reg, var_type, len) = inst_type;
-    let raw_data = get_data(current_addr, len);
-    let read_data = match var_type {
-        VarType::Signed => match len {
-            1 => raw_data as i8 as usize,
-            2 => raw_data as i16 as usize,
-            4 => raw_data as i32 as usize,
-            8 => raw_data as i64 as usize,
-            _ => panic!("Invalid len"),
-        },
-        VarType::UnSigned => match len {
-            1 => raw_data as u8 as usize,
-            2 => raw_data as u16 as usize,
-            4 => raw_data as u32 as usize,
-            8 => raw_data as u64 as usize,
-            _ => panic!("Invalid len"),
-        },
-        VarType::Float => match len {
-            4 => raw_data as u32 as usize,
-            8 => raw_data as u64 as usize,
-            _ => panic!("Invalid len"),
-        },
+    let raw_data = match get_data(current_addr, len) {
+        Ok(data) => data,
+        Err(trap_info) => return forward_fault(ctx, trap_info),
     };
     debug!(
-        "read 0x{:x} from 0x{:x} to x{}, len 0x{:x}",
-        read_data, current_addr, target_reg, len
+        "read 0x{:x} from 0x{:x} to {:?}, len 0x{:x}",
+        raw_data, current_addr, target_reg, len
     );
-    match var_type {
-        VarType::Signed | VarType::UnSigned => save_reg_x(&mut ctx, target_reg as usize, read_data),
-        VarType::Float => set_reg_f(target_reg as usize, len, read_data),
-    };
+    match target_reg {
+        Register::X(rd) => save_reg_x(
+            &mut ctx,
+            rd,
+            misaligned::extend(raw_data as u64, len, signed) as usize,
+        ),
+        // Half-precision values are NaN-boxed like single-precision ones.
+        Register::F(rd) if len == 2 => {
+            set_reg_f(rd, 4, misaligned::nan_box(raw_data as u64, 2, 4) as usize)
+        }
+        Register::F(rd) => set_reg_f(rd, len, raw_data),
+    }
     mepc::write(current_pc + inst_len);
     ctx.restore()
 }
@@ -257,56 +299,142 @@ pub extern "C" fn store_misaligned_handler(ctx: EntireContext) -> EntireResult {
     let current_pc = mepc::read();
     let current_addr = mtval::read();
 
-    let (current_inst, inst_len) = get_inst(current_pc);
+    let (current_inst, inst_len) = match get_inst(current_pc) {
+        Ok(inst) => inst,
+        Err(trap_info) => return forward_fault(ctx, trap_info),
+    };
     debug!(
         "Misaligned store: inst/{:x?}, store {:x?} in {:x?}",
         current_inst, current_addr, current_pc
     );
 
-    let decode_result = decode(current_inst as u32);
-
-    // TODO: INST FSD c.*sp
     // TODO: maybe can we reduce the time to update csr for read virtual-address.
-    let inst_type = match decode_result {
-        Ok(Instruction::Sb(data)) => (data.rs2(), VarType::UnSigned, 1),
-        Ok(Instruction::Sh(data)) => (data.rs2(), VarType::UnSigned, 2),
-        Ok(Instruction::Sw(data)) => (data.rs2(), VarType::UnSigned, 4),
-        Ok(Instruction::Sd(data)) => (data.rs2(), VarType::UnSigned, 8),
-        Ok(Instruction::Fsw(data)) => (data.rs2(), VarType::Float, 4),
-        _ => panic!("Unsupported inst"),
-    };
-    let (target_reg, var_type, len) = inst_type;
-    let raw_data = match var_type {
-        VarType::Signed | VarType::UnSigned => get_reg_x(&mut ctx, target_reg as usize),
-        VarType::Float => get_reg_f(target_reg as usize, len),
+    let (target_reg, len) = match misaligned::decode(current_inst as u32, XLEN) {
+        Some(Access::Store { rs2, width }) => (rs2, width),
+        Some(Access::Vector(vector)) if vector.store && vector_emulated(&vector) => {
+            if let Err(trap_info) = vector_misaligned(&mut ctx, vector) {
+                return forward_fault(ctx, trap_info);
+            }
+            mepc::write(current_pc + inst_len);
+            return ctx.restore();
+        }
+        _ if mstatus::read().mpp() == mstatus::MPP::Machine => panic!("Unsupported inst"),
+        // Let the supervisor handle instructions that can't be emulated.
+        _ => {
+            delegate(&mut ctx);
+            return ctx.restore();
+        }
     };
-
-    let read_data = match var_type {
-        VarType::Signed => match len {
-            _ => panic!("Can not store signed data"),
-        },
-        VarType::UnSigned => match len {
-            1 => &(raw_data as u8).to_le_bytes()[..],
-            2 => &(raw_data as u16).to_le_bytes()[..],
-            4 => &(raw_data as u32).to_le_bytes()[..],
-            8 => &(raw_data as u64).to_le_bytes()[..],
-            _ => panic!("Invalid len"),
-        },
-        VarType::Float => match len {
-            4 => &(raw_data as u32).to_le_bytes()[..],
-            8 => &(raw_data as u64).to_le_bytes()[..],
-            _ => panic!("Invalid len"),
-        },
+    let raw_data = match target_reg {
+        Register::X(rs2) => get_reg_x(&mut ctx, rs2),
+        // Half-precision values are the low bits of single-precision ones.
+        Register::F(rs2) => get_reg_f(rs2, len.max(4)),
     };
 
     debug!(
         "save 0x{:x} to 0x{:x}, len 0x{:x}",
         raw_data, current_addr, len
     );
-    for i in 0..read_data.len() {
-        save_byte(current_addr + i, read_data[i] as usize);
+    for (i, byte) in raw_data.to_le_bytes()[..len].iter().enumerate() {
+        if let Err(trap_info) = save_byte(current_addr + i, *byte as usize) {
+            return forward_fault(ctx, trap_info);
+        }
     }
 
     mepc::write(current_pc + inst_len);
     ctx.restore()
 }
+
+/// Forwards a fault of an access emulated for the trapped mode, as if the access raised it.
+///
+/// Faults of the firmware itself are fatal.
+fn forward_fault(mut ctx: EntireContextSeparated, trap_info: TrapInfo) -> EntireResult {
+    if mstatus::read().mpp() == mstatus::MPP::Machine {
+        panic!(
+            "Fault {:#x} when emulating access to {:#x}",
+            trap_info.mcause, trap_info.mtval
+        );
+    }
+    debug!(
+        "Forward fault {:#x} at {:#x} to supervisor",
+        trap_info.mcause, trap_info.mtval
+    );
+    delegate_with(&mut ctx, trap_info.mcause, trap_info.mtval);
+    ctx.restore()
+}
+
+/// Largest `vlenb` of emulated vector accesses, for VLEN up to 1024 bits.
+const MAX_VLENB: usize = 128;
+/// Largest number of registers of an emulated vector access, as EMUL is at most 8.
+const MAX_VECTOR_REGISTERS: usize = 8;
+
+/// Whether `access` fits the buffers of `vector_misaligned` on the current hart.
+#[inline]
+fn vector_emulated(access: &Vector) -> bool {
+    misa::read().is_some_and(|misa| misa.has_extension('V'))
+        && vector::vlenb() <= MAX_VLENB
+        && access.registers(vector::vtype()) <= MAX_VECTOR_REGISTERS
+}
+
+/// Emulates a misaligned vector unit-stride load or store, from the element in `vstart` on.
+///
+/// Masked off and tail elements are left undisturbed. On a fault, `vstart` holds the
+/// faulting element, or fault-only-first loads after the first element trim `vl` instead.
+fn vector_misaligned(ctx: &mut EntireContextSeparated, access: Vector) -> Result<(), TrapInfo> {
+    let vstart = vector::vstart();
+    let (vl, vtype, vlenb) = (vector::vl(), vector::vtype(), vector::vlenb());
+    debug!(
+        "Misaligned vector access: {:x?}, vstart {}, vl {}, vtype 0x{:x}",
+        access, vstart, vl, vtype
+    );
+    // Whole register moves below only touch the elements from `vstart` on.
+    vector::set_vstart(0);
+    let mut buf = [0u8; MAX_VECTOR_REGISTERS * MAX_VLENB];
+    let registers = &mut buf[..access.registers(vtype) * vlenb];
+    for (i, chunk) in registers.chunks_exact_mut(vlenb).enumerate() {
+        get_reg_v(access.vd + i, chunk);
+    }
+    let mut mask = [0u8; MAX_VLENB];
+    if access.masked {
+        get_reg_v(0, &mut mask[..vlenb]);
+    }
+
+    let base = get_reg_x(ctx, access.rs1);
+    let mut result = Ok(());
+    'elements: for index in vstart..vl {
+        if access.masked && mask[index / 8] & (1 << (index % 8)) == 0 {
+            continue;
+        }
+        let offset = index * access.eew;
+        for (i, byte) in registers[offset..offset + access.eew]
+            .iter_mut()
+            .enumerate()
+        {
+            let access_result = if access.store {
+                save_byte(base + offset + i, *byte as usize)
+            } else {
+                get_unsigned_byte(base + offset + i).map(|value| *byte = value)
+            };
+            if let Err(trap_info) = access_result {
+                if access.fault_only_first && index > 0 {
+                    vector::set_vl(index, vtype);
+                } else {
+                    vector::set_vstart(index);
+                    result = Err(trap_info);
+                }
+                break 'elements;
+            }
+        }
+    }
+
+    if !access.store {
+        // Keep `vstart` of the fault, as whole register moves would skip elements before it.
+        let vstart = vector::vstart();
+        vector::set_vstart(0);
+        for (i, chunk) in registers.chunks_exact(vlenb).enumerate() {
+            set_reg_v(access.vd + i, chunk);
+        }
+        vector::set_vstart(vstart);
+    }
+    result
+}
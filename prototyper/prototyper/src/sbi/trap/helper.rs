@@ -1,17 +1,13 @@
 use core::arch::asm;
-use riscv::register::{mtvec, sscratch};
+use riscv::register::{mepc, mtvec, sscratch};
 
 use fast_trap::EntireContextSeparated;
 
+use crate::sbi::early_trap::{TrapInfo, expected_trap};
+
 const MPRV_BIT: usize = 1usize << 17;
 const MXR_BIT: usize = 1usize << 19;
 
-pub enum VarType {
-    Signed,
-    UnSigned,
-    Float,
-}
-
 #[inline(always)]
 pub fn read_gp() -> usize {
     let mut data: usize;
@@ -36,45 +32,84 @@ pub fn write_tp(data: usize) {
     unsafe { asm!("mv tp, {}", in(reg) data, options(nomem)) };
 }
 
-// If inline this and next function will cause crash. It looks like magic.
+/// Faults an access of the trapped mode may raise, as access, page and guest page faults.
+const LOAD_FAULTS: [usize; 3] = [5, 13, 21];
+const STORE_FAULTS: [usize; 3] = [7, 15, 23];
+const INSTRUCTION_FAULTS: [usize; 3] = [1, 12, 20];
+
+/// Checks the trap taken by an access through `expected_trap`, if any.
+///
+/// The trap overwrites `mepc` of the trap being handled, which is restored to `prev_mepc`.
+/// Traps other than `faults` are not caused by the trapped mode, and are fatal.
+#[inline(always)]
+fn expected_trap_result(
+    trap_info: TrapInfo,
+    prev_mepc: usize,
+    faults: &[usize],
+) -> Result<(), TrapInfo> {
+    if trap_info.mcause == usize::MAX {
+        return Ok(());
+    }
+    mepc::write(prev_mepc);
+    if !faults.contains(&trap_info.mcause) {
+        panic!(
+            "Unexpected trap {:#x} at {:#x} when accessing {:#x}",
+            trap_info.mcause, trap_info.mepc, trap_info.mtval
+        );
+    }
+    Err(trap_info)
+}
+
+/// Loads a byte with `MPRV` and the mode in `MPP`, and the other `mstatus` bits in `bits`.
+// If inline this and `save_byte` will cause crash. It looks like magic.
 #[inline(never)]
-pub fn get_unsigned_byte(addr: usize) -> u8 {
-    let mut data: usize = 0;
-    let mut status: usize = 0;
+fn load_byte(addr: usize, bits: usize) -> Result<u8, TrapInfo> {
+    let data: usize;
+    let mut trap_info = TrapInfo {
+        mcause: usize::MAX,
+        ..Default::default()
+    };
+    let prev_mepc = mepc::read();
     unsafe {
         let prev_mtvec = mtvec::read().bits();
-        mtvec::write(
-            crate::sbi::early_trap::expected_trap as _,
-            mtvec::TrapMode::Direct,
-        );
+        mtvec::write(expected_trap as *const () as _, mtvec::TrapMode::Direct);
         asm!(
             "csrrs t3, mstatus, t3",
             "lbu t0, 0(t1)",
             "csrw mstatus, t3",
             "csrw mtvec, t4",
             in("t1") addr,
-            in("t3") MPRV_BIT | MXR_BIT,
-            in("a1") 0,
+            inout("t3") MPRV_BIT | bits => _,
             in("t4") prev_mtvec,
-            inout("t0") data,
-            inout("a0") status,
+            in("a3") &raw mut trap_info,
+            out("a4") _,
+            out("t0") data,
         );
-        if status != 0 {
-            panic!("Error when load byte");
-        }
     }
-    data as u8
+    expected_trap_result(trap_info, prev_mepc, &LOAD_FAULTS).map(|()| data as u8)
+}
+
+/// Loads a byte as the trapped mode would, with `MPRV` and the mode in `MPP`.
+///
+/// Returns the load or page fault raised by the access, with its address in `mtval`.
+#[inline(always)]
+pub fn get_unsigned_byte(addr: usize) -> Result<u8, TrapInfo> {
+    load_byte(addr, 0)
 }
 
+/// Stores a byte as the trapped mode would, with `MPRV` and the mode in `MPP`.
+///
+/// Returns the store or page fault raised by the access, with its address in `mtval`.
 #[inline(never)]
-pub fn save_byte(addr: usize, data: usize) {
+pub fn save_byte(addr: usize, data: usize) -> Result<(), TrapInfo> {
+    let mut trap_info = TrapInfo {
+        mcause: usize::MAX,
+        ..Default::default()
+    };
+    let prev_mepc = mepc::read();
     unsafe {
         let prev_mtvec = mtvec::read().bits();
-        let mut status: usize = 0;
-        mtvec::write(
-            crate::sbi::early_trap::expected_trap as _,
-            mtvec::TrapMode::Direct,
-        );
+        mtvec::write(expected_trap as *const () as _, mtvec::TrapMode::Direct);
         asm!(
               "csrrs t3, mstatus, t3",
               "sb t0, 0(t1)",
@@ -83,34 +118,51 @@ pub fn save_byte(addr: usize, data: usize) {
             in("t0") data,
             in("t1") addr,
             in("t4") prev_mtvec,
-            in("a1") 0,
-            in("t3") MPRV_BIT | MXR_BIT,
-            inout("a0") status,
+            inout("t3") MPRV_BIT => _,
+            in("a3") &raw mut trap_info,
+            out("a4") _,
         );
-        if status != 0 {
-            panic!("Error when save byte");
-        }
     }
+    expected_trap_result(trap_info, prev_mepc, &STORE_FAULTS)
 }
 
+/// Loads `len` bytes from `addr` on, with the `mstatus` bits in `bits` as `load_byte`.
+///
+/// Bytes are loaded in address order, so a fault reports the lowest faulting address.
 #[inline(always)]
-pub fn get_data(addr: usize, len: usize) -> usize {
+fn load_data(addr: usize, len: usize, bits: usize) -> Result<usize, TrapInfo> {
     let mut data: usize = 0;
-    for i in (addr..addr + len).rev() {
-        data <<= 8;
-        data |= get_unsigned_byte(i) as usize;
+    for i in 0..len {
+        data |= (load_byte(addr + i, bits)? as usize) << (i * 8);
     }
-    data
+    Ok(data)
+}
+
+#[inline(always)]
+pub fn get_data(addr: usize, len: usize) -> Result<usize, TrapInfo> {
+    load_data(addr, len, 0)
 }
 
+/// Reads the instruction at `addr` and its length.
+///
+/// Only the fetch sets `MXR`, so that execute-only pages can be read. Faults are reported
+/// as the instruction faults fetching it would raise, with the faulting address in `mtval`.
 #[inline(always)]
-pub fn get_inst(addr: usize) -> (usize, usize) {
-    let low_data = get_data(addr, 2);
+pub fn get_inst(addr: usize) -> Result<(usize, usize), TrapInfo> {
+    let fetch = |addr| {
+        load_data(addr, 2, MXR_BIT).map_err(|mut trap_info| {
+            // `load_byte` only returns load faults, each mapped to its instruction fault.
+            let kind = LOAD_FAULTS.iter().position(|&c| c == trap_info.mcause);
+            trap_info.mcause = INSTRUCTION_FAULTS[kind.unwrap()];
+            trap_info
+        })
+    };
+    let low_data = fetch(addr)?;
     // We assume we only have 16bit and 32bit inst.
     if riscv_decode::instruction_length(low_data as u16) == 2 {
-        return (low_data, 2);
+        Ok((low_data, 2))
     } else {
-        return (low_data | (get_data(addr + 2, 2) << 16), 4);
+        Ok((low_data | (fetch(addr + 2)? << 16), 4))
     }
 }
 
@@ -213,3 +265,49 @@ pub fn set_reg_f(reg_id: usize, len: usize, value: usize) {
         _ => todo!(),
     }
 }
+
+/// Stores the vector register `reg_id` into `buf`, which holds `vlenb` bytes.
+///
+/// `vstart` must be zero, as whole register stores skip the elements before it.
+pub fn get_reg_v(reg_id: usize, buf: &mut [u8]) {
+    seq_macro::seq!(N in 0..32 {
+        match reg_id {
+            #(
+                N => unsafe {
+                    asm!(
+                        ".option push",
+                        ".option arch, +v",
+                        "vs1r.v v{x}, ({buf})",
+                        ".option pop",
+                        buf = in(reg) buf.as_mut_ptr(),
+                        x = const N,
+                    )
+                },
+            )*
+            _ => unreachable!()
+        }
+    });
+}
+
+/// Loads the vector register `reg_id` from `buf`, which holds `vlenb` bytes.
+///
+/// `vstart` must be zero, as whole register loads skip the elements before it.
+pub fn set_reg_v(reg_id: usize, buf: &[u8]) {
+    seq_macro::seq!(N in 0..32 {
+        match reg_id {
+            #(
+                N => unsafe {
+                    asm!(
+                        ".option push",
+                        ".option arch, +v",
+                        "vl1re8.v v{x}, ({buf})",
+                        ".option pop",
+                        buf = in(reg) buf.as_ptr(),
+                        x = const N,
+                    )
+                },
+            )*
+            _ => unreachable!()
+        }
+    });
+}
@@ -0,0 +1,419 @@
+//! Decoding of loads and stores trapped by address-misaligned exceptions.
+//!
+//! Supported instructions are base integer loads and stores, floating-point ones of the
+//! F, D and Zfh extensions, compressed ones of the C extension, and vector unit-stride
+//! ones. Decoding doesn't touch CSRs or registers, so that it can be tested on the host.
+
+/// Base integer ISA width of the hart, which changes the meaning of some compressed instructions.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Xlen {
+    /// RV32, where `c.flw` and `c.fsw` take the encodings of `c.ld` and `c.sd`.
+    Rv32,
+    /// RV64.
+    Rv64,
+}
+
+/// Register read or written by a scalar load or store.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Register {
+    /// Integer register `x0` to `x31`.
+    X(usize),
+    /// Floating-point register `f0` to `f31`.
+    F(usize),
+}
+
+/// Decoded load or store instruction.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Access {
+    /// Scalar load of `width` bytes into `rd`.
+    Load {
+        /// Destination register.
+        rd: Register,
+        /// Number of bytes loaded.
+        width: usize,
+        /// Whether the value is sign-extended into an integer register.
+        signed: bool,
+    },
+    /// Scalar store of the lowest `width` bytes of `rs2`.
+    Store {
+        /// Source register.
+        rs2: Register,
+        /// Number of bytes stored.
+        width: usize,
+    },
+    /// Vector unit-stride load or store.
+    Vector(Vector),
+}
+
+/// Decoded vector unit-stride load or store.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Vector {
+    /// Whether the instruction is a store.
+    pub store: bool,
+    /// First destination register of loads, or first source register of stores.
+    pub vd: usize,
+    /// Integer register holding the base address.
+    pub rs1: usize,
+    /// Element width in bytes.
+    pub eew: usize,
+    /// Whether active elements are selected by the mask in `v0`.
+    pub masked: bool,
+    /// Whether the load only traps on its first element, trimming `vl` on later faults.
+    pub fault_only_first: bool,
+}
+
+const OPCODE_LOAD: u32 = 0b000_0011;
+const OPCODE_LOAD_FP: u32 = 0b000_0111;
+const OPCODE_STORE: u32 = 0b010_0011;
+const OPCODE_STORE_FP: u32 = 0b010_0111;
+
+/// Vector `lumop` and `sumop` values.
+const UNIT_STRIDE: u32 = 0b00000;
+const FAULT_ONLY_FIRST: u32 = 0b10000;
+
+/// Decodes a load or store instruction, `None` if it is not supported.
+///
+/// Compressed instructions are given in the lowest 16 bits of `inst`.
+pub fn decode(inst: u32, xlen: Xlen) -> Option<Access> {
+    if inst & 0b11 != 0b11 {
+        return decode_compressed(inst as u16, xlen);
+    }
+    let rd = (inst >> 7) as usize & 0x1f;
+    let rs1 = (inst >> 15) as usize & 0x1f;
+    let rs2 = (inst >> 20) as usize & 0x1f;
+    let funct3 = (inst >> 12) & 0b111;
+    let load = |rd, width, signed| Some(Access::Load { rd, width, signed });
+    let store = |rs2, width| Some(Access::Store { rs2, width });
+    match (inst & 0x7f, funct3) {
+        (OPCODE_LOAD, 0b000) => load(Register::X(rd), 1, true),
+        (OPCODE_LOAD, 0b001) => load(Register::X(rd), 2, true),
+        (OPCODE_LOAD, 0b010) => load(Register::X(rd), 4, true),
+        (OPCODE_LOAD, 0b011) if xlen == Xlen::Rv64 => load(Register::X(rd), 8, true),
+        (OPCODE_LOAD, 0b100) => load(Register::X(rd), 1, false),
+        (OPCODE_LOAD, 0b101) => load(Register::X(rd), 2, false),
+        (OPCODE_LOAD, 0b110) if xlen == Xlen::Rv64 => load(Register::X(rd), 4, false),
+        (OPCODE_STORE, 0b000) => store(Register::X(rs2), 1),
+        (OPCODE_STORE, 0b001) => store(Register::X(rs2), 2),
+        (OPCODE_STORE, 0b010) => store(Register::X(rs2), 4),
+        (OPCODE_STORE, 0b011) if xlen == Xlen::Rv64 => store(Register::X(rs2), 8),
+        (OPCODE_LOAD_FP, 0b001) => load(Register::F(rd), 2, false),
+        (OPCODE_LOAD_FP, 0b010) => load(Register::F(rd), 4, false),
+        (OPCODE_LOAD_FP, 0b011) => load(Register::F(rd), 8, false),
+        (OPCODE_STORE_FP, 0b001) => store(Register::F(rs2), 2),
+        (OPCODE_STORE_FP, 0b010) => store(Register::F(rs2), 4),
+        (OPCODE_STORE_FP, 0b011) => store(Register::F(rs2), 8),
+        (OPCODE_LOAD_FP | OPCODE_STORE_FP, 0b000 | 0b101 | 0b110 | 0b111) => {
+            decode_vector(inst, rd, rs1, funct3)
+        }
+        _ => None,
+    }
+}
+
+fn decode_vector(inst: u32, vd: usize, rs1: usize, width: u32) -> Option<Access> {
+    let store = inst & 0x7f == OPCODE_STORE_FP;
+    let eew = match width {
+        0b000 => 1,
+        0b101 => 2,
+        0b110 => 4,
+        _ => 8,
+    };
+    let nf = (inst >> 29) & 0b111;
+    let mew = (inst >> 28) & 1;
+    let mop = (inst >> 26) & 0b11;
+    // Segment, strided and indexed accesses are not emulated.
+    if nf != 0 || mew != 0 || mop != 0 {
+        return None;
+    }
+    let fault_only_first = match (inst >> 20) & 0x1f {
+        UNIT_STRIDE => false,
+        FAULT_ONLY_FIRST if !store => true,
+        _ => return None,
+    };
+    Some(Access::Vector(Vector {
+        store,
+        vd,
+        rs1,
+        eew,
+        masked: (inst >> 25) & 1 == 0,
+        fault_only_first,
+    }))
+}
+
+fn decode_compressed(inst: u16, xlen: Xlen) -> Option<Access> {
+    // Registers `x8` to `x15` of the CL and CS formats.
+    let rd_prime = ((inst >> 2) & 0b111) as usize + 8;
+    // Full registers of the CI and CSS formats.
+    let rd = ((inst >> 7) & 0x1f) as usize;
+    let rs2 = ((inst >> 2) & 0x1f) as usize;
+    let load = |rd, width| {
+        Some(Access::Load {
+            rd,
+            width,
+            signed: true,
+        })
+    };
+    let store = |rs2, width| Some(Access::Store { rs2, width });
+    let rv64 = xlen == Xlen::Rv64;
+    match (inst & 0b11, inst >> 13) {
+        // c.fld
+        (0b00, 0b001) => load(Register::F(rd_prime), 8),
+        // c.lw
+        (0b00, 0b010) => load(Register::X(rd_prime), 4),
+        // c.ld or c.flw
+        (0b00, 0b011) if rv64 => load(Register::X(rd_prime), 8),
+        (0b00, 0b011) => load(Register::F(rd_prime), 4),
+        // c.fsd
+        (0b00, 0b101) => store(Register::F(rd_prime), 8),
+        // c.sw
+        (0b00, 0b110) => store(Register::X(rd_prime), 4),
+        // c.sd or c.fsw
+        (0b00, 0b111) if rv64 => store(Register::X(rd_prime), 8),
+        (0b00, 0b111) => store(Register::F(rd_prime), 4),
+        // c.fldsp
+        (0b10, 0b001) => load(Register::F(rd), 8),
+        // c.lwsp
+        (0b10, 0b010) if rd != 0 => load(Register::X(rd), 4),
+        // c.ldsp or c.flwsp
+        (0b10, 0b011) if rv64 && rd != 0 => load(Register::X(rd), 8),
+        (0b10, 0b011) if !rv64 => load(Register::F(rd), 4),
+        // c.fsdsp
+        (0b10, 0b101) => store(Register::F(rs2), 8),
+        // c.swsp
+        (0b10, 0b110) => store(Register::X(rs2), 4),
+        // c.sdsp or c.fswsp
+        (0b10, 0b111) if rv64 => store(Register::X(rs2), 8),
+        (0b10, 0b111) => store(Register::F(rs2), 4),
+        _ => None,
+    }
+    .map(|access| match access {
+        // Compressed floating-point loads don't extend their values.
+        Access::Load {
+            rd: rd @ Register::F(_),
+            width,
+            ..
+        } => Access::Load {
+            rd,
+            width,
+            signed: false,
+        },
+        access => access,
+    })
+}
+
+/// Mask of the lowest `width` bytes.
+const fn low_bytes(width: usize) -> u64 {
+    if width >= 8 {
+        u64::MAX
+    } else {
+        (1 << (width * 8)) - 1
+    }
+}
+
+/// Extends the lowest `width` bytes of `raw`, loaded into an integer register.
+pub const fn extend(raw: u64, width: usize, signed: bool) -> u64 {
+    let shift = 64 - width as u32 * 8;
+    if signed {
+        ((raw << shift) as i64 >> shift) as u64
+    } else {
+        (raw << shift) >> shift
+    }
+}
+
+/// NaN-boxes the lowest `width` bytes of `raw` into a floating-point value of `flen` bytes.
+pub const fn nan_box(raw: u64, width: usize, flen: usize) -> u64 {
+    (raw & low_bytes(width)) | (low_bytes(flen) & !low_bytes(width))
+}
+
+impl Vector {
+    /// Number of registers accessed from `vd`, with the `vtype` of the hart.
+    pub fn registers(&self, vtype: usize) -> usize {
+        // EMUL = EEW / SEW * LMUL, with LMUL in eighths.
+        let sew = 1 << ((vtype >> 3) & 0b111);
+        let lmul = match vtype & 0b111 {
+            vlmul @ 0..=3 => 8 << vlmul,
+            vlmul => 8 >> (8 - vlmul),
+        };
+        (lmul * self.eew / sew / 8).max(1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn load(rd: Register, width: usize, signed: bool) -> Option<Access> {
+        Some(Access::Load { rd, width, signed })
+    }
+
+    fn store(rs2: Register, width: usize) -> Option<Access> {
+        Some(Access::Store { rs2, width })
+    }
+
+    #[test]
+    fn decode_integer() {
+        use Register::X;
+        // lh a0, 1(a1)
+        assert_eq!(decode(0x0015_9503, Xlen::Rv64), load(X(10), 2, true));
+        // lhu s2, 3(t0)
+        assert_eq!(decode(0x0032_d903, Xlen::Rv64), load(X(18), 2, false));
+        // lw t6, 0(a0)
+        assert_eq!(decode(0x0005_2f83, Xlen::Rv64), load(X(31), 4, true));
+        // lwu a5, 0(a0)
+        assert_eq!(decode(0x0005_6783, Xlen::Rv64), load(X(15), 4, false));
+        assert_eq!(decode(0x0005_6783, Xlen::Rv32), None);
+        // sh a2, 1(a1)
+        assert_eq!(decode(0x00c5_90a3, Xlen::Rv64), store(X(12), 2));
+        // sw s3, 0(a0)
+        assert_eq!(decode(0x0135_2023, Xlen::Rv64), store(X(19), 4));
+        // sd t1, 0(a0)
+        assert_eq!(decode(0x0065_3023, Xlen::Rv64), store(X(6), 8));
+        assert_eq!(decode(0x0065_3023, Xlen::Rv32), None);
+        // addi a0, a0, 1
+        assert_eq!(decode(0x0015_0513, Xlen::Rv64), None);
+    }
+
+    #[test]
+    fn decode_float() {
+        use Register::F;
+        // flh fa0, 0(a1)
+        assert_eq!(decode(0x0005_9507, Xlen::Rv64), load(F(10), 2, false));
+        // fsh ft3, 0(a1)
+        assert_eq!(decode(0x0035_9027, Xlen::Rv64), store(F(3), 2));
+        // flw fs1, 0(a1)
+        assert_eq!(decode(0x0005_a487, Xlen::Rv64), load(F(9), 4, false));
+        // fsw fa7, 0(a1)
+        assert_eq!(decode(0x0115_a027, Xlen::Rv64), store(F(17), 4));
+        // fld ft11, 0(a1)
+        assert_eq!(decode(0x0005_bf87, Xlen::Rv32), load(F(31), 8, false));
+        // fsd fs11, 0(a1)
+        assert_eq!(decode(0x01b5_b027, Xlen::Rv64), store(F(27), 8));
+    }
+
+    #[test]
+    fn decode_compressed() {
+        use Register::{F, X};
+        // c.lw a0, 4(a1)
+        assert_eq!(decode(0x41c8, Xlen::Rv64), load(X(10), 4, true));
+        // c.ld s1, 8(a5)
+        assert_eq!(decode(0x6784, Xlen::Rv64), load(X(9), 8, true));
+        // c.flw fs1, 8(a5) on RV32
+        assert_eq!(decode(0x6784, Xlen::Rv32), load(F(9), 4, false));
+        // c.sw a4, 0(s0)
+        assert_eq!(decode(0xc018, Xlen::Rv64), store(X(14), 4));
+        // c.sd a3, 8(a2)
+        assert_eq!(decode(0xe614, Xlen::Rv64), store(X(13), 8));
+        // c.fsw fa3, 8(a2) on RV32
+        assert_eq!(decode(0xe614, Xlen::Rv32), store(F(13), 4));
+        // c.fld fa2, 16(a0)
+        assert_eq!(decode(0x2910, Xlen::Rv64), load(F(12), 8, false));
+        // c.fsd fs0, 0(a1)
+        assert_eq!(decode(0xa180, Xlen::Rv64), store(F(8), 8));
+        // c.lwsp t0, 4(sp)
+        assert_eq!(decode(0x4292, Xlen::Rv64), load(X(5), 4, true));
+        // c.ldsp s11, 16(sp)
+        assert_eq!(decode(0x6dc2, Xlen::Rv64), load(X(27), 8, true));
+        // c.flwsp fs11, 16(sp) on RV32
+        assert_eq!(decode(0x6dc2, Xlen::Rv32), load(F(27), 4, false));
+        // c.swsp ra, 12(sp)
+        assert_eq!(decode(0xc606, Xlen::Rv64), store(X(1), 4));
+        // c.sdsp s4, 24(sp)
+        assert_eq!(decode(0xec52, Xlen::Rv64), store(X(20), 8));
+        // c.fswsp fs4, 24(sp) on RV32
+        assert_eq!(decode(0xec52, Xlen::Rv32), store(F(20), 4));
+        // c.fldsp ft7, 32(sp)
+        assert_eq!(decode(0x3382, Xlen::Rv64), load(F(7), 8, false));
+        // c.fsdsp fa1, 40(sp)
+        assert_eq!(decode(0xb42e, Xlen::Rv64), store(F(11), 8));
+        // c.addi a0, 1
+        assert_eq!(decode(0x0505, Xlen::Rv64), None);
+    }
+
+    fn vector(store: bool, vd: usize, rs1: usize, eew: usize) -> Vector {
+        Vector {
+            store,
+            vd,
+            rs1,
+            eew,
+            masked: false,
+            fault_only_first: false,
+        }
+    }
+
+    #[test]
+    fn decode_vector() {
+        let masked = |v: Vector| Vector { masked: true, ..v };
+        let fault_only_first = |v: Vector| Vector {
+            fault_only_first: true,
+            ..v
+        };
+        let decode = |inst| match decode(inst, Xlen::Rv64) {
+            Some(Access::Vector(vector)) => Some(vector),
+            _ => None,
+        };
+        // vle16.v v8, (a0)
+        assert_eq!(decode(0x0205_5407), Some(vector(false, 8, 10, 2)));
+        // vle32.v v4, (a1), v0.t
+        assert_eq!(decode(0x0005_e207), Some(masked(vector(false, 4, 11, 4))));
+        // vle64ff.v v16, (t0)
+        assert_eq!(
+            decode(0x0302_f807),
+            Some(fault_only_first(vector(false, 16, 5, 8)))
+        );
+        // vse16.v v2, (a0)
+        assert_eq!(decode(0x0205_5127), Some(vector(true, 2, 10, 2)));
+        // vse64.v v24, (s1), v0.t
+        assert_eq!(decode(0x0004_fc27), Some(masked(vector(true, 24, 9, 8))));
+        // vle8.v v1, (a0)
+        assert_eq!(decode(0x0205_0087), Some(vector(false, 1, 10, 1)));
+        // vlseg3e32.v v1, (a2), segment
+        assert_eq!(decode(0x4206_6087), None);
+        // vsseg2e16.v v10, (a3), segment
+        assert_eq!(decode(0x2206_d527), None);
+        // vl2re32.v v6, (a4), whole register
+        assert_eq!(decode(0x2287_6307), None);
+        // vs1r.v v12, (a5), whole register
+        assert_eq!(decode(0x0287_8627), None);
+        // vlm.v v1, (a0), whose elements are bits
+        assert_eq!(decode(0x02b5_0087), None);
+        // vlse32.v v1, (a0), a1, strided
+        assert_eq!(decode(0x0ab5_6087), None);
+    }
+
+    #[test]
+    fn extend_width() {
+        assert_eq!(extend(0x8001, 2, true), 0xffff_ffff_ffff_8001);
+        assert_eq!(extend(0x1234_8001, 2, false), 0x8001);
+        assert_eq!(extend(0x8000_0000, 4, true), 0xffff_ffff_8000_0000);
+        assert_eq!(extend(0xffff_ffff_8000_0000, 4, false), 0x8000_0000);
+        assert_eq!(
+            extend(0x8000_0000_0000_0000, 8, true),
+            0x8000_0000_0000_0000
+        );
+        assert_eq!(extend(0x80, 1, true), 0xffff_ffff_ffff_ff80);
+    }
+
+    #[test]
+    fn nan_box_width() {
+        assert_eq!(nan_box(0x1234_3c00, 2, 4), 0xffff_3c00);
+        assert_eq!(nan_box(0x3c00, 2, 8), 0xffff_ffff_ffff_3c00);
+        assert_eq!(nan_box(0x3f80_0000, 4, 8), 0xffff_ffff_3f80_0000);
+        assert_eq!(nan_box(0x3ff0_0000_0000_0000, 8, 8), 0x3ff0_0000_0000_0000);
+    }
+
+    /// `vtype` with SEW of `sew` bytes and the `vlmul` field.
+    fn vtype(sew: usize, vlmul: usize) -> usize {
+        (sew.trailing_zeros() as usize) << 3 | vlmul
+    }
+
+    #[test]
+    fn vector_registers() {
+        // EEW 32, SEW 32, LMUL 2
+        assert_eq!(vector(false, 0, 0, 4).registers(vtype(4, 1)), 2);
+        // EEW 64, SEW 16, LMUL 1
+        assert_eq!(vector(false, 0, 0, 8).registers(vtype(2, 0)), 4);
+        // EEW 16, SEW 64, LMUL 1/2 takes a whole register
+        assert_eq!(vector(false, 0, 0, 2).registers(vtype(8, 7)), 1);
+        // EEW 8, SEW 8, LMUL 8
+        assert_eq!(vector(false, 0, 0, 1).registers(vtype(1, 3)), 8);
+    }
+}
@@ -2,6 +2,7 @@ pub mod boot;
 pub mod handler;
 
 mod helper;
+mod misaligned;
 use super::pmu::pmu_firmware_counter_increment;
 use crate::fail::unsupported_trap;
 
@@ -31,7 +32,15 @@ pub extern "C" fn fast_handler(
         ctx.regs().a = [ctx.a0(), a1, a2, a3, a4, a5, a6, a7];
     };
 
-    match mcause::read().cause().try_into() {
+    // Local counter overflow interrupt is only taken when claimed by SSE
+    let mcause = mcause::read();
+    if mcause.is_interrupt() && mcause.code() == crate::sbi::sse::LCOFI {
+        save_regs(&mut ctx);
+        crate::sbi::sse::lcofi_handler(&mut ctx);
+        return ctx.restore();
+    }
+
+    match mcause.cause().try_into() {
         Ok(cause) => {
             match cause {
                 // Handle Msoft
@@ -39,6 +48,15 @@ pub extern "C" fn fast_handler(
                     save_regs(&mut ctx);
                     handler::msoft_handler(ctx)
                 }
+                // Handle IPIs delivered as external interrupts, such as by the IMSIC
+                Trap::Interrupt(Interrupt::MachineExternal) => {
+                    save_regs(&mut ctx);
+                    if crate::sbi::ipi::claim_external_ipi() {
+                        handler::msoft_handler(ctx)
+                    } else {
+                        ctx.restore()
+                    }
+                }
                 // Handle MTimer
                 Trap::Interrupt(Interrupt::MachineTimer) => {
                     use crate::sbi::ipi;
@@ -73,6 +91,21 @@ pub extern "C" fn fast_handler(
                     save_regs(&mut ctx);
                     ctx.continue_with(handler::store_misaligned_handler, ())
                 }
+                // Redirect exceptions the supervisor could take itself, from supervisor or user mode
+                Trap::Exception(
+                    Exception::InstructionMisaligned
+                    | Exception::InstructionFault
+                    | Exception::Breakpoint
+                    | Exception::LoadFault
+                    | Exception::StoreFault
+                    | Exception::UserEnvCall
+                    | Exception::InstructionPageFault
+                    | Exception::LoadPageFault
+                    | Exception::StorePageFault,
+                ) if mstatus::read().mpp() != mstatus::MPP::Machine => {
+                    save_regs(&mut ctx);
+                    ctx.continue_with(handler::redirect_handler, ())
+                }
                 // Handle other traps
                 trap => unsupported_trap(Some(trap)),
             }
@@ -40,6 +40,17 @@ pub(crate) fn prepare_for_trap() {
     };
 }
 
+/// Prepares trap stack for current hart entering the firmware again after power off.
+///
+/// Unlike [`prepare_for_trap`], the HSM state of the hart is kept.
+pub(crate) fn prepare_for_warm_boot() {
+    unsafe {
+        let stack = ROOT_STACK.get_unchecked_mut(current_hartid());
+        stack.hart_context_mut().reset();
+        stack.load_trap_stack()
+    };
+}
+
 pub fn hart_context_mut(hart_id: usize) -> &'static mut HartContext {
     unsafe { ROOT_STACK.get_mut(hart_id).unwrap().hart_context_mut() }
 }
@@ -78,9 +89,13 @@ impl Stack {
     /// - Sets up hart context.
     /// - Creates and loads FreeTrapStack with the stack range.
     fn load_as_stack(&'static mut self) {
-        let hart = self.hart_context_mut();
-        let context_ptr = hart.context_ptr();
-        hart.init();
+        self.hart_context_mut().init();
+        self.load_trap_stack();
+    }
+
+    /// Creates and loads FreeTrapStack with the stack range.
+    fn load_trap_stack(&'static mut self) {
+        let context_ptr = self.hart_context_mut().context_ptr();
 
         // Get stack memory range.
         let range = self.0.as_ptr_range();
@@ -14,7 +14,10 @@ use sbi_spec::{
     binary::{CounterMask, HartMask, SbiRet},
     pmu::firmware_event,
 };
-use sbi_testing::sbi::{self, ConfigFlags, StartFlags, StopFlags};
+use sbi_testing::{
+    Entry, Format, Marker,
+    sbi::{self, ConfigFlags, StartFlags, StopFlags},
+};
 // use sbi_spec::pmu::*;
 use uart16550::Uart16550;
 
@@ -98,14 +101,29 @@ extern "C" fn rust_main(hartid: usize, dtb_pa: usize) -> ! {
 | dtb physical address  | {dtb_pa:#20x} |
 ------------------------------------------------"
     );
+    // Survives system suspend and reset, so that tests continue after re-entry.
+    #[unsafe(link_section = ".bss.uninit")]
+    static mut MARKER: Marker = Marker::new();
+
+    let marker = unsafe { &mut *core::ptr::addr_of_mut!(MARKER) };
     let testing = sbi_testing::Testing {
         hartid,
         hart_mask: (1 << smp) - 1,
         hart_mask_base: 0,
         delay: frequency,
+        format: match option_env!("SBI_TESTING_FORMAT") {
+            Some("json") => Format::Json(|args| println!("{args}")),
+            _ => Format::Log,
+        },
+    };
+    let entry = Entry {
+        hartid,
+        opaque: dtb_pa,
+        resume_addr: _start as *const () as usize,
     };
-    let test_result = testing.test();
+    let test_result = testing.test_reentrant(marker, entry, true);
 
+    // Runs once after the suites have finished, never on re-entry after suspend or reset.
     pmu_test();
     fence_test();
 
@@ -449,7 +467,7 @@ struct CounterInfo {
 impl CounterInfo {
     const CSR_MASK: usize = 0xFFF; // Bits [11:0]
     const WIDTH_MASK: usize = 0x3F << 12; // Bits [17:12]
-    const FIRMWARE_FLAG: usize = 1 << (size_of::<usize>() * 8 - 1); // MSB
+    const FIRMWARE_FLAG: usize = 1 << (usize::BITS - 1); // MSB
 
     #[inline]
     pub const fn new(counter_info: usize) -> Self {
@@ -0,0 +1,762 @@
+use std::{env, fs, path::PathBuf, process::ExitStatus};
+
+use clap::Args;
+use toml::{Table, Value};
+
+#[derive(Debug, Args, Clone)]
+pub struct DtbArg {
+    /// Board configuration file with a `[board]` table.
+    #[clap(long, short = 'c')]
+    pub config: PathBuf,
+
+    /// Output path of the device tree blob, `target/<config name>.dtb` by default.
+    #[clap(long, short = 'o')]
+    pub output: Option<PathBuf>,
+}
+
+const FDT_MAGIC: u32 = 0xd00dfeed;
+const FDT_VERSION: u32 = 17;
+const FDT_LAST_COMP_VERSION: u32 = 16;
+const FDT_HEADER_SIZE: usize = 40;
+const FDT_BEGIN_NODE: u32 = 0x1;
+const FDT_END_NODE: u32 = 0x2;
+const FDT_PROP: u32 = 0x3;
+const FDT_END: u32 = 0x9;
+
+/// Register region sizes of the devices, as used by QEMU.
+const UART_SIZE: u64 = 0x100;
+const CLINT_SIZE: u64 = 0x10000;
+const TEST_SIZE: u64 = 0x1000;
+
+/// Timer frequency assumed when the board does not set `timebase_frequency`.
+const DEFAULT_TIMEBASE_FREQUENCY: u32 = 10_000_000;
+
+/// Interrupt numbers of the CLINT on each hart.
+const IRQ_M_SOFT: u32 = 3;
+const IRQ_M_TIMER: u32 = 7;
+
+/// Suspend types with this bit set lose the hart state.
+const NON_RETENTIVE: u32 = 0x8000_0000;
+
+#[must_use]
+pub fn run(arg: &DtbArg) -> Option<ExitStatus> {
+    info!("Read board description from: {}", arg.config.display());
+    let config = fs::read_to_string(&arg.config)
+        .map_err(|err| error!("Failed to read {}: {}", arg.config.display(), err))
+        .ok()?;
+    let config: Table = toml::from_str(&config)
+        .map_err(|err| error!("Failed to parse {}: {}", arg.config.display(), err))
+        .ok()?;
+    let Some(board) = config.get("board").and_then(Value::as_table) else {
+        error!("No [board] table in {}", arg.config.display());
+        return None;
+    };
+    let board = Board::parse(board)
+        .map_err(|err| error!("Invalid [board] table: {}", err))
+        .ok()?;
+
+    let output = match &arg.output {
+        Some(output) => output.clone(),
+        None => {
+            let Some(name) = arg.config.file_stem() else {
+                error!(
+                    "Cannot name the output after {}, pass --output",
+                    arg.config.display()
+                );
+                return None;
+            };
+            env::current_dir()
+                .map_err(|err| error!("Failed to get current directory: {}", err))
+                .ok()?
+                .join("target")
+                .join(format!("{}.dtb", name.to_string_lossy()))
+        }
+    };
+    if let Some(parent) = output.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|err| error!("Failed to create {}: {}", parent.display(), err))
+            .ok()?;
+    }
+    info!("Write device tree to: {}", output.display());
+    fs::write(&output, board.to_dtb())
+        .map_err(|err| error!("Failed to write {}: {}", output.display(), err))
+        .ok()?;
+
+    Some(ExitStatus::default())
+}
+
+/// Hardware described by the `[board]` table of a configuration file.
+struct Board {
+    model: String,
+    memory: Vec<(u64, u64)>,
+    harts: Vec<u32>,
+    isa: String,
+    timebase_frequency: u32,
+    console: Option<Device>,
+    ipi: Option<Device>,
+    reset: Option<Device>,
+    pmu_events: Vec<(u32, u64)>,
+    pmu_counters: Vec<(u32, u32, u32)>,
+    pmu_raw: Vec<(u64, u64, u32)>,
+    idle_states: Vec<IdleState>,
+}
+
+/// A device table of the board, such as `[board.console]`.
+struct Device {
+    compatible: String,
+    base: u64,
+    clock_frequency: Option<u32>,
+}
+
+/// An entry of `[[board.idle_states]]`.
+struct IdleState {
+    suspend_type: u32,
+    entry_latency_us: u32,
+    exit_latency_us: u32,
+    min_residency_us: u32,
+}
+
+impl Board {
+    fn parse(board: &Table) -> Result<Self, String> {
+        fn get<'a>(table: &'a Table, key: &str) -> Result<&'a Value, String> {
+            table.get(key).ok_or_else(|| format!("missing `{}`", key))
+        }
+        fn string(table: &Table, key: &str) -> Result<String, String> {
+            get(table, key)?
+                .as_str()
+                .map(str::to_string)
+                .ok_or_else(|| format!("`{}` should be a string", key))
+        }
+        fn integer(value: &Value, key: &str) -> Result<i64, String> {
+            value
+                .as_integer()
+                .ok_or_else(|| format!("`{}` should only hold integers", key))
+        }
+        fn integers(value: &Value, key: &str) -> Result<Vec<i64>, String> {
+            value
+                .as_array()
+                .ok_or_else(|| format!("`{}` should be an array", key))?
+                .iter()
+                .map(|value| integer(value, key))
+                .collect()
+        }
+        fn integers_rows(value: &Value, key: &str) -> Result<Vec<Vec<i64>>, String> {
+            value
+                .as_array()
+                .ok_or_else(|| format!("`{}` should be an array", key))?
+                .iter()
+                .map(|row| integers(row, key))
+                .collect()
+        }
+        fn field(table: &Table, key: &str) -> Result<i64, String> {
+            integer(get(table, key)?, key)
+        }
+        fn optional_u32(table: &Table, key: &str) -> Result<Option<u32>, String> {
+            table
+                .get(key)
+                .map(|value| integer(value, key))
+                .transpose()
+                .map(|value| value.map(|value| value as u32))
+        }
+        /// Gets the optional subtable `key`, rejecting keys other than `keys`.
+        fn table<'a>(
+            table: &'a Table,
+            key: &str,
+            keys: &[&str],
+        ) -> Result<Option<&'a Table>, String> {
+            let Some(value) = table.get(key) else {
+                return Ok(None);
+            };
+            let value = value
+                .as_table()
+                .ok_or_else(|| format!("`{}` should be a table", key))?;
+            check_keys(value, key, keys)?;
+            Ok(Some(value))
+        }
+        /// Gets the optional array of tables `key`, rejecting keys other than `keys`.
+        fn tables<'a>(
+            table: &'a Table,
+            key: &str,
+            keys: &[&str],
+        ) -> Result<Vec<&'a Table>, String> {
+            let Some(value) = table.get(key) else {
+                return Ok(Vec::new());
+            };
+            value
+                .as_array()
+                .ok_or_else(|| format!("`{}` should be an array of tables", key))?
+                .iter()
+                .map(|value| {
+                    let value = value
+                        .as_table()
+                        .ok_or_else(|| format!("`{}` should be an array of tables", key))?;
+                    check_keys(value, key, keys)?;
+                    Ok(value)
+                })
+                .collect()
+        }
+        fn check_keys(table: &Table, key: &str, keys: &[&str]) -> Result<(), String> {
+            match table.keys().find(|k| !keys.contains(&k.as_str())) {
+                Some(unknown) => Err(format!("unknown key `{}` in `{}`", unknown, key)),
+                None => Ok(()),
+            }
+        }
+        fn device(board: &Table, key: &str) -> Result<Option<Device>, String> {
+            table(board, key, &["compatible", "base", "clock_frequency"])?
+                .map(|device| {
+                    Ok(Device {
+                        compatible: string(device, "compatible")?,
+                        base: field(device, "base")? as u64,
+                        clock_frequency: optional_u32(device, "clock_frequency")?,
+                    })
+                })
+                .transpose()
+        }
+
+        check_keys(
+            board,
+            "board",
+            &[
+                "model",
+                "memory",
+                "harts",
+                "isa",
+                "timebase_frequency",
+                "console",
+                "ipi",
+                "reset",
+                "pmu",
+                "idle_states",
+            ],
+        )?;
+        let memory = integers_rows(get(board, "memory")?, "memory")?
+            .into_iter()
+            .map(|row| match row[..] {
+                [start, size] => Ok((start as u64, size as u64)),
+                _ => Err("rows of `memory` should hold [start, size]".to_string()),
+            })
+            .collect::<Result<_, String>>()?;
+        let pmu = table(board, "pmu", &["event", "counters", "raw"])?;
+        let pmu_tables = |key, keys| match pmu {
+            Some(pmu) => tables(pmu, key, keys),
+            None => Ok(Vec::new()),
+        };
+        Ok(Self {
+            model: string(board, "model")?,
+            memory,
+            harts: integers(get(board, "harts")?, "harts")?
+                .into_iter()
+                .map(|hart| hart as u32)
+                .collect(),
+            isa: string(board, "isa")?,
+            timebase_frequency: optional_u32(board, "timebase_frequency")?
+                .unwrap_or(DEFAULT_TIMEBASE_FREQUENCY),
+            console: device(board, "console")?,
+            ipi: device(board, "ipi")?,
+            reset: device(board, "reset")?,
+            pmu_events: pmu_tables("event", &["event", "mhpmevent"])?
+                .into_iter()
+                .map(|t| Ok((field(t, "event")? as u32, field(t, "mhpmevent")? as u64)))
+                .collect::<Result<_, String>>()?,
+            pmu_counters: pmu_tables("counters", &["first", "last", "counters"])?
+                .into_iter()
+                .map(|t| {
+                    Ok((
+                        field(t, "first")? as u32,
+                        field(t, "last")? as u32,
+                        field(t, "counters")? as u32,
+                    ))
+                })
+                .collect::<Result<_, String>>()?,
+            pmu_raw: pmu_tables("raw", &["event", "mask", "counters"])?
+                .into_iter()
+                .map(|t| {
+                    Ok((
+                        field(t, "event")? as u64,
+                        field(t, "mask")? as u64,
+                        field(t, "counters")? as u32,
+                    ))
+                })
+                .collect::<Result<_, String>>()?,
+            idle_states: tables(
+                board,
+                "idle_states",
+                &[
+                    "suspend_type",
+                    "entry_latency_us",
+                    "exit_latency_us",
+                    "min_residency_us",
+                ],
+            )?
+            .into_iter()
+            .map(|t| {
+                Ok(IdleState {
+                    suspend_type: field(t, "suspend_type")? as u32,
+                    entry_latency_us: field(t, "entry_latency_us")? as u32,
+                    exit_latency_us: field(t, "exit_latency_us")? as u32,
+                    min_residency_us: field(t, "min_residency_us")? as u32,
+                })
+            })
+            .collect::<Result<_, String>>()?,
+        })
+    }
+
+    /// Builds the device tree blob of the board.
+    fn to_dtb(&self) -> Vec<u8> {
+        let mut fdt = FdtBuilder::new();
+        // Phandles of the interrupt controllers of the harts, in order.
+        let intc_phandles: Vec<u32> = (1..=self.harts.len() as u32).collect();
+        // Phandles of the idle states, following the interrupt controllers.
+        let idle_phandles: Vec<u32> = (1..=self.idle_states.len() as u32)
+            .map(|index| self.harts.len() as u32 + index)
+            .collect();
+
+        fdt.begin_node("");
+        fdt.prop_u32("#address-cells", &[2]);
+        fdt.prop_u32("#size-cells", &[2]);
+        fdt.prop_strs("compatible", &[&self.model]);
+        fdt.prop_strs("model", &[&self.model]);
+
+        fdt.begin_node("chosen");
+        if let Some(console) = &self.console {
+            fdt.prop_strs("stdout-path", &[&format!("/soc/serial@{:x}", console.base)]);
+        }
+        fdt.end_node();
+
+        for &(start, size) in self.memory.iter() {
+            fdt.begin_node(&format!("memory@{:x}", start));
+            fdt.prop_strs("device_type", &["memory"]);
+            fdt.prop_u32("reg", &[cells(start), cells(size)].concat());
+            fdt.end_node();
+        }
+
+        fdt.begin_node("cpus");
+        fdt.prop_u32("#address-cells", &[1]);
+        fdt.prop_u32("#size-cells", &[0]);
+        fdt.prop_u32("timebase-frequency", &[self.timebase_frequency]);
+        let (isa_base, extensions) = isa_extensions(&self.isa);
+        let extensions: Vec<&str> = extensions.iter().map(String::as_str).collect();
+        let mmu_type = if isa_base == "rv32i" {
+            "riscv,sv32"
+        } else {
+            "riscv,sv39"
+        };
+        if !self.idle_states.is_empty() {
+            fdt.begin_node("idle-states");
+            for (index, (state, &phandle)) in self
+                .idle_states
+                .iter()
+                .zip(idle_phandles.iter())
+                .enumerate()
+            {
+                let suspend_type = state.suspend_type;
+                let kind = if suspend_type & NON_RETENTIVE != 0 {
+                    "nonretentive"
+                } else {
+                    "retentive"
+                };
+                fdt.begin_node(&format!("cpu-{}-{}", kind, index));
+                fdt.prop_strs("compatible", &["riscv,idle-state"]);
+                fdt.prop_u32("riscv,sbi-suspend-param", &[suspend_type]);
+                fdt.prop_u32("entry-latency-us", &[state.entry_latency_us]);
+                fdt.prop_u32("exit-latency-us", &[state.exit_latency_us]);
+                fdt.prop_u32("min-residency-us", &[state.min_residency_us]);
+                fdt.prop_u32("phandle", &[phandle]);
+                fdt.end_node();
+            }
+            fdt.end_node();
+        }
+        for (&hart, &phandle) in self.harts.iter().zip(intc_phandles.iter()) {
+            fdt.begin_node(&format!("cpu@{:x}", hart));
+            fdt.prop_strs("device_type", &["cpu"]);
+            fdt.prop_u32("reg", &[hart]);
+            fdt.prop_strs("status", &["okay"]);
+            fdt.prop_strs("compatible", &["riscv"]);
+            fdt.prop_strs("riscv,isa", &[&self.isa]);
+            fdt.prop_strs("riscv,isa-base", &[&isa_base]);
+            fdt.prop_strs("riscv,isa-extensions", &extensions);
+            fdt.prop_strs("mmu-type", &[mmu_type]);
+            if !idle_phandles.is_empty() {
+                fdt.prop_u32("cpu-idle-states", &idle_phandles);
+            }
+            fdt.begin_node("interrupt-controller");
+            fdt.prop_u32("#interrupt-cells", &[1]);
+            fdt.prop_empty("interrupt-controller");
+            fdt.prop_strs("compatible", &["riscv,cpu-intc"]);
+            fdt.prop_u32("phandle", &[phandle]);
+            fdt.end_node();
+            fdt.end_node();
+        }
+        fdt.end_node();
+
+        if let Some(pmu) = self.pmu_node() {
+            fdt.begin_node("pmu");
+            fdt.prop_strs("compatible", &["riscv,pmu"]);
+            for (name, value) in pmu {
+                fdt.prop_u32(name, &value);
+            }
+            fdt.end_node();
+        }
+
+        fdt.begin_node("soc");
+        fdt.prop_u32("#address-cells", &[2]);
+        fdt.prop_u32("#size-cells", &[2]);
+        fdt.prop_strs("compatible", &["simple-bus"]);
+        fdt.prop_empty("ranges");
+        if let Some(console) = &self.console {
+            fdt.begin_node(&format!("serial@{:x}", console.base));
+            fdt.prop_strs("compatible", &[&console.compatible]);
+            fdt.prop_u32("reg", &[cells(console.base), cells(UART_SIZE)].concat());
+            if let Some(frequency) = console.clock_frequency {
+                fdt.prop_u32("clock-frequency", &[frequency]);
+            }
+            fdt.end_node();
+        }
+        if let Some(ipi) = &self.ipi {
+            fdt.begin_node(&format!("clint@{:x}", ipi.base));
+            fdt.prop_strs("compatible", &[&ipi.compatible]);
+            fdt.prop_u32("reg", &[cells(ipi.base), cells(CLINT_SIZE)].concat());
+            let interrupts: Vec<u32> = intc_phandles
+                .iter()
+                .flat_map(|&phandle| [phandle, IRQ_M_SOFT, phandle, IRQ_M_TIMER])
+                .collect();
+            fdt.prop_u32("interrupts-extended", &interrupts);
+            fdt.end_node();
+        }
+        if let Some(reset) = &self.reset {
+            fdt.begin_node(&format!("test@{:x}", reset.base));
+            fdt.prop_strs("compatible", &[&reset.compatible]);
+            fdt.prop_u32("reg", &[cells(reset.base), cells(TEST_SIZE)].concat());
+            fdt.end_node();
+        }
+        fdt.end_node();
+
+        fdt.end_node();
+        fdt.finish(self.harts.first().copied().unwrap_or(0))
+    }
+
+    /// Properties of the PMU node, `None` if the board has no PMU mappings.
+    fn pmu_node(&self) -> Option<Vec<(&'static str, Vec<u32>)>> {
+        let event_to_mhpmevent: Vec<u32> = self
+            .pmu_events
+            .iter()
+            .flat_map(|&(event, mhpmevent)| {
+                let [mhpmevent_hi, mhpmevent_lo] = cells(mhpmevent);
+                [event, mhpmevent_hi, mhpmevent_lo]
+            })
+            .collect();
+        let event_to_mhpmcounters: Vec<u32> = self
+            .pmu_counters
+            .iter()
+            .flat_map(|&(first, last, counters)| [first, last, counters])
+            .collect();
+        let raw_event_to_mhpmcounters: Vec<u32> = self
+            .pmu_raw
+            .iter()
+            .flat_map(|&(event, mask, counters)| {
+                let [event_hi, event_lo] = cells(event);
+                let [mask_hi, mask_lo] = cells(mask);
+                [event_hi, event_lo, mask_hi, mask_lo, counters]
+            })
+            .collect();
+        let pmu: Vec<_> = [
+            ("riscv,event-to-mhpmevent", event_to_mhpmevent),
+            ("riscv,event-to-mhpmcounters", event_to_mhpmcounters),
+            ("riscv,raw-event-to-mhpmcounters", raw_event_to_mhpmcounters),
+        ]
+        .into_iter()
+        .filter(|(_, value)| !value.is_empty())
+        .collect();
+        (!pmu.is_empty()).then_some(pmu)
+    }
+}
+
+/// Splits a 64-bit value into two cells.
+fn cells(value: u64) -> [u32; 2] {
+    [(value >> 32) as u32, value as u32]
+}
+
+/// Splits an ISA string like `rv64imafdc_zicsr` into its base and its extensions.
+fn isa_extensions(isa: &str) -> (String, Vec<String>) {
+    let isa = isa.to_ascii_lowercase();
+    let mut parts = isa.split('_');
+    let first = parts.next().unwrap_or_default();
+    let letters = first
+        .trim_start_matches("rv")
+        .trim_start_matches(|c: char| c.is_ascii_digit());
+    let base = &first[..first.len() - letters.len()];
+
+    let mut extensions = Vec::new();
+    for letter in letters.chars() {
+        if letter == 'g' {
+            extensions.extend(["i", "m", "a", "f", "d", "zicsr", "zifencei"].map(String::from));
+        } else {
+            extensions.push(letter.to_string());
+        }
+    }
+    extensions.extend(parts.filter(|part| !part.is_empty()).map(String::from));
+    let mut unique = Vec::new();
+    for extension in extensions {
+        if !unique.contains(&extension) {
+            unique.push(extension);
+        }
+    }
+    (format!("{}i", base), unique)
+}
+
+/// Writer of flattened device trees.
+struct FdtBuilder {
+    structure: Vec<u8>,
+    strings: Vec<u8>,
+}
+
+impl FdtBuilder {
+    fn new() -> Self {
+        Self {
+            structure: Vec::new(),
+            strings: Vec::new(),
+        }
+    }
+
+    fn push_u32(&mut self, value: u32) {
+        self.structure.extend(value.to_be_bytes());
+    }
+
+    fn align(&mut self) {
+        while !self.structure.len().is_multiple_of(4) {
+            self.structure.push(0);
+        }
+    }
+
+    fn begin_node(&mut self, name: &str) {
+        self.push_u32(FDT_BEGIN_NODE);
+        self.structure.extend(name.as_bytes());
+        self.structure.push(0);
+        self.align();
+    }
+
+    fn end_node(&mut self) {
+        self.push_u32(FDT_END_NODE);
+    }
+
+    /// Finds the offset of `name` in the strings block, adding it if needed.
+    fn string_offset(&mut self, name: &str) -> u32 {
+        let mut offset = 0;
+        for string in self.strings.split(|&byte| byte == 0) {
+            if string == name.as_bytes() && offset < self.strings.len() {
+                return offset as u32;
+            }
+            offset += string.len() + 1;
+        }
+        let offset = self.strings.len();
+        self.strings.extend(name.as_bytes());
+        self.strings.push(0);
+        offset as u32
+    }
+
+    fn prop(&mut self, name: &str, value: &[u8]) {
+        let name = self.string_offset(name);
+        self.push_u32(FDT_PROP);
+        self.push_u32(value.len() as u32);
+        self.push_u32(name);
+        self.structure.extend(value);
+        self.align();
+    }
+
+    fn prop_empty(&mut self, name: &str) {
+        self.prop(name, &[]);
+    }
+
+    fn prop_u32(&mut self, name: &str, values: &[u32]) {
+        let value: Vec<u8> = values
+            .iter()
+            .flat_map(|value| value.to_be_bytes())
+            .collect();
+        self.prop(name, &value);
+    }
+
+    fn prop_strs(&mut self, name: &str, values: &[&str]) {
+        let value: Vec<u8> = values
+            .iter()
+            .flat_map(|value| value.as_bytes().iter().copied().chain([0]))
+            .collect();
+        self.prop(name, &value);
+    }
+
+    /// Assembles the header, an empty memory reservation block, the structure and the strings.
+    fn finish(mut self, boot_cpuid: u32) -> Vec<u8> {
+        self.push_u32(FDT_END);
+        // The memory reservation block only holds its terminating entry.
+        let off_mem_rsvmap = FDT_HEADER_SIZE;
+        let off_dt_struct = off_mem_rsvmap + 16;
+        let off_dt_strings = off_dt_struct + self.structure.len();
+        let total_size = off_dt_strings + self.strings.len();
+
+        let mut blob = Vec::with_capacity(total_size);
+        for value in [
+            FDT_MAGIC,
+            total_size as u32,
+            off_dt_struct as u32,
+            off_dt_strings as u32,
+            off_mem_rsvmap as u32,
+            FDT_VERSION,
+            FDT_LAST_COMP_VERSION,
+            boot_cpuid,
+            self.strings.len() as u32,
+            self.structure.len() as u32,
+        ] {
+            blob.extend(value.to_be_bytes());
+        }
+        blob.extend([0; 16]);
+        blob.extend(self.structure);
+        blob.extend(self.strings);
+        blob
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn read_u32(blob: &[u8], offset: usize) -> u32 {
+        u32::from_be_bytes(blob[offset..offset + 4].try_into().unwrap())
+    }
+
+    fn parse_board(config: &str) -> Result<Board, String> {
+        let config: Table = toml::from_str(config).unwrap();
+        Board::parse(config["board"].as_table().unwrap())
+    }
+
+    #[test]
+    fn board_optional_tables() {
+        let board = parse_board(
+            r#"
+            [board]
+            model = "test"
+            memory = [[0x80000000, 0x1000]]
+            harts = [0]
+            isa = "rv64imac"
+            "#,
+        )
+        .unwrap();
+        assert!(board.console.is_none() && board.ipi.is_none() && board.reset.is_none());
+        assert!(board.idle_states.is_empty());
+        assert!(board.pmu_node().is_none());
+    }
+
+    #[test]
+    fn board_pmu_tables() {
+        let board = parse_board(
+            r#"
+            [board]
+            model = "test"
+            memory = [[0x80000000, 0x1000]]
+            harts = [0]
+            isa = "rv64imac"
+            [[board.pmu.event]]
+            event = 0x9
+            mhpmevent = 0x1_0000_0002
+            [[board.pmu.raw]]
+            event = 0x2_0000_0003
+            mask = 0xffff
+            counters = 0x78
+            "#,
+        )
+        .unwrap();
+        assert_eq!(
+            board.pmu_node().unwrap(),
+            [
+                ("riscv,event-to-mhpmevent", vec![0x9, 0x1, 0x2]),
+                (
+                    "riscv,raw-event-to-mhpmcounters",
+                    vec![0x2, 0x3, 0, 0xffff, 0x78]
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn board_unknown_key() {
+        let err = parse_board(
+            r#"
+            [board]
+            model = "test"
+            memory = [[0x80000000, 0x1000]]
+            harts = [0]
+            isa = "rv64imac"
+            [board.console]
+            compatible = "ns16550a"
+            base = 0x10000000
+            clock = 3686400
+            "#,
+        )
+        .err()
+        .unwrap();
+        assert_eq!(err, "unknown key `clock` in `console`");
+    }
+
+    #[test]
+    fn fdt_header_offsets() {
+        let mut fdt = FdtBuilder::new();
+        fdt.begin_node("");
+        fdt.prop_u32("#address-cells", &[2]);
+        fdt.begin_node("cpus");
+        fdt.prop_strs("compatible", &["riscv"]);
+        fdt.end_node();
+        fdt.end_node();
+        let blob = fdt.finish(3);
+
+        assert_eq!(read_u32(&blob, 0), FDT_MAGIC);
+        assert_eq!(read_u32(&blob, 4) as usize, blob.len());
+        let off_dt_struct = read_u32(&blob, 8) as usize;
+        let off_dt_strings = read_u32(&blob, 12) as usize;
+        assert_eq!(read_u32(&blob, 16) as usize, FDT_HEADER_SIZE);
+        assert_eq!(read_u32(&blob, 20), FDT_VERSION);
+        assert_eq!(read_u32(&blob, 24), FDT_LAST_COMP_VERSION);
+        assert_eq!(read_u32(&blob, 28), 3);
+        let size_dt_strings = read_u32(&blob, 32) as usize;
+        let size_dt_struct = read_u32(&blob, 36) as usize;
+
+        // The memory reservation block only holds its terminating entry.
+        assert_eq!(off_dt_struct, FDT_HEADER_SIZE + 16);
+        assert!(
+            blob[FDT_HEADER_SIZE..off_dt_struct]
+                .iter()
+                .all(|&byte| byte == 0)
+        );
+        assert_eq!(off_dt_strings, off_dt_struct + size_dt_struct);
+        assert_eq!(off_dt_strings + size_dt_strings, blob.len());
+        assert_eq!(read_u32(&blob, off_dt_struct), FDT_BEGIN_NODE);
+        assert_eq!(read_u32(&blob, off_dt_strings - 4), FDT_END);
+        assert_eq!(&blob[off_dt_strings..], b"#address-cells\0compatible\0");
+    }
+
+    #[test]
+    fn fdt_string_dedup() {
+        let mut fdt = FdtBuilder::new();
+        assert_eq!(fdt.string_offset("compatible"), 0);
+        assert_eq!(fdt.string_offset("reg"), 11);
+        assert_eq!(fdt.string_offset("compatible"), 0);
+        assert_eq!(fdt.string_offset("reg"), 11);
+        // A prefix of an existing string is a string of its own.
+        assert_eq!(fdt.string_offset("re"), 15);
+        assert_eq!(fdt.strings, b"compatible\0reg\0re\0");
+    }
+
+    #[test]
+    fn isa_extensions_of_g() {
+        let (base, extensions) = isa_extensions("rv64gc_zicsr");
+        assert_eq!(base, "rv64i");
+        assert_eq!(
+            extensions,
+            ["i", "m", "a", "f", "d", "zicsr", "zifencei", "c"]
+        );
+    }
+
+    #[test]
+    fn isa_extensions_of_letters() {
+        let (base, extensions) = isa_extensions("RV32IMAC_Zba__zbb");
+        assert_eq!(base, "rv32i");
+        assert_eq!(extensions, ["i", "m", "a", "c", "zba", "zbb"]);
+    }
+}
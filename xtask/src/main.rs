@@ -5,6 +5,7 @@ use std::process::ExitCode;
 #[macro_use]
 mod utils;
 mod bench;
+mod dtb;
 mod logger;
 mod prototyper;
 mod test;
@@ -13,6 +14,7 @@ mod test;
 extern crate log;
 
 use crate::bench::BenchArg;
+use crate::dtb::DtbArg;
 use crate::prototyper::PrototyperArg;
 use crate::test::TestArg;
 
@@ -33,10 +35,12 @@ struct Cli {
 enum Cmd {
     /// Build and configure the RustSBI Prototyper bootloader.
     Prototyper(PrototyperArg),
-    /// Build test-kernel for the RustSBI Prototyper.
+    /// Build test-kernel for the RustSBI Prototyper, and optionally run it in QEMU.
     Test(TestArg),
     /// Build bench-kernel for the RustSBI Prototyper.
     Bench(BenchArg),
+    /// Generate a device tree blob from the board description of a configuration file.
+    Dtb(DtbArg),
 }
 
 fn main() -> ExitCode {
@@ -51,6 +55,7 @@ fn main() -> ExitCode {
         Cmd::Prototyper(arg) => prototyper::run(arg),
         Cmd::Test(arg) => test::run(arg),
         Cmd::Bench(arg) => bench::run(arg),
+        Cmd::Dtb(arg) => dtb::run(arg),
     };
 
     match result {
@@ -63,6 +68,7 @@ fn main() -> ExitCode {
                 Cmd::Prototyper(_) => "prototyper",
                 Cmd::Test(_) => "test",
                 Cmd::Bench(_) => "bench",
+                Cmd::Dtb(_) => "dtb",
             };
             error!("Task '{}' failed with exit code: {}", cmd_name, exit_status);
             ExitCode::FAILURE
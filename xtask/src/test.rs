@@ -1,23 +1,48 @@
 use std::{
+    collections::BTreeMap,
     env, fs,
+    io::{BufRead, BufReader},
     path::{Path, PathBuf},
-    process::{Command, ExitStatus},
+    process::{Command, ExitStatus, Stdio},
+    sync::mpsc::{self, RecvTimeoutError},
+    thread,
+    time::{Duration, Instant},
 };
 
 use clap::Args;
+use serde_json::Value;
 
-use crate::utils::cargo;
+use crate::utils::{CmdOptional, cargo};
 
 #[derive(Debug, Args, Clone)]
 pub struct TestArg {
     /// Package Prototyper and Test-Kernel into a single image
     #[clap(long, help = "Create a combined image with Prototyper and test kernel")]
     pub pack: bool,
+
+    /// Boot the test kernel in QEMU and check the results
+    #[clap(long, help = "Run the test kernel with Prototyper in QEMU")]
+    pub run: bool,
+
+    /// Prototyper firmware in jump mode used by `--run`
+    #[clap(long, env = "PROTOTYPER_BIOS_PATH")]
+    pub bios: Option<PathBuf>,
+
+    /// Seconds to wait before the test run is stopped
+    #[clap(long, default_value_t = 60)]
+    pub timeout: u64,
+
+    /// Number of harts in QEMU; HSM and SUSP need another hart for some cases
+    #[clap(long, default_value_t = 2)]
+    pub smp: usize,
 }
 
 const ARCH: &str = "riscv64imac-unknown-none-elf";
 const TEST_KERNEL_NAME: &str = "rustsbi-test-kernel";
 const PROTOTYPER_BIN: &str = "rustsbi-prototyper.bin";
+const PROTOTYPER_ARCH: &str = "riscv64gc-unknown-none-elf";
+const PROTOTYPER_JUMP_ELF: &str = "rustsbi-prototyper-jump.elf";
+const QEMU: &str = "qemu-system-riscv64";
 
 #[must_use]
 pub fn run(arg: &TestArg) -> Option<ExitStatus> {
@@ -26,7 +51,7 @@ pub fn run(arg: &TestArg) -> Option<ExitStatus> {
 
     // Build the test kernel
     info!("Building test kernel");
-    let build_status = build_test_kernel()?;
+    let build_status = build_test_kernel(arg.run)?;
     if !build_status.success() {
         error!("Failed to build test kernel");
         return Some(build_status);
@@ -51,15 +76,13 @@ pub fn run(arg: &TestArg) -> Option<ExitStatus> {
                         .join(format!("{}.itb", TEST_KERNEL_NAME))
                         .display()
                 );
-                return Some(status);
+                if !arg.run || !status.success() {
+                    return Some(status);
+                }
             }
             Err(err_msg) => {
                 error!("{}", err_msg);
-                // TODO cross-platform ExitStatus return value
-                #[cfg(unix)]
-                return Some(<ExitStatus as std::os::unix::process::ExitStatusExt>::from_raw(1));
-                #[cfg(not(unix))]
-                return None;
+                return failure();
             }
         }
     } else {
@@ -71,17 +94,30 @@ pub fn run(arg: &TestArg) -> Option<ExitStatus> {
         );
     }
 
+    if arg.run {
+        return run_in_qemu(arg, &current_dir, &target_dir);
+    }
+
     Some(exit_status)
 }
 
+// TODO cross-platform ExitStatus return value
+fn failure() -> Option<ExitStatus> {
+    #[cfg(unix)]
+    return Some(<ExitStatus as std::os::unix::process::ExitStatusExt>::from_raw(1));
+    #[cfg(not(unix))]
+    return None;
+}
+
 fn get_target_dir(current_dir: &Path) -> PathBuf {
     current_dir.join("target").join(ARCH).join("release")
 }
 
-fn build_test_kernel() -> Option<ExitStatus> {
+fn build_test_kernel(json: bool) -> Option<ExitStatus> {
     cargo::Cargo::new("build")
         .package(TEST_KERNEL_NAME)
         .target(ARCH)
+        .optional(json, |cargo| cargo.env("SBI_TESTING_FORMAT", "json"))
         .release()
         .status()
         .ok()
@@ -152,3 +188,229 @@ fn pack_image(current_dir: &Path, target_dir: &Path) -> Result<ExitStatus, Strin
 
     Ok(status)
 }
+
+fn run_in_qemu(arg: &TestArg, current_dir: &Path, target_dir: &Path) -> Option<ExitStatus> {
+    let bios = arg.bios.clone().unwrap_or_else(|| {
+        current_dir
+            .join("target")
+            .join(PROTOTYPER_ARCH)
+            .join("release")
+            .join(PROTOTYPER_JUMP_ELF)
+    });
+    if !bios.exists() {
+        error!(
+            "Prototyper firmware not found at '{}'\n\
+             Please run 'cargo prototyper --jump' first, or specify it with '--bios'.",
+            bios.display()
+        );
+        return failure();
+    }
+    let kernel = target_dir.join(format!("{}.bin", TEST_KERNEL_NAME));
+
+    info!("Running test kernel in QEMU, timeout {}s", arg.timeout);
+    let mut child = match Command::new(QEMU)
+        .args(["-machine", "virt", "-m", "256M", "-display", "none"])
+        .args(["-serial", "stdio", "-monitor", "none"])
+        .args(["-smp", &arg.smp.to_string()])
+        .arg("-bios")
+        .arg(&bios)
+        .arg("-kernel")
+        .arg(&kernel)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .spawn()
+    {
+        Ok(child) => child,
+        Err(e) => {
+            error!("Failed to execute {}: {}", QEMU, e);
+            return failure();
+        }
+    };
+
+    // Read serial output on another thread, so that the timeout applies on blocking reads
+    let stdout = child.stdout.take()?;
+    let (tx, rx) = mpsc::channel();
+    thread::spawn(move || {
+        for line in BufReader::new(stdout).lines() {
+            let Ok(line) = line else { break };
+            if tx.send(line).is_err() {
+                break;
+            }
+        }
+    });
+
+    let deadline = Instant::now() + Duration::from_secs(arg.timeout);
+    let mut summary = Summary::default();
+    loop {
+        let timeout = deadline.saturating_duration_since(Instant::now());
+        match rx.recv_timeout(timeout) {
+            Ok(line) => {
+                println!("{}", line.trim_end());
+                summary.parse(&line);
+            }
+            Err(RecvTimeoutError::Timeout) => {
+                error!("Test run timed out after {}s", arg.timeout);
+                let _ = child.kill();
+                summary.timed_out = true;
+                break;
+            }
+            Err(RecvTimeoutError::Disconnected) => break,
+        }
+    }
+    let status = child.wait().ok()?;
+
+    summary.print();
+    if summary.passed() {
+        Some(status)
+    } else {
+        failure()
+    }
+}
+
+/// Results of test suites parsed from `sbi-testing` JSON records.
+#[derive(Default)]
+struct Summary {
+    suites: BTreeMap<String, Outcome>,
+    end: Option<bool>,
+    timed_out: bool,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Default, Debug)]
+enum Outcome {
+    #[default]
+    Incomplete,
+    Passed,
+    Skipped,
+    Failed,
+}
+
+impl Summary {
+    /// Parse a line of serial output; lines other than JSON records are ignored.
+    fn parse(&mut self, line: &str) {
+        let Some(start) = line.find('{') else {
+            return;
+        };
+        let Ok(record) = serde_json::from_str::<Value>(line[start..].trim_end()) else {
+            return;
+        };
+        let event = record["event"].as_str().unwrap_or_default();
+        if event == "end" {
+            self.end = record["passed"].as_bool();
+            return;
+        }
+        let Some(suite) = record["suite"].as_str() else {
+            return;
+        };
+        let outcome = self.suites.entry(suite.to_string()).or_default();
+        match event {
+            "fail" => *outcome = Outcome::Failed,
+            "pass" if *outcome != Outcome::Failed => *outcome = Outcome::Passed,
+            "skip" if *outcome != Outcome::Failed => *outcome = Outcome::Skipped,
+            _ => {}
+        }
+    }
+
+    fn passed(&self) -> bool {
+        !self.timed_out
+            && self.end == Some(true)
+            && self
+                .suites
+                .values()
+                .all(|o| matches!(o, Outcome::Passed | Outcome::Skipped))
+    }
+
+    fn print(&self) {
+        let count = |outcome| self.suites.values().filter(|o| **o == outcome).count();
+        for (suite, outcome) in &self.suites {
+            match outcome {
+                Outcome::Passed => info!("{:>6}: passed", suite),
+                Outcome::Skipped => warn!("{:>6}: skipped", suite),
+                Outcome::Failed => error!("{:>6}: FAILED", suite),
+                Outcome::Incomplete => error!("{:>6}: INCOMPLETE", suite),
+            }
+        }
+        info!(
+            "{} passed, {} failed, {} skipped, {} incomplete",
+            count(Outcome::Passed),
+            count(Outcome::Failed),
+            count(Outcome::Skipped),
+            count(Outcome::Incomplete),
+        );
+        if self.timed_out {
+            error!("Test kernel did not finish before timeout");
+        } else if self.end.is_none() {
+            error!("Test kernel stopped without reporting the end of tests");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Outcome, Summary};
+
+    fn summary(lines: &[&str]) -> Summary {
+        let mut summary = Summary::default();
+        for line in lines {
+            summary.parse(line);
+        }
+        summary
+    }
+
+    #[test]
+    fn parse_pass() {
+        let summary = summary(&[
+            "[rustsbi-prototyper] booting",
+            r#"{"suite":"BASE","event":"begin","message":""}"#,
+            r#"[ INFO] {"suite":"BASE","event":"info","message":"spec version 2.0"}"#,
+            r#"{"suite":"BASE","event":"pass","message":""}"#,
+            r#"{"event":"end","passed":true}"#,
+        ]);
+        assert_eq!(summary.suites["BASE"], Outcome::Passed);
+        assert!(summary.passed());
+    }
+
+    #[test]
+    fn parse_fail() {
+        let summary = summary(&[
+            r#"{"suite":"HSM","event":"begin","message":""}"#,
+            r#"{"suite":"HSM","event":"fail","message":"hart 1 not started"}"#,
+            r#"{"suite":"HSM","event":"pass","message":""}"#,
+            r#"{"event":"end","passed":true}"#,
+        ]);
+        assert_eq!(summary.suites["HSM"], Outcome::Failed);
+        assert!(!summary.passed());
+    }
+
+    #[test]
+    fn parse_skip() {
+        let summary = summary(&[
+            r#"{"suite":"DBTR","event":"begin","message":""}"#,
+            r#"{"suite":"DBTR","event":"skip","message":"extension not exist"}"#,
+            r#"{"event":"end","passed":true}"#,
+        ]);
+        assert_eq!(summary.suites["DBTR"], Outcome::Skipped);
+        assert!(summary.passed());
+    }
+
+    #[test]
+    fn parse_incomplete() {
+        let summary = summary(&[
+            r#"{"suite":"SRST","event":"begin","message":""}"#,
+            r#"{"suite":"SRST","event":"info","message":"requesting warm reboot"}"#,
+            r#"{"event":"end","passed":true}"#,
+        ]);
+        assert_eq!(summary.suites["SRST"], Outcome::Incomplete);
+        assert!(!summary.passed());
+    }
+
+    #[test]
+    fn parse_truncated() {
+        let summary = summary(&[
+            r#"{"suite":"SUSP","event":"begin","message":""}"#,
+            r#"{"suite":"SUSP","event":"pa"#,
+        ]);
+        assert_eq!(summary.suites["SUSP"], Outcome::Incomplete);
+        assert_eq!(summary.end, None);
+        assert!(!summary.passed());
+    }
+}